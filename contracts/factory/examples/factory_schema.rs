@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use palomadex::factory::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use palomadex::factory::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         query: QueryMsg,
         execute: ExecuteMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -3,31 +3,52 @@ use std::collections::HashSet;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply, ReplyOn, Response,
-    StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
+    attr, instantiate2_address, to_json_binary, wasm_execute, Addr, Binary, CosmosMsg, Deps,
+    DepsMut, Env, MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, SubMsg,
+    SubMsgResponse, SubMsgResult, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use cw_utils::parse_instantiate_response_data;
 use itertools::Itertools;
 
-use palomadex::asset::{addr_opt_validate, AssetInfo, PairInfo};
-use palomadex::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use cw20::Cw20ExecuteMsg;
+use palomadex::asset::{
+    addr_opt_validate, validate_native_denom, Asset, AssetInfo, CoinsExt, PairInfo,
+};
+use palomadex::common::{
+    claim_ownership, drop_ownership_proposal, grant_role, has_role, propose_new_owner,
+    query_role_members, renounce_role, revoke_role, Role,
+};
 use palomadex::factory::{
-    Config, ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType,
-    PairsResponse, QueryMsg,
+    Config, ConfigResponse, DenomMatcher, ExecuteMsg, FeeInfoResponse, InstantiateMsg, ListingRule,
+    MigrateMsg, PairConfig, PairFeeOverride, PairType, PairsResponse, PairsToMigrateResponse,
+    QueryMsg, QueuedChange, TimelockedChange,
 };
-use palomadex::pair::InstantiateMsg as PairInstantiateMsg;
+use palomadex::pair::{ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg};
 
 use crate::error::ContractError;
+use crate::migration::migrate_config_v010;
 use crate::querier::query_pair_info;
 use crate::state::{
-    check_asset_infos, pair_key, read_pairs, TmpPairInfo, CONFIG, OWNERSHIP_PROPOSAL, PAIRS,
-    PAIR_CONFIGS, TMP_PAIR_INFO,
+    check_asset_infos, check_listing_rules, index_pair, next_pair_salt, pair_key, read_pairs,
+    read_pairs_by_asset, read_pairs_of_type, remove_pair_to_migrate, typed_pair_key, unindex_pair,
+    PairsMigration, TmpPairInfo, TmpProvideInfo, ALL_PAIRS_PAUSED, CONFIG, DEFAULT_LIMIT,
+    LAST_CHANGE_ID, LISTING_RULES, MAX_LIMIT, OWNERSHIP_PROPOSAL, PAIRS, PAIRS_MIGRATION,
+    PAIRS_TO_MIGRATE, PAIR_CONFIGS, PAIR_FEE_OVERRIDES, PAIR_NONCES, PAIR_TYPES, PAUSED_PAIRS,
+    QUEUED_CHANGES, ROLES, TMP_PAIR_INFO,
 };
 
 /// A `reply` call code ID used in a sub-message.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
 
+/// The minimum timelock delay is 1 day
+const MIN_TIMELOCK_DELAY: u64 = 86_400;
+/// The maximum timelock delay is 30 days
+const MAX_TIMELOCK_DELAY: u64 = 2_592_000;
+/// Queued changes can be executed during 14 days after their ETA
+const TIMELOCK_GRACE_PERIOD: u64 = 1_209_600;
+
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:palomadex-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -42,6 +63,8 @@ pub fn instantiate(
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    check_timelock_delay(msg.timelock_delay)?;
+
     let mut config = Config {
         owner: deps.api.addr_validate(&msg.owner)?,
         token_code_id: msg.token_code_id,
@@ -49,8 +72,9 @@ pub fn instantiate(
         generator_address: None,
         whitelist_code_id: msg.whitelist_code_id,
         coin_registry_address: deps.api.addr_validate(&msg.coin_registry_address)?,
-        bonding_curve_factory_address: None,
-        pusd_denom: None,
+        guardian: None,
+        timelock_delay: msg.timelock_delay,
+        canceller: addr_opt_validate(deps.api, &msg.canceller)?,
     };
 
     config.generator_address = addr_opt_validate(deps.api, &msg.generator_address)?;
@@ -75,6 +99,7 @@ pub fn instantiate(
         PAIR_CONFIGS.save(deps.storage, pc.pair_type.to_string(), pc)?;
     }
     CONFIG.save(deps.storage, &config)?;
+    LISTING_RULES.save(deps.storage, &vec![])?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
@@ -92,22 +117,25 @@ pub struct UpdateConfig {
     /// CW1 whitelist contract code id used to store 3rd party staking rewards
     whitelist_code_id: Option<u64>,
     coin_registry_address: Option<String>,
-    bonding_curve_factory_address: Option<String>,
-    pusd_denom: Option<String>,
+    /// Address allowed to pause pairs
+    guardian: Option<String>,
+    /// The minimum time between proposing a timelocked change and executing it
+    timelock_delay: Option<u64>,
+    /// Address allowed to cancel timelocked changes
+    canceller: Option<String>,
 }
 
 /// Exposes all the execute functions available in the contract.
 /// * **msg** is an object of type [`ExecuteMsg`].
 ///
 /// ## Variants
-/// * **ExecuteMsg::UpdateConfig {
-///             token_code_id,
-///             fee_address,
-///             generator_address,
-///         }** Updates general contract parameters.
+/// * **ExecuteMsg::ProposeChange { change, eta }** Queues a timelocked change, which is either
+///   an update of the general contract parameters, an update of a pair type configuration,
+///   an `UpdateConfig` message forwarded to a pair or a change of a pair's fee override.
 ///
-/// * **ExecuteMsg::UpdatePairConfig { config }** Updates a pair type
-/// * configuration or creates a new pair type if a [`Custom`] name is used (which hasn't been used before).
+/// * **ExecuteMsg::ExecuteChange { id }** Applies a queued change after its ETA.
+///
+/// * **ExecuteMsg::CancelChange { id }** Removes a queued change without applying it.
 ///
 /// * **ExecuteMsg::CreatePair {
 ///             pair_type,
@@ -115,9 +143,29 @@ pub struct UpdateConfig {
 ///             init_params,
 ///         }** Creates a new pair with the specified input parameters.
 ///
-/// * **ExecuteMsg::Deregister { asset_infos }** Removes an existing pair from the factory.
+/// * **ExecuteMsg::CreatePairAndProvide { pair_type, asset_infos, init_params, assets, receiver }** Creates a new pair
+///   and provides its initial liquidity in the same transaction.
+///
+/// * **ExecuteMsg::Deregister { asset_infos, pair_type }** Removes an existing pair from the factory.
 /// * The asset information is for the assets that are traded in the pair.
 ///
+/// * **ExecuteMsg::SetListingRule { denom, allowed_senders }** Adds or replaces a rule of the listing policy.
+///
+/// * **ExecuteMsg::RemoveListingRule { denom }** Removes a rule of the listing policy.
+///
+/// * **ExecuteMsg::MigratePairs { pair_type, new_code_id, msg, limit }** Migrates registered pairs
+///   of the specified type to a new code ID in batches, once the migration is approved through the timelock.
+///
+/// * **ExecuteMsg::PausePairs { pairs }** Pauses the specified pairs or all pairs.
+///
+/// * **ExecuteMsg::UnpausePairs { pairs }** Unpauses the specified pairs or lifts every pause.
+///
+/// * **ExecuteMsg::GrantRole { role, account }** Grants a role to an account.
+///
+/// * **ExecuteMsg::RevokeRole { role, account }** Revokes a role from an account.
+///
+/// * **ExecuteMsg::RenounceRole { role }** Gives up a role held by the sender.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a request to change contract ownership.
 ///
 /// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change contract ownership.
@@ -131,34 +179,62 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig {
-            token_code_id,
-            fee_address,
-            generator_address,
-            whitelist_code_id,
-            coin_registry_address,
-            bonding_curve_factory_address,
-            pusd_denom,
-        } => execute_update_config(
+        ExecuteMsg::ProposeChange { change, eta } => {
+            execute_propose_change(deps, env, info, change, eta)
+        }
+        ExecuteMsg::ExecuteChange { id } => execute_queued_change(deps, env, info, id),
+        ExecuteMsg::CancelChange { id } => execute_cancel_change(deps, info, id),
+        ExecuteMsg::CreatePair {
+            pair_type,
+            asset_infos,
+            init_params,
+        } => execute_create_pair(deps, info, env, pair_type, asset_infos, init_params, None),
+        ExecuteMsg::CreatePairAndProvide {
+            pair_type,
+            asset_infos,
+            init_params,
+            assets,
+            receiver,
+        } => execute_create_pair_and_provide(
             deps,
             info,
-            UpdateConfig {
-                token_code_id,
-                fee_address,
-                generator_address,
-                whitelist_code_id,
-                coin_registry_address,
-                bonding_curve_factory_address,
-                pusd_denom,
-            },
-        ),
-        ExecuteMsg::UpdatePairConfig { config } => execute_update_pair_config(deps, info, config),
-        ExecuteMsg::CreatePair {
+            env,
             pair_type,
             asset_infos,
             init_params,
-        } => execute_create_pair(deps, info, env, pair_type, asset_infos, init_params),
-        ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
+            assets,
+            receiver,
+        ),
+        ExecuteMsg::Deregister {
+            asset_infos,
+            pair_type,
+        } => deregister(deps, info, asset_infos, pair_type),
+        ExecuteMsg::SetListingRule {
+            denom,
+            allowed_senders,
+        } => execute_set_listing_rule(deps, info, denom, allowed_senders),
+        ExecuteMsg::RemoveListingRule { denom } => execute_remove_listing_rule(deps, info, denom),
+        ExecuteMsg::MigratePairs {
+            pair_type,
+            new_code_id,
+            msg,
+            limit,
+        } => execute_migrate_pairs(deps, info, pair_type, new_code_id, msg, limit),
+        ExecuteMsg::PausePairs { pairs } => execute_set_pairs_paused(deps, info, pairs, true),
+        ExecuteMsg::UnpausePairs { pairs } => execute_set_pairs_paused(deps, info, pairs, false),
+        ExecuteMsg::GrantRole { role, account } => {
+            let config = CONFIG.load(deps.storage)?;
+
+            grant_role(deps, info, config.owner, role, account, ROLES).map_err(Into::into)
+        }
+        ExecuteMsg::RevokeRole { role, account } => {
+            let config = CONFIG.load(deps.storage)?;
+
+            revoke_role(deps, info, config.owner, role, account, ROLES).map_err(Into::into)
+        }
+        ExecuteMsg::RenounceRole { role } => {
+            renounce_role(deps, info, role, ROLES).map_err(Into::into)
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -198,19 +274,13 @@ pub fn execute(
 /// * **param** is an object of type [`UpdateConfig`] that contains the parameters to update.
 ///
 /// ## Executor
-/// Only the owner can execute this.
+/// Only executed through the timelock queue.
 pub fn execute_update_config(
     deps: DepsMut,
-    info: MessageInfo,
     param: UpdateConfig,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
-    // Permission check
-    if info.sender != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
-
     if let Some(fee_address) = param.fee_address {
         // Validate address format
         config.fee_address = Some(deps.api.addr_validate(&fee_address)?);
@@ -233,13 +303,17 @@ pub fn execute_update_config(
         config.coin_registry_address = deps.api.addr_validate(&coin_registry_address)?;
     }
 
-    if let Some(bonding_curve_factory_address) = param.bonding_curve_factory_address {
-        config.bonding_curve_factory_address =
-            Some(deps.api.addr_validate(&bonding_curve_factory_address)?);
+    if let Some(guardian) = param.guardian {
+        config.guardian = Some(deps.api.addr_validate(&guardian)?);
     }
 
-    if let Some(pusd_denom) = param.pusd_denom {
-        config.pusd_denom = Some(pusd_denom);
+    if let Some(timelock_delay) = param.timelock_delay {
+        check_timelock_delay(timelock_delay)?;
+        config.timelock_delay = timelock_delay;
+    }
+
+    if let Some(canceller) = param.canceller {
+        config.canceller = Some(deps.api.addr_validate(&canceller)?);
     }
 
     CONFIG.save(deps.storage, &config)?;
@@ -247,24 +321,231 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
-/// Updates a pair type's configuration.
+/// Checks that the timelock delay is within [`MIN_TIMELOCK_DELAY`] and [`MAX_TIMELOCK_DELAY`].
+pub(crate) fn check_timelock_delay(timelock_delay: u64) -> Result<(), ContractError> {
+    if timelock_delay < MIN_TIMELOCK_DELAY {
+        return Err(ContractError::TimelockDelayTooShort {
+            min: MIN_TIMELOCK_DELAY,
+        });
+    }
+    if timelock_delay > MAX_TIMELOCK_DELAY {
+        return Err(ContractError::TimelockDelayTooLong {
+            max: MAX_TIMELOCK_DELAY,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns whether `sender` may queue and execute `change`.
+/// Fee managers may only change the fee overrides of pairs.
+fn can_manage_change(
+    deps: Deps,
+    config: &Config,
+    sender: &Addr,
+    change: &TimelockedChange,
+) -> bool {
+    *sender == config.owner
+        || (change.is_fee_change() && has_role(deps.storage, &Role::FeeManager, sender, ROLES))
+}
+
+/// Queues a timelocked change which can be executed at `eta` or later.
 ///
-/// * **pair_config** is an object of type [`PairConfig`] that contains the pair type information to update.
+/// * **change** is the queued change.
+///
+/// * **eta** is the time (in seconds) after which the change can be executed. It must be at least
+///   `timelock_delay` seconds in the future.
 ///
 /// ## Executor
-/// Only the owner can execute this.
-pub fn execute_update_pair_config(
+/// Only the owner can execute this. Fee managers can queue changes of pair fee overrides.
+pub fn execute_propose_change(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    pair_config: PairConfig,
+    change: TimelockedChange,
+    eta: u64,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     // Permission check
-    if info.sender != config.owner {
+    if !can_manage_change(deps.as_ref(), &config, &info.sender, &change) {
         return Err(ContractError::Unauthorized {});
     }
 
+    let earliest = env.block.time.seconds() + config.timelock_delay;
+    if eta < earliest {
+        return Err(ContractError::TimelockEtaTooEarly { earliest });
+    }
+
+    // Changes of a specific pair can only be queued for registered pairs.
+    // The pair is checked again when the change is executed, as it can be deregistered meanwhile
+    if let TimelockedChange::UpdatePair { pair_addr, .. }
+    | TimelockedChange::SetPairFeeOverride { pair_addr, .. } = &change
+    {
+        check_registered_pair(deps.as_ref(), &deps.api.addr_validate(pair_addr)?)?;
+    }
+
+    let id = LAST_CHANGE_ID.may_load(deps.storage)?.unwrap_or_default() + 1;
+    LAST_CHANGE_ID.save(deps.storage, &id)?;
+    QUEUED_CHANGES.save(deps.storage, id, &QueuedChange { id, change, eta })?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_change"),
+        attr("id", id.to_string()),
+        attr("eta", eta.to_string()),
+    ]))
+}
+
+/// Applies the queued change with the identifier `id`.
+/// Changes can be executed during [`TIMELOCK_GRACE_PERIOD`] seconds after their ETA.
+///
+/// ## Executor
+/// Only the owner can execute this. Fee managers can execute changes of pair fee overrides.
+pub fn execute_queued_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let queued = QUEUED_CHANGES
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::QueuedChangeNotFound { id })?;
+
+    // Permission check
+    if !can_manage_change(deps.as_ref(), &config, &info.sender, &queued.change) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let now = env.block.time.seconds();
+    if now < queued.eta {
+        return Err(ContractError::TimelockNotReady {
+            id,
+            eta: queued.eta,
+        });
+    }
+    let expired_at = queued.eta + TIMELOCK_GRACE_PERIOD;
+    if now > expired_at {
+        return Err(ContractError::TimelockExpired { id, expired_at });
+    }
+
+    QUEUED_CHANGES.remove(deps.storage, id);
+
+    let response = match queued.change {
+        TimelockedChange::UpdateConfig {
+            token_code_id,
+            fee_address,
+            generator_address,
+            whitelist_code_id,
+            coin_registry_address,
+            guardian,
+            timelock_delay,
+            canceller,
+        } => execute_update_config(
+            deps,
+            UpdateConfig {
+                token_code_id,
+                fee_address,
+                generator_address,
+                whitelist_code_id,
+                coin_registry_address,
+                guardian,
+                timelock_delay,
+                canceller,
+            },
+        )?,
+        TimelockedChange::UpdatePairConfig { config } => execute_update_pair_config(deps, config)?,
+        TimelockedChange::UpdatePair { pair_addr, params } => {
+            let pair_addr = deps.api.addr_validate(&pair_addr)?;
+            check_registered_pair(deps.as_ref(), &pair_addr)?;
+
+            Response::new()
+                .add_message(wasm_execute(
+                    &pair_addr,
+                    &PairExecuteMsg::UpdateConfig { params },
+                    vec![],
+                )?)
+                .add_attributes(vec![
+                    attr("action", "update_pair"),
+                    attr("pair_contract_addr", pair_addr),
+                ])
+        }
+        TimelockedChange::SetPairFeeOverride {
+            pair_addr,
+            total_fee_bps,
+            maker_fee_bps,
+        } => execute_set_pair_fee_override(
+            deps,
+            pair_addr,
+            PairFeeOverride {
+                total_fee_bps,
+                maker_fee_bps,
+            },
+        )?,
+        TimelockedChange::RemovePairFeeOverride { pair_addr } => {
+            execute_remove_pair_fee_override(deps, pair_addr)?
+        }
+        TimelockedChange::MigratePairs {
+            pair_type,
+            new_code_id,
+            msg,
+        } => execute_approve_pairs_migration(
+            deps,
+            PairsMigration {
+                pair_type,
+                new_code_id,
+                msg,
+            },
+        )?,
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "execute_change"),
+            attr("id", id.to_string()),
+        ])
+        .add_submessages(response.messages)
+        .add_attributes(response.attributes))
+}
+
+/// Removes the queued change with the identifier `id` without applying it.
+///
+/// ## Executor
+/// Only the owner or the canceller can execute this.
+pub fn execute_cancel_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner && Some(&info.sender) != config.canceller.as_ref() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !QUEUED_CHANGES.has(deps.storage, id) {
+        return Err(ContractError::QueuedChangeNotFound { id });
+    }
+    QUEUED_CHANGES.remove(deps.storage, id);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_change"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+/// Updates a pair type's configuration.
+///
+/// * **pair_config** is an object of type [`PairConfig`] that contains the pair type information to update.
+///
+/// ## Executor
+/// Only executed through the timelock queue.
+pub fn execute_update_pair_config(
+    deps: DepsMut,
+    pair_config: PairConfig,
+) -> Result<Response, ContractError> {
     // Validate total and maker fee bps
     if !pair_config.valid_fee_bps() {
         return Err(ContractError::PairConfigInvalidFeeBps {});
@@ -279,6 +560,307 @@ pub fn execute_update_pair_config(
     Ok(Response::new().add_attribute("action", "update_pair_config"))
 }
 
+/// Sets fees for a specific pair which are used instead of its pair type fees.
+///
+/// * **pair_addr** is the pair contract address.
+///
+/// * **fee_override** is an object of type [`PairFeeOverride`] that contains the pair fees.
+///
+/// The pair must be registered in the factory.
+///
+/// ## Executor
+/// Only executed through the timelock queue.
+pub fn execute_set_pair_fee_override(
+    deps: DepsMut,
+    pair_addr: String,
+    fee_override: PairFeeOverride,
+) -> Result<Response, ContractError> {
+    // Validate total and maker fee bps
+    if !fee_override.valid_fee_bps() {
+        return Err(ContractError::PairConfigInvalidFeeBps {});
+    }
+
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    check_registered_pair(deps.as_ref(), &pair_addr)?;
+    PAIR_FEE_OVERRIDES.save(deps.storage, &pair_addr, &fee_override)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_pair_fee_override"),
+        attr("pair_contract_addr", pair_addr),
+        attr("total_fee_bps", fee_override.total_fee_bps.to_string()),
+        attr("maker_fee_bps", fee_override.maker_fee_bps.to_string()),
+    ]))
+}
+
+/// Removes the fee override of a specific pair, so it uses its pair type fees again.
+///
+/// * **pair_addr** is the pair contract address.
+///
+/// ## Executor
+/// Only executed through the timelock queue.
+pub fn execute_remove_pair_fee_override(
+    deps: DepsMut,
+    pair_addr: String,
+) -> Result<Response, ContractError> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+    PAIR_FEE_OVERRIDES.remove(deps.storage, &pair_addr);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_pair_fee_override"),
+        attr("pair_contract_addr", pair_addr),
+    ]))
+}
+
+/// Adds a rule to the listing policy or replaces the rule with the same `denom` matcher.
+///
+/// * **denom** is an object of type [`DenomMatcher`] that describes the native denoms the rule applies to.
+///
+/// * **allowed_senders** are the senders that may pair matching assets without restrictions.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_set_listing_rule(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: DenomMatcher,
+    allowed_senders: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match &denom {
+        DenomMatcher::Exact(denom) => validate_native_denom(denom)?,
+        DenomMatcher::TokenFactoryCreator(creator) => {
+            deps.api.addr_validate(creator)?;
+        }
+    }
+
+    let allowed_senders = allowed_senders
+        .iter()
+        .map(|sender| deps.api.addr_validate(sender))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut rules = LISTING_RULES.load(deps.storage)?;
+    rules.retain(|rule| rule.denom != denom);
+    rules.push(ListingRule {
+        denom: denom.clone(),
+        allowed_senders: allowed_senders.clone(),
+    });
+    LISTING_RULES.save(deps.storage, &rules)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_listing_rule"),
+        attr("denom", denom.to_string()),
+        attr("allowed_senders", allowed_senders.iter().join(",")),
+    ]))
+}
+
+/// Removes the rule with the `denom` matcher from the listing policy.
+///
+/// * **denom** is the matcher of the rule to remove.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_remove_listing_rule(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: DenomMatcher,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut rules = LISTING_RULES.load(deps.storage)?;
+    let rules_len = rules.len();
+    rules.retain(|rule| rule.denom != denom);
+    if rules.len() == rules_len {
+        return Err(ContractError::ListingRuleNotFound {});
+    }
+    LISTING_RULES.save(deps.storage, &rules)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_listing_rule"),
+        attr("denom", denom.to_string()),
+    ]))
+}
+
+/// Approves the migration of registered pairs of `pair_type` to `new_code_id`.
+///
+/// All registered pairs of `pair_type` are collected into [`PAIRS_TO_MIGRATE`],
+/// they are migrated afterwards by [`execute_migrate_pairs`] calls.
+///
+/// ## Executor
+/// Only executed through the timelock queue.
+pub fn execute_approve_pairs_migration(
+    deps: DepsMut,
+    migration: PairsMigration,
+) -> Result<Response, ContractError> {
+    if let Some(pending) = PAIRS_MIGRATION.may_load(deps.storage)? {
+        return Err(ContractError::MigrationInProgress {
+            pair_type: pending.pair_type.to_string(),
+        });
+    }
+
+    let pairs = read_pairs_of_type(deps.as_ref(), &migration.pair_type)?;
+    PAIRS_TO_MIGRATE.save(deps.storage, &pairs)?;
+    PAIRS_MIGRATION.save(deps.storage, &migration)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_pairs_migration"),
+        attr("pair_type", migration.pair_type.to_string()),
+        attr("new_code_id", migration.new_code_id.to_string()),
+        attr("pending_pairs", pairs.len().to_string()),
+    ]))
+}
+
+/// Migrates registered pairs of `pair_type` to `new_code_id`.
+///
+/// The migration must be approved by a timelocked `MigratePairs` change with the same `pair_type`,
+/// `new_code_id` and `msg` first. Every call migrates up to `limit` of the approved pairs,
+/// so large sets of pairs can be migrated over several transactions.
+///
+/// * **msg** is the binary serialised migration message sent to every pair.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_migrate_pairs(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_type: PairType,
+    new_code_id: u64,
+    msg: Binary,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let migration = PairsMigration {
+        pair_type,
+        new_code_id,
+        msg,
+    };
+
+    match PAIRS_MIGRATION.may_load(deps.storage)? {
+        None => {
+            return Err(ContractError::MigrationNotApproved {
+                pair_type: migration.pair_type.to_string(),
+            })
+        }
+        Some(pending) if pending.pair_type != migration.pair_type => {
+            return Err(ContractError::MigrationInProgress {
+                pair_type: pending.pair_type.to_string(),
+            })
+        }
+        Some(pending) if pending != migration => {
+            return Err(ContractError::MigrationMismatch {
+                pair_type: pending.pair_type.to_string(),
+                new_code_id: pending.new_code_id,
+            })
+        }
+        Some(_) => {}
+    }
+    let mut pairs = PAIRS_TO_MIGRATE.may_load(deps.storage)?.unwrap_or_default();
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let batch = pairs.drain(..limit.min(pairs.len())).collect_vec();
+
+    if pairs.is_empty() {
+        PAIRS_TO_MIGRATE.remove(deps.storage);
+        PAIRS_MIGRATION.remove(deps.storage);
+    } else {
+        PAIRS_TO_MIGRATE.save(deps.storage, &pairs)?;
+        PAIRS_MIGRATION.save(deps.storage, &migration)?;
+    }
+
+    let messages = batch
+        .iter()
+        .map(|pair_addr| -> CosmosMsg {
+            WasmMsg::Migrate {
+                contract_addr: pair_addr.to_string(),
+                new_code_id: migration.new_code_id,
+                msg: migration.msg.clone(),
+            }
+            .into()
+        })
+        .collect_vec();
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "migrate_pairs"),
+        attr("pair_type", migration.pair_type.to_string()),
+        attr("migrated_pairs", batch.iter().join(",")),
+        attr("pending_pairs", pairs.len().to_string()),
+    ]))
+}
+
+/// Pauses or unpauses swaps and liquidity provision in pairs.
+///
+/// * **pairs** are the pair contract addresses to update. If not set, the pause of all pairs is
+///   updated, which is independent of the pauses of individual pairs.
+///
+/// * **paused** is whether the pairs are paused or unpaused.
+///
+/// ## Executor
+/// The owner or the guardian can pause pairs, only the owner can unpause them.
+pub fn execute_set_pairs_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    pairs: Option<Vec<String>>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check. The guardian is only trusted to stop trading, not to resume it
+    let is_guardian = paused && Some(&info.sender) == config.guardian.as_ref();
+    if info.sender != config.owner && !is_guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let action = if paused {
+        "pause_pairs"
+    } else {
+        "unpause_pairs"
+    };
+
+    let pairs = match pairs {
+        Some(pairs) => pairs,
+        None => {
+            ALL_PAIRS_PAUSED.save(deps.storage, &paused)?;
+
+            return Ok(
+                Response::new().add_attributes(vec![attr("action", action), attr("pairs", "all")])
+            );
+        }
+    };
+
+    let pairs = pairs
+        .iter()
+        .map(|pair| deps.api.addr_validate(pair))
+        .collect::<StdResult<Vec<_>>>()?;
+    for pair in &pairs {
+        if paused {
+            PAUSED_PAIRS.save(deps.storage, pair, &())?;
+        } else {
+            PAUSED_PAIRS.remove(deps.storage, pair);
+        }
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", action),
+        attr("pairs", pairs.iter().join(",")),
+    ]))
+}
+
 /// Creates a new pair of `pair_type` with the assets specified in `asset_infos`.
 ///
 /// * **pair_type** is the pair type of the newly created pair.
@@ -286,6 +868,8 @@ pub fn execute_update_pair_config(
 /// * **asset_infos** is a vector with assets for which we create a pair.
 ///
 /// * **init_params** These are packed params used for custom pair types that need extra data to be instantiated.
+///
+/// * **provide** is the initial liquidity which is provided to the pair in the instantiation reply.
 pub fn execute_create_pair(
     deps: DepsMut,
     info: MessageInfo,
@@ -293,42 +877,16 @@ pub fn execute_create_pair(
     pair_type: PairType,
     asset_infos: Vec<AssetInfo>,
     init_params: Option<Binary>,
+    provide: Option<TmpProvideInfo>,
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &asset_infos)?;
 
     let config = CONFIG.load(deps.storage)?;
-    let mut auth_flag: bool = true;
-    if let Some(bonding_curve_factory_address) = config.bonding_curve_factory_address {
-        if !asset_infos.iter().all(|asset_info| {
-            if let AssetInfo::NativeToken { denom } = asset_info {
-                if info.sender != bonding_curve_factory_address
-                    && denom.contains(bonding_curve_factory_address.to_string().as_str())
-                {
-                    if auth_flag {
-                        auth_flag = false;
-                        return true;
-                    } else {
-                        return false;
-                    }
-                }
-                if let Some(pusd_denom) = &config.pusd_denom {
-                    if denom.contains(pusd_denom) {
-                        if auth_flag {
-                            auth_flag = false;
-                            return true;
-                        } else {
-                            return false;
-                        }
-                    }
-                }
-            }
-            true
-        }) {
-            return Err(ContractError::Unauthorized {});
-        }
-    }
+    let listing_rules = LISTING_RULES.load(deps.storage)?;
+    check_listing_rules(&listing_rules, &info.sender, &asset_infos)?;
 
-    if PAIRS.has(deps.storage, &pair_key(&asset_infos)) {
+    let pair_key = registry_key(deps.as_ref(), &asset_infos, Some(&pair_type))?;
+    if PAIRS.has(deps.storage, &pair_key) {
         return Err(ContractError::PairWasCreated {});
     }
 
@@ -337,7 +895,11 @@ pub fn execute_create_pair(
         .load(deps.storage, pair_type.to_string())
         .map_err(|_| ContractError::PairConfigNotFound {})?;
 
-    if pair_config.permissioned && info.sender != config.owner {
+    // Permissioned pair types can only be created by the owner or a lister
+    if pair_config.permissioned
+        && info.sender != config.owner
+        && !has_role(deps.storage, &Role::Lister, &info.sender, ROLES)
+    {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -346,13 +908,47 @@ pub fn execute_create_pair(
         return Err(ContractError::PairConfigDisabled {});
     }
 
-    let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    let salt = next_pair_salt(deps.storage, &asset_infos, &pair_type)?;
+    let pair_addr = predict_pair_address(deps.as_ref(), &env, pair_config.code_id, &salt)?;
+    PAIR_NONCES.update(
+        deps.storage,
+        &typed_pair_key(&asset_infos, &pair_type),
+        |nonce| -> StdResult<_> { Ok(nonce.unwrap_or_default() + 1) },
+    )?;
+
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            pair_addr,
+            asset_infos: asset_infos.clone(),
+            pair_type,
+            provide: provide.clone(),
+        },
+    )?;
+
+    // cw20 tokens to provide are held by the factory until the pair is instantiated
+    let transfer_msgs = provide
+        .iter()
+        .flat_map(|provide| &provide.assets)
+        .filter_map(|asset| match &asset.info {
+            AssetInfo::Token { contract_addr } => Some(wasm_execute(
+                contract_addr,
+                &Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: asset.amount,
+                },
+                vec![],
+            )),
+            AssetInfo::NativeToken { .. } => None,
+        })
+        .collect::<StdResult<Vec<_>>>()?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         id: INSTANTIATE_PAIR_REPLY_ID,
-        msg: WasmMsg::Instantiate {
-            admin: Some(config.owner.to_string()),
+        msg: WasmMsg::Instantiate2 {
+            admin: Some(env.contract.address.to_string()),
             code_id: pair_config.code_id,
             msg: to_json_binary(&PairInstantiateMsg {
                 asset_infos: asset_infos.clone(),
@@ -362,6 +958,7 @@ pub fn execute_create_pair(
             })?,
             funds: vec![],
             label: "Palomadex pair".to_string(),
+            salt: salt.into(),
         }
         .into(),
         gas_limit: None,
@@ -369,6 +966,7 @@ pub fn execute_create_pair(
     }];
 
     Ok(Response::new()
+        .add_messages(transfer_msgs)
         .add_submessages(sub_msg)
         .add_attributes(vec![
             attr("action", "create_pair"),
@@ -376,6 +974,97 @@ pub fn execute_create_pair(
         ]))
 }
 
+/// Returns the address of the pair instantiated by the factory with `code_id` and `salt`,
+/// which is calculated by [`next_pair_salt`].
+fn predict_pair_address(deps: Deps, env: &Env, code_id: u64, salt: &[u8]) -> StdResult<Addr> {
+    let checksum = deps.querier.query_wasm_code_info(code_id)?.checksum;
+    let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let address = instantiate2_address(checksum.as_slice(), &creator, salt)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    deps.api.addr_humanize(&address)
+}
+
+/// Creates a new pair of `pair_type` with the assets specified in `asset_infos` and provides `assets` as its
+/// initial liquidity, so that nobody can trade against the pair before its first deposit.
+///
+/// * **assets** are the assets to provide. Native coins must be sent along with the message,
+///   the factory must be allowed to transfer cw20 tokens from the sender.
+///
+/// * **receiver** is the receiver of LP tokens. The sender is used if not set.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_pair_and_provide(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    pair_type: PairType,
+    asset_infos: Vec<AssetInfo>,
+    init_params: Option<Binary>,
+    assets: Vec<Asset>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    info.funds
+        .assert_coins_properly_sent(&assets, &asset_infos)?;
+
+    let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
+    let provide = TmpProvideInfo {
+        assets,
+        funds: info.funds.clone(),
+        receiver,
+    };
+
+    execute_create_pair(
+        deps,
+        info,
+        env,
+        pair_type,
+        asset_infos,
+        init_params,
+        Some(provide),
+    )
+}
+
+/// Returns the registry key of the pool of `pair_type` for the assets in `asset_infos`.
+/// The first pool created for a set of assets is stored under [`pair_key`], any pool of
+/// another type for the same assets is stored under [`typed_pair_key`].
+///
+/// If `pair_type` is not set, the key of the first pool is returned.
+fn registry_key(
+    deps: Deps,
+    asset_infos: &[AssetInfo],
+    pair_type: Option<&PairType>,
+) -> StdResult<Vec<u8>> {
+    let key = pair_key(asset_infos);
+    let Some(pair_type) = pair_type else {
+        return Ok(key);
+    };
+
+    let typed_key = typed_pair_key(asset_infos, pair_type);
+    if PAIRS.has(deps.storage, &typed_key) {
+        return Ok(typed_key);
+    }
+
+    match PAIR_TYPES.may_load(deps.storage, &key)? {
+        Some(original_type) if original_type != *pair_type => Ok(typed_key),
+        _ => Ok(key),
+    }
+}
+
+/// Checks that `pair_addr` is the pair registered in the factory for its assets and pair type.
+fn check_registered_pair(deps: Deps, pair_addr: &Addr) -> Result<(), ContractError> {
+    let not_registered = || ContractError::PairNotRegistered {
+        pair_addr: pair_addr.to_string(),
+    };
+
+    let pair_info = query_pair_info(&deps.querier, pair_addr).map_err(|_| not_registered())?;
+    let pair_key = registry_key(deps, &pair_info.asset_infos, Some(&pair_info.pair_type))?;
+    if PAIRS.may_load(deps.storage, &pair_key)?.as_ref() != Some(pair_addr) {
+        return Err(not_registered());
+    }
+
+    Ok(())
+}
+
 /// The entry point to the contract for processing replies from submessages.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -396,10 +1085,53 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
                 .map_err(|e| StdError::generic_err(format!("{e}")))?;
 
             let pair_contract = deps.api.addr_validate(&init_response.contract_address)?;
+            if pair_contract != tmp.pair_addr {
+                return Err(ContractError::PairAddressMismatch {
+                    predicted: tmp.pair_addr.to_string(),
+                    actual: pair_contract.to_string(),
+                });
+            }
 
             PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+            PAIR_TYPES.save(deps.storage, &tmp.pair_key, &tmp.pair_type)?;
+            index_pair(deps.storage, &tmp.asset_infos, &pair_contract)?;
+
+            let mut messages: Vec<CosmosMsg> = vec![];
+            if let Some(provide) = tmp.provide {
+                for asset in &provide.assets {
+                    if let AssetInfo::Token { contract_addr } = &asset.info {
+                        messages.push(
+                            wasm_execute(
+                                contract_addr,
+                                &Cw20ExecuteMsg::IncreaseAllowance {
+                                    spender: pair_contract.to_string(),
+                                    amount: asset.amount,
+                                    expires: None,
+                                },
+                                vec![],
+                            )?
+                            .into(),
+                        );
+                    }
+                }
+
+                messages.push(
+                    wasm_execute(
+                        &pair_contract,
+                        &PairExecuteMsg::ProvideLiquidity {
+                            assets: provide.assets,
+                            slippage_tolerance: None,
+                            receiver: Some(provide.receiver.to_string()),
+                            min_lp_to_receive: None,
+                            deadline: None,
+                        },
+                        provide.funds,
+                    )?
+                    .into(),
+                );
+            }
 
-            Ok(Response::new().add_attributes(vec![
+            Ok(Response::new().add_messages(messages).add_attributes(vec![
                 attr("action", "register"),
                 attr("pair_contract_addr", pair_contract),
             ]))
@@ -408,16 +1140,19 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
     }
 }
 
-/// Removes an existing pair from the factory.
+/// Removes an existing pair from the factory along with its fee override and pending migration.
 ///
 /// * **asset_infos** is a vector with assets for which we deregister the pair.
 ///
+/// * **pair_type** is the type of the pair to deregister. The first pair created for the assets is used if not set.
+///
 /// ## Executor
 /// Only the owner can execute this.
 pub fn deregister(
     deps: DepsMut,
     info: MessageInfo,
     asset_infos: Vec<AssetInfo>,
+    pair_type: Option<PairType>,
 ) -> Result<Response, ContractError> {
     check_asset_infos(deps.api, &asset_infos)?;
 
@@ -427,8 +1162,13 @@ pub fn deregister(
         return Err(ContractError::Unauthorized {});
     }
 
-    let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
-    PAIRS.remove(deps.storage, &pair_key(&asset_infos));
+    let pair_key = registry_key(deps.as_ref(), &asset_infos, pair_type.as_ref())?;
+    let pair_addr = PAIRS.load(deps.storage, &pair_key)?;
+    PAIRS.remove(deps.storage, &pair_key);
+    PAIR_TYPES.remove(deps.storage, &pair_key);
+    unindex_pair(deps.storage, &asset_infos, &pair_addr);
+    PAIR_FEE_OVERRIDES.remove(deps.storage, &pair_addr);
+    remove_pair_to_migrate(deps.storage, &pair_addr)?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "deregister"),
@@ -441,24 +1181,75 @@ pub fn deregister(
 /// ## Queries
 /// * **QueryMsg::Config {}** Returns general contract parameters using a custom [`ConfigResponse`] structure.
 ///
-/// * **QueryMsg::Pair { asset_infos }** Returns a [`PairInfo`] object with information about a specific Palomadex pair.
+/// * **QueryMsg::Pair { asset_infos, pair_type }** Returns a [`PairInfo`] object with information about a specific Palomadex pair.
 ///
 /// * **QueryMsg::Pairs { start_after, limit }** Returns an array that contains items of type [`PairInfo`].
 ///   This returns information about multiple Palomadex pairs
 ///
-/// * **QueryMsg::FeeInfo { pair_type }** Returns the fee structure (total and maker fees) for a specific pair type.
+/// * **QueryMsg::FeeInfo { pair_type, pair_addr }** Returns the fee structure (total and maker fees) for a specific pair type
+///   or for a specific pair if it has a fee override.
 ///
 /// * **QueryMsg::BlacklistedPairTypes {}** Returns a vector that contains blacklisted pair types (pair types that cannot get ASTRO emissions).
+///
+/// * **QueryMsg::PairsByAsset { asset_info, start_after, limit }** Returns an array that contains items of type [`PairInfo`]
+///   for every pair that contains `asset_info`.
+///
+/// * **QueryMsg::PairsToMigrate {}** Returns the pairs that are still pending migration.
+///
+/// * **QueryMsg::ListingRules {}** Returns the rules of the listing policy.
+///
+/// * **QueryMsg::PredictPairAddress { pair_type, asset_infos }** Returns the address a pair is created at.
+///
+/// * **QueryMsg::IsPaused { pair_addr }** Returns whether the pair is paused.
+///
+/// * **QueryMsg::RoleMembers { role }** Returns the accounts that were granted the role.
+///
+/// * **QueryMsg::HasRole { role, account }** Returns whether the account was granted the role.
+///
+/// * **QueryMsg::QueuedChanges { start_after, limit }** Returns the timelocked changes waiting in the queue.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_json_binary(&query_pair(deps, asset_infos)?),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => to_json_binary(&query_pair(deps, asset_infos, pair_type)?),
         QueryMsg::Pairs { start_after, limit } => {
             to_json_binary(&query_pairs(deps, start_after, limit)?)
         }
-        QueryMsg::FeeInfo { pair_type } => to_json_binary(&query_fee_info(deps, pair_type)?),
+        QueryMsg::FeeInfo {
+            pair_type,
+            pair_addr,
+        } => to_json_binary(&query_fee_info(deps, pair_type, pair_addr)?),
         QueryMsg::BlacklistedPairTypes {} => to_json_binary(&query_blacklisted_pair_types(deps)?),
+        QueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            limit,
+        } => to_json_binary(&query_pairs_by_asset(deps, asset_info, start_after, limit)?),
+        QueryMsg::PairsToMigrate {} => to_json_binary(&query_pairs_to_migrate(deps)?),
+        QueryMsg::ListingRules {} => to_json_binary(&LISTING_RULES.load(deps.storage)?),
+        QueryMsg::IsPaused { pair_addr } => to_json_binary(&query_is_paused(deps, pair_addr)?),
+        QueryMsg::RoleMembers { role } => {
+            to_json_binary(&query_role_members(deps.storage, &role, ROLES)?)
+        }
+        QueryMsg::HasRole { role, account } => {
+            let account = deps.api.addr_validate(&account)?;
+            to_json_binary(&has_role(deps.storage, &role, &account, ROLES))
+        }
+        QueryMsg::QueuedChanges { start_after, limit } => {
+            to_json_binary(&query_queued_changes(deps, start_after, limit)?)
+        }
+        QueryMsg::PredictPairAddress {
+            pair_type,
+            asset_infos,
+        } => to_json_binary(&query_predict_pair_address(
+            deps,
+            env,
+            pair_type,
+            asset_infos,
+        )?),
     }
 }
 
@@ -479,6 +1270,30 @@ pub fn query_blacklisted_pair_types(deps: Deps) -> StdResult<Vec<PairType>> {
         .collect()
 }
 
+/// Returns the timelocked changes which were neither executed nor cancelled.
+///
+/// * **start_after** is the identifier of the change to start reading from.
+///
+/// * **limit** sets the number of changes to be retrieved.
+pub fn query_queued_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<QueuedChange>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    QUEUED_CHANGES
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
 /// Returns general contract parameters using a custom [`ConfigResponse`] structure.
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
@@ -493,8 +1308,9 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         generator_address: config.generator_address,
         whitelist_code_id: config.whitelist_code_id,
         coin_registry_address: config.coin_registry_address,
-        bonding_curve_factory_address: config.bonding_curve_factory_address,
-        pusd_denom: config.pusd_denom,
+        guardian: config.guardian,
+        timelock_delay: config.timelock_delay,
+        canceller: config.canceller,
     };
 
     Ok(resp)
@@ -502,11 +1318,42 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
 /// Returns a pair's data using the assets in `asset_infos` as input (those being the assets that are traded in the pair).
 /// * **asset_infos** is a vector with assets traded in the pair.
-pub fn query_pair(deps: Deps, asset_infos: Vec<AssetInfo>) -> StdResult<PairInfo> {
-    let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
+///
+/// * **pair_type** is the type of the pair. The first pair created for the assets is returned if not set.
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: Option<PairType>,
+) -> StdResult<PairInfo> {
+    let pair_key = registry_key(deps, &asset_infos, pair_type.as_ref())?;
+    let pair_addr = PAIRS.load(deps.storage, &pair_key)?;
     query_pair_info(&deps.querier, pair_addr)
 }
 
+/// Returns whether swaps and liquidity provision are paused in the pair `pair_addr`,
+/// either because all pairs or because this pair in particular is paused.
+pub fn query_is_paused(deps: Deps, pair_addr: String) -> StdResult<bool> {
+    let pair_addr = deps.api.addr_validate(&pair_addr)?;
+
+    Ok(ALL_PAIRS_PAUSED.may_load(deps.storage)?.unwrap_or_default()
+        || PAUSED_PAIRS.has(deps.storage, &pair_addr))
+}
+
+/// Returns the address the next pair of `pair_type` for the assets in `asset_infos` is created at.
+/// * **pair_type** is the type of the pair.
+///
+/// * **asset_infos** is a vector with assets traded in the pair.
+pub fn query_predict_pair_address(
+    deps: Deps,
+    env: Env,
+    pair_type: PairType,
+    asset_infos: Vec<AssetInfo>,
+) -> StdResult<Addr> {
+    let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
+    let salt = next_pair_salt(deps.storage, &asset_infos, &pair_type)?;
+    predict_pair_address(deps, &env, pair_config.code_id, &salt)
+}
+
 /// Returns a vector with pair data that contains items of type [`PairInfo`]. Querying starts at `start_after` and returns `limit` pairs.
 /// * **start_after** is a field which accepts a vector with items of type [`AssetInfo`].
 ///   This is the pair from which we start a query.
@@ -525,15 +1372,114 @@ pub fn query_pairs(
     Ok(PairsResponse { pairs })
 }
 
+/// Returns a vector with data for every pair that contains `asset_info`. Querying starts at `start_after` and returns `limit` pairs.
+/// * **asset_info** is the asset that must be traded in the returned pairs.
+///
+/// * **start_after** is the pair contract address from which we start a query.
+///
+/// * **limit** sets the number of pairs to be retrieved.
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let start_after = addr_opt_validate(deps.api, &start_after)?;
+    let pairs = read_pairs_by_asset(deps, &asset_info, start_after, limit)?
+        .iter()
+        .map(|pair_addr| query_pair_info(&deps.querier, pair_addr))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PairsResponse { pairs })
+}
+
+/// Returns the pairs that are still pending migration using a [`PairsToMigrateResponse`] struct.
+pub fn query_pairs_to_migrate(deps: Deps) -> StdResult<PairsToMigrateResponse> {
+    let migration = PAIRS_MIGRATION.may_load(deps.storage)?;
+    Ok(PairsToMigrateResponse {
+        pair_type: migration.as_ref().map(|m| m.pair_type.clone()),
+        new_code_id: migration.map(|m| m.new_code_id),
+        pairs: PAIRS_TO_MIGRATE.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
 /// Returns the fee setup for a specific pair type using a [`FeeInfoResponse`] struct.
 /// * **pair_type** is a struct that represents the fee information (total and maker fees) for a specific pair type.
-pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoResponse> {
+///
+/// * **pair_addr** is the pair for which we return fees. Its fee override takes precedence over the pair type fees.
+pub fn query_fee_info(
+    deps: Deps,
+    pair_type: PairType,
+    pair_addr: Option<String>,
+) -> StdResult<FeeInfoResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
+
+    let fee_override = addr_opt_validate(deps.api, &pair_addr)?
+        .map(|pair_addr| PAIR_FEE_OVERRIDES.may_load(deps.storage, &pair_addr))
+        .transpose()?
+        .flatten();
+
+    let (total_fee_bps, maker_fee_bps) = match fee_override {
+        Some(fee_override) => (fee_override.total_fee_bps, fee_override.maker_fee_bps),
+        None => {
+            let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
+            (pair_config.total_fee_bps, pair_config.maker_fee_bps)
+        }
+    };
 
     Ok(FeeInfoResponse {
         fee_address: config.fee_address,
-        total_fee_bps: pair_config.total_fee_bps,
-        maker_fee_bps: pair_config.maker_fee_bps,
+        total_fee_bps,
+        maker_fee_bps,
     })
 }
+
+/// Manages the contract migration.
+///
+/// Migrating from version 0.1.0 backfills the [`crate::state::PAIRS_BY_ASSET`] index and [`PAIR_TYPES`] for all pairs registered before they existed.
+/// Pairs created by older versions have the factory owner as their admin, which has to be moved to the factory
+/// before they can be migrated with `MigratePairs`.
+/// The bonding curve factory and PUSD settings of the config are converted into listing rules,
+/// the timelock delay and canceller are taken from `msg`.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    match contract_version.contract.as_ref() {
+        CONTRACT_NAME => match contract_version.version.as_ref() {
+            "0.1.0" => {
+                let pairs = PAIRS
+                    .range(deps.storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<_>>>()?;
+
+                for (pair_key, pair_addr) in pairs {
+                    let pair_info = query_pair_info(&deps.querier, &pair_addr)?;
+                    index_pair(deps.storage, &pair_info.asset_infos, &pair_addr)?;
+                    PAIR_TYPES.save(deps.storage, &pair_key, &pair_info.pair_type)?;
+                }
+
+                // Drop pairs left over from the legacy migration flow
+                PAIRS_TO_MIGRATE.remove(deps.storage);
+                PAIRS_MIGRATION.remove(deps.storage);
+
+                let timelock_delay = msg
+                    .timelock_delay
+                    .ok_or(ContractError::MissingTimelockDelay {})?;
+                check_timelock_delay(timelock_delay)?;
+                let canceller = addr_opt_validate(deps.api, &msg.canceller)?;
+                migrate_config_v010(deps.storage, timelock_delay, canceller)?;
+            }
+            "0.2.0" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
@@ -28,12 +28,51 @@ pub enum ContractError {
     #[error("Pair config disabled")]
     PairConfigDisabled {},
 
+    #[error("Listing rule not found")]
+    ListingRuleNotFound {},
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
+    #[error("{pair_addr} is not a registered pair")]
+    PairNotRegistered { pair_addr: String },
+
+    #[error("Pairs of type {pair_type} are already being migrated")]
+    MigrationInProgress { pair_type: String },
+
+    #[error("Pairs of type {pair_type} are being migrated to code ID {new_code_id} with another message")]
+    MigrationMismatch { pair_type: String, new_code_id: u64 },
+
+    #[error("The migration of pairs of type {pair_type} wasn't approved through the timelock")]
+    MigrationNotApproved { pair_type: String },
+
+    #[error("Pair address {actual} doesn't match the predicted address {predicted}")]
+    PairAddressMismatch { predicted: String, actual: String },
+
+    #[error("The ETA of a change must be at least {earliest}")]
+    TimelockEtaTooEarly { earliest: u64 },
+
+    #[error("Queued change {id} can't be executed before {eta}")]
+    TimelockNotReady { id: u64, eta: u64 },
+
+    #[error("Queued change {id} expired at {expired_at}")]
+    TimelockExpired { id: u64, expired_at: u64 },
+
+    #[error("Queued change {id} not found")]
+    QueuedChangeNotFound { id: u64 },
+
+    #[error("Timelock delay must be smaller than or equal to {max} seconds")]
+    TimelockDelayTooLong { max: u64 },
+
+    #[error("Timelock delay must be greater than or equal to {min} seconds")]
+    TimelockDelayTooShort { min: u64 },
+
+    #[error("The timelock delay must be set when migrating from version 0.1.0")]
+    MissingTimelockDelay {},
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 }
@@ -3,6 +3,7 @@ pub mod state;
 
 pub mod error;
 
+mod migration;
 mod querier;
 
 #[cfg(test)]
@@ -0,0 +1,63 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, StdResult, Storage};
+use cw_storage_plus::Item;
+
+use palomadex::factory::{Config, DenomMatcher, ListingRule};
+
+use crate::state::{CONFIG, LISTING_RULES};
+
+/// This structure holds the factory parameters of version 0.1.0.
+#[cw_serde]
+struct ConfigV010 {
+    owner: Addr,
+    token_code_id: u64,
+    generator_address: Option<Addr>,
+    fee_address: Option<Addr>,
+    whitelist_code_id: u64,
+    coin_registry_address: Addr,
+    bonding_curve_factory_address: Option<Addr>,
+    pusd_denom: Option<String>,
+}
+
+const CONFIG_V010: Item<ConfigV010> = Item::new("config");
+
+/// Migrates the config of version 0.1.0 and converts its bonding curve factory and PUSD settings
+/// into listing rules. Version 0.1.0 only applied these restrictions if the bonding curve factory was set.
+/// Version 0.1.0 had no timelock, so its `timelock_delay` and `canceller` are set by the migration.
+pub fn migrate_config_v010(
+    storage: &mut dyn Storage,
+    timelock_delay: u64,
+    canceller: Option<Addr>,
+) -> StdResult<()> {
+    let old_config = CONFIG_V010.load(storage)?;
+
+    let mut rules = vec![];
+    if let Some(bonding_curve_factory) = old_config.bonding_curve_factory_address {
+        rules.push(ListingRule {
+            denom: DenomMatcher::TokenFactoryCreator(bonding_curve_factory.to_string()),
+            allowed_senders: vec![bonding_curve_factory],
+        });
+        if let Some(pusd_denom) = old_config.pusd_denom {
+            rules.push(ListingRule {
+                denom: DenomMatcher::Exact(pusd_denom),
+                allowed_senders: vec![],
+            });
+        }
+    }
+    LISTING_RULES.save(storage, &rules)?;
+
+    CONFIG.save(
+        storage,
+        &Config {
+            owner: old_config.owner,
+            token_code_id: old_config.token_code_id,
+            generator_address: old_config.generator_address,
+            fee_address: old_config.fee_address,
+            whitelist_code_id: old_config.whitelist_code_id,
+            coin_registry_address: old_config.coin_registry_address,
+            guardian: None,
+            timelock_delay,
+            canceller,
+        },
+    )
+}
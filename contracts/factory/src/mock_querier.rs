@@ -1,30 +1,38 @@
 use std::collections::HashMap;
 
-use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::{MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_json, to_json_binary, Coin, Empty, OwnedDeps, Querier, QuerierResult, QueryRequest,
-    SystemError, SystemResult, WasmQuery,
+    from_json, to_json_binary, CodeInfoResponse, Coin, Empty, Env, HexBinary, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, SystemError, SystemResult, WasmQuery,
 };
+use cw_multi_test::MockApiBech32;
 
 use palomadex::asset::PairInfo;
 use palomadex::pair::QueryMsg;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
-/// This uses the Palomadex CustomQuerier.
+/// This uses the Palomadex CustomQuerier and a bech32 API, so that pair addresses can be predicted.
 pub fn mock_dependencies(
     contract_balance: &[Coin],
-) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+) -> OwnedDeps<MockStorage, MockApiBech32, WasmMockQuerier> {
     let custom_querier: WasmMockQuerier =
         WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
 
     OwnedDeps {
         storage: MockStorage::default(),
-        api: MockApi::default(),
+        api: MockApiBech32::new("cosmwasm"),
         querier: custom_querier,
         custom_query_type: Default::default(),
     }
 }
 
+/// mock_env is a drop-in replacement for cosmwasm_std::testing::mock_env with a bech32 contract address.
+pub fn mock_env() -> Env {
+    let mut env = cosmwasm_std::testing::mock_env();
+    env.contract.address = MockApiBech32::new("cosmwasm").addr_make(MOCK_CONTRACT_ADDR);
+    env
+}
+
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     palomadex_pair_querier: PalomadexPairQuerier,
@@ -87,6 +95,19 @@ impl WasmMockQuerier {
                     }
                     _ => panic!("DO NOT ENTER HERE")
             }
+            QueryRequest::Wasm(WasmQuery::CodeInfo { code_id }) => {
+                let mut checksum = [0u8; 32];
+                checksum[24..].copy_from_slice(&code_id.to_be_bytes());
+
+                SystemResult::Ok(
+                    to_json_binary(&CodeInfoResponse::new(
+                        *code_id,
+                        "creator".to_string(),
+                        HexBinary::from(checksum),
+                    ))
+                    .into(),
+                )
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -1,16 +1,47 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Api, Deps, Order, StdResult};
+use cosmwasm_std::{Addr, Api, Binary, Coin, Deps, Order, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
 use itertools::Itertools;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
-use palomadex::asset::AssetInfo;
-use palomadex::common::OwnershipProposal;
-use palomadex::factory::{Config, PairConfig};
+use palomadex::asset::{Asset, AssetInfo};
+use palomadex::common::{OwnershipProposal, Roles};
+use palomadex::factory::{
+    Config, ListingRule, PairConfig, PairFeeOverride, PairType, QueuedChange,
+};
 /// This is an intermediate structure for storing a pair's key. It is used in a submessage response.
 #[cw_serde]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    /// The predicted address of the pair
+    pub pair_addr: Addr,
+    pub asset_infos: Vec<AssetInfo>,
+    pub pair_type: PairType,
+    /// The initial liquidity provided to the pair once it is instantiated
+    pub provide: Option<TmpProvideInfo>,
+}
+
+/// This is an intermediate structure for storing the initial liquidity of a pair created with `CreatePairAndProvide`.
+#[cw_serde]
+pub struct TmpProvideInfo {
+    /// The assets to provide
+    pub assets: Vec<Asset>,
+    /// The native coins sent along with the assets
+    pub funds: Vec<Coin>,
+    /// The receiver of LP tokens
+    pub receiver: Addr,
+}
+
+/// This structure describes a batched pair migration approved by a timelocked `MigratePairs` change.
+#[cw_serde]
+pub struct PairsMigration {
+    /// The type of the pairs being migrated
+    pub pair_type: PairType,
+    /// The code ID the pairs are migrated to
+    pub new_code_id: u64,
+    /// The binary serialised migration message sent to every pair
+    pub msg: Binary,
 }
 
 /// Saves a pair's key
@@ -19,9 +50,34 @@ pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 /// Saves factory settings
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Saves created pairs (from olders to latest)
+/// Saves created pairs (from olders to latest).
+/// The first pair for a set of assets is keyed by [`pair_key`], pairs of other types for the same assets by [`typed_pair_key`]
 pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
 
+/// Saves the type each pair was created with. The key is the same as in [`PAIRS`]
+pub const PAIR_TYPES: Map<&[u8], PairType> = Map::new("pair_types");
+
+/// Saves created pairs indexed by every asset they contain. The key is (asset, pair address)
+pub const PAIRS_BY_ASSET: Map<(&AssetInfo, &Addr), ()> = Map::new("pairs_by_asset");
+
+/// Adds a pair to the [`PAIRS_BY_ASSET`] index under each of its assets.
+pub fn index_pair(
+    storage: &mut dyn Storage,
+    asset_infos: &[AssetInfo],
+    pair_addr: &Addr,
+) -> StdResult<()> {
+    asset_infos
+        .iter()
+        .try_for_each(|asset_info| PAIRS_BY_ASSET.save(storage, (asset_info, pair_addr), &()))
+}
+
+/// Removes a pair from the [`PAIRS_BY_ASSET`] index.
+pub fn unindex_pair(storage: &mut dyn Storage, asset_infos: &[AssetInfo], pair_addr: &Addr) {
+    asset_infos
+        .iter()
+        .for_each(|asset_info| PAIRS_BY_ASSET.remove(storage, (asset_info, pair_addr)));
+}
+
 /// Calculates a pair key from the specified parameters in the `asset_infos` variable.
 ///
 /// `asset_infos` is an array with multiple items of type [`AssetInfo`].
@@ -35,14 +91,88 @@ pub fn pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
         .collect()
 }
 
+/// Calculates the key of a pair of `pair_type` for the assets in `asset_infos`.
+/// It is used for every pair type created after the first pair for the same assets,
+/// which is stored under [`pair_key`].
+pub fn typed_pair_key(asset_infos: &[AssetInfo], pair_type: &PairType) -> Vec<u8> {
+    let mut key = pair_key(asset_infos);
+    key.push(0);
+    key.extend(pair_type.to_string().as_bytes());
+    key
+}
+
+/// Saves how many pairs were created for each [`typed_pair_key`], so that a pair recreated
+/// after `Deregister` is instantiated at a new address
+pub const PAIR_NONCES: Map<&[u8], u64> = Map::new("pair_nonces");
+
+/// Calculates the salt used to instantiate the pair of `pair_type` for the assets in `asset_infos`
+/// with `Instantiate2`. It is the SHA-256 hash of [`typed_pair_key`] followed by `nonce`, the number of
+/// pairs created for the same key before. The nonce is omitted for the first pair.
+pub fn pair_salt(asset_infos: &[AssetInfo], pair_type: &PairType, nonce: u64) -> Vec<u8> {
+    let mut key = typed_pair_key(asset_infos, pair_type);
+    if nonce > 0 {
+        key.extend(nonce.to_be_bytes());
+    }
+    Sha256::digest(key).to_vec()
+}
+
+/// Returns the salt of the next pair of `pair_type` created for the assets in `asset_infos`.
+pub fn next_pair_salt(
+    storage: &dyn Storage,
+    asset_infos: &[AssetInfo],
+    pair_type: &PairType,
+) -> StdResult<Vec<u8>> {
+    let nonce = PAIR_NONCES
+        .may_load(storage, &typed_pair_key(asset_infos, pair_type))?
+        .unwrap_or_default();
+    Ok(pair_salt(asset_infos, pair_type, nonce))
+}
+
 /// Saves pair type configurations
 pub const PAIR_CONFIGS: Map<String, PairConfig> = Map::new("pair_configs");
 
+/// Saves fees of specific pairs which override the fees of their pair type
+pub const PAIR_FEE_OVERRIDES: Map<&Addr, PairFeeOverride> = Map::new("pair_fee_overrides");
+
+/// Whether all pairs are paused
+pub const ALL_PAIRS_PAUSED: Item<bool> = Item::new("all_pairs_paused");
+
+/// Saves pairs which are paused individually
+pub const PAUSED_PAIRS: Map<&Addr, ()> = Map::new("paused_pairs");
+
+/// Saves the rules of the listing policy
+pub const LISTING_RULES: Item<Vec<ListingRule>> = Item::new("listing_rules");
+
+/// Checks that `sender` is allowed to create a pair for `asset_infos` according to the listing `rules`.
+/// A native asset is restricted if it is matched by a rule that doesn't allow `sender`,
+/// and a pair can contain at most one restricted asset.
+pub fn check_listing_rules(
+    rules: &[ListingRule],
+    sender: &Addr,
+    asset_infos: &[AssetInfo],
+) -> Result<(), ContractError> {
+    let restricted = asset_infos
+        .iter()
+        .filter(|asset_info| match asset_info {
+            AssetInfo::NativeToken { denom } => rules
+                .iter()
+                .any(|rule| rule.denom.matches(denom) && !rule.allowed_senders.contains(sender)),
+            AssetInfo::Token { .. } => false,
+        })
+        .count();
+
+    if restricted > 1 {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
 /// ## Pagination settings
 /// The maximum limit for reading pairs from [`PAIRS`]
-const MAX_LIMIT: u32 = 30;
+pub(crate) const MAX_LIMIT: u32 = 30;
 /// The default limit for reading pairs from [`PAIRS`]
-const DEFAULT_LIMIT: u32 = 10;
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
 
 /// Reads pairs from the [`PAIRS`] vector according to the `start_after` and `limit` variables.
 /// Otherwise, it returns the default number of pairs, starting from the oldest one.
@@ -83,6 +213,32 @@ pub fn read_pairs(
     }
 }
 
+/// Reads the addresses of pairs that contain `asset_info` from the [`PAIRS_BY_ASSET`] index.
+/// Pairs are ordered by their contract address.
+///
+/// `start_after` is the pair address from which the function starts to fetch results.
+///
+/// `limit` is the number of items to retrieve.
+pub fn read_pairs_by_asset(
+    deps: Deps,
+    asset_info: &AssetInfo,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    PAIRS_BY_ASSET
+        .prefix(asset_info)
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect()
+}
+
 /// Calculates the key of a pair from which to start reading data.
 ///
 /// `start_after` is an [`Option`] type that accepts [`AssetInfo`] elements.
@@ -112,9 +268,49 @@ pub(crate) fn check_asset_infos(
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
-/// This state key isn't used anymore but left for backward compatability with old pairs
+/// Stores the accounts that were granted roles by the owner
+pub const ROLES: Roles = Map::new("roles");
+
+/// Stores timelocked changes which were neither executed nor cancelled
+pub const QUEUED_CHANGES: Map<u64, QueuedChange> = Map::new("queued_changes");
+
+/// Stores the identifier of the last proposed timelocked change
+pub const LAST_CHANGE_ID: Item<u64> = Item::new("last_change_id");
+
+/// Stores pairs which are still pending the approved migration
 pub const PAIRS_TO_MIGRATE: Item<Vec<Addr>> = Item::new("pairs_to_migrate");
 
+/// Stores the pair type, code ID and message of the migration of [`PAIRS_TO_MIGRATE`]
+pub const PAIRS_MIGRATION: Item<PairsMigration> = Item::new("pairs_migration");
+
+/// Drops a pair from the pending migration, finishing the migration if no pairs are left.
+pub fn remove_pair_to_migrate(storage: &mut dyn Storage, pair_addr: &Addr) -> StdResult<()> {
+    let Some(mut pairs) = PAIRS_TO_MIGRATE.may_load(storage)? else {
+        return Ok(());
+    };
+
+    pairs.retain(|pair| pair != pair_addr);
+    if pairs.is_empty() {
+        PAIRS_TO_MIGRATE.remove(storage);
+        PAIRS_MIGRATION.remove(storage);
+        Ok(())
+    } else {
+        PAIRS_TO_MIGRATE.save(storage, &pairs)
+    }
+}
+
+/// Returns the addresses of all registered pairs of `pair_type`.
+pub fn read_pairs_of_type(deps: Deps, pair_type: &PairType) -> StdResult<Vec<Addr>> {
+    PAIR_TYPES
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((pair_key, ty)) if ty == *pair_type => Some(PAIRS.load(deps.storage, &pair_key)),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use palomadex::asset::{native_asset_info, token_asset_info};
@@ -1,22 +1,24 @@
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, Reply, ReplyOn, SubMsg, SubMsgResponse, SubMsgResult,
-    WasmMsg,
+    attr, from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Reply, ReplyOn, Response,
+    Storage, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
 };
 
-use crate::mock_querier::mock_dependencies;
-use crate::state::CONFIG;
+use crate::mock_querier::{mock_dependencies, mock_env};
+use crate::state::{pair_key, pair_salt, PAIRS};
 use crate::{
-    contract::{execute, instantiate, query},
+    contract::{execute, instantiate, migrate, query},
     error::ContractError,
 };
 
 use palomadex::asset::{AssetInfo, PairInfo};
+use palomadex::common::Role;
 use palomadex::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PairConfig, PairType, PairsResponse, QueryMsg,
+    ConfigResponse, DenomMatcher, ExecuteMsg, InstantiateMsg, ListingRule, MigrateMsg, PairConfig,
+    PairType, PairsResponse, QueryMsg, TimelockedChange,
 };
 
 use crate::contract::reply;
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::mock_info;
 use palomadex::pair::InstantiateMsg as PairInstantiateMsg;
 
 use prost::Message;
@@ -39,7 +41,7 @@ fn pair_type_to_string() {
 fn proper_initialization() {
     // Validate total and maker fee bps
     let mut deps = mock_dependencies(&[]);
-    let owner = "owner0000".to_string();
+    let owner = deps.api.addr_make("owner0000").to_string();
 
     let msg = InstantiateMsg {
         pair_configs: vec![
@@ -64,10 +66,12 @@ fn proper_initialization() {
         ],
         token_code_id: 123u64,
         fee_address: None,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         owner: owner.clone(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -88,10 +92,12 @@ fn proper_initialization() {
         }],
         token_code_id: 123u64,
         fee_address: None,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         owner: owner.clone(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -125,10 +131,12 @@ fn proper_initialization() {
         ],
         token_code_id: 123u64,
         fee_address: None,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         owner: owner.clone(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -146,7 +154,7 @@ fn proper_initialization() {
 #[test]
 fn update_config() {
     let mut deps = mock_dependencies(&[]);
-    let owner = "owner0000";
+    let owner = deps.api.addr_make("owner0000");
 
     let pair_configs = vec![PairConfig {
         code_id: 123u64,
@@ -163,31 +171,37 @@ fn update_config() {
         token_code_id: 123u64,
         fee_address: None,
         owner: owner.to_string(),
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
-    let info = mock_info(owner, &[]);
+    let info = mock_info(owner.as_str(), &[]);
 
     // We can just call .unwrap() to assert this was a success
     let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
     // Update config
     let env = mock_env();
-    let info = mock_info(owner, &[]);
-    let msg = ExecuteMsg::UpdateConfig {
+    let new_fee_addr = deps.api.addr_make("new_fee_addr");
+    let new_generator_addr = deps.api.addr_make("new_generator_addr");
+    let new_guardian = deps.api.addr_make("new_guardian");
+    let new_canceller = deps.api.addr_make("new_canceller");
+    let change = TimelockedChange::UpdateConfig {
         token_code_id: Some(200u64),
-        fee_address: Some(String::from("new_fee_addr")),
-        generator_address: Some(String::from("new_generator_addr")),
+        fee_address: Some(new_fee_addr.to_string()),
+        generator_address: Some(new_generator_addr.to_string()),
         whitelist_code_id: None,
         coin_registry_address: None,
-        bonding_curve_factory_address: None,
-        pusd_denom: None,
+        guardian: Some(new_guardian.to_string()),
+        timelock_delay: Some(86400),
+        canceller: Some(new_canceller.to_string()),
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = apply_change(deps.as_mut(), owner.as_str(), change).unwrap();
     assert_eq!(0, res.messages.len());
 
     // It worked, let's query the state
@@ -195,54 +209,86 @@ fn update_config() {
     let config_res: ConfigResponse = from_json(&query_res).unwrap();
     assert_eq!(200u64, config_res.token_code_id);
     assert_eq!(owner, config_res.owner);
-    assert_eq!(
-        String::from("new_fee_addr"),
-        config_res.fee_address.unwrap()
-    );
-    assert_eq!(
-        String::from("new_generator_addr"),
-        config_res.generator_address.unwrap()
-    );
+    assert_eq!(new_fee_addr, config_res.fee_address.unwrap());
+    assert_eq!(new_generator_addr, config_res.generator_address.unwrap());
+    assert_eq!(new_guardian, config_res.guardian.unwrap());
+    assert_eq!(86400, config_res.timelock_delay);
+    assert_eq!(new_canceller, config_res.canceller.unwrap());
 
     // Unauthorized err
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
+    let change = TimelockedChange::UpdateConfig {
         token_code_id: None,
         fee_address: None,
         generator_address: None,
         whitelist_code_id: None,
         coin_registry_address: None,
-        bonding_curve_factory_address: None,
-        pusd_denom: None,
+        guardian: None,
+        timelock_delay: None,
+        canceller: None,
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    let res = apply_change(deps.as_mut(), "addr0000", change).unwrap_err();
     assert_eq!(res, ContractError::Unauthorized {});
 }
 
+/// Queues `change` and executes it once the timelock delay has passed.
+fn apply_change(
+    mut deps: DepsMut,
+    sender: &str,
+    change: TimelockedChange,
+) -> Result<Response, ContractError> {
+    let mut env = mock_env();
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    let eta = env.block.time.seconds() + config.timelock_delay;
+    let res = execute(
+        deps.branch(),
+        env.clone(),
+        mock_info(sender, &[]),
+        ExecuteMsg::ProposeChange { change, eta },
+    )?;
+    let id = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(config.timelock_delay);
+    execute(
+        deps,
+        env,
+        mock_info(sender, &[]),
+        ExecuteMsg::ExecuteChange { id },
+    )
+}
+
 #[test]
 fn update_owner() {
     let mut deps = mock_dependencies(&[]);
-    let owner = "owner0000";
+    let owner = deps.api.addr_make("owner0000");
 
     let msg = InstantiateMsg {
         pair_configs: vec![],
         token_code_id: 123u64,
         fee_address: None,
         owner: owner.to_string(),
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
-    let info = mock_info(owner, &[]);
+    let info = mock_info(owner.as_str(), &[]);
 
     // We can just call .unwrap() to assert this was a success
     instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    let new_owner = String::from("new_owner");
+    let new_owner = deps.api.addr_make("new_owner").to_string();
 
     // New owner
     let env = mock_env();
@@ -268,7 +314,7 @@ fn update_owner() {
     .unwrap_err();
 
     // Propose new owner
-    let info = mock_info(owner, &[]);
+    let info = mock_info(owner.as_str(), &[]);
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
@@ -303,7 +349,7 @@ fn update_owner() {
 #[test]
 fn update_pair_config() {
     let mut deps = mock_dependencies(&[]);
-    let owner = "owner0000";
+    let owner = deps.api.addr_make("owner0000");
     let pair_configs = vec![PairConfig {
         code_id: 123u64,
         pair_type: PairType::Xyk {},
@@ -319,9 +365,11 @@ fn update_pair_config() {
         token_code_id: 123u64,
         fee_address: None,
         owner: owner.to_string(),
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -348,18 +396,15 @@ fn update_pair_config() {
 
     // Unauthorized err
     let env = mock_env();
-    let info = mock_info("wrong-addr0000", &[]);
-    let msg = ExecuteMsg::UpdatePairConfig {
+    let change = TimelockedChange::UpdatePairConfig {
         config: pair_config.clone(),
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    let res = apply_change(deps.as_mut(), "wrong-addr0000", change).unwrap_err();
     assert_eq!(res, ContractError::Unauthorized {});
 
     // Check validation of total and maker fee bps
-    let env = mock_env();
-    let info = mock_info(owner, &[]);
-    let msg = ExecuteMsg::UpdatePairConfig {
+    let change = TimelockedChange::UpdatePairConfig {
         config: PairConfig {
             code_id: 123u64,
             pair_type: PairType::Xyk {},
@@ -371,15 +416,14 @@ fn update_pair_config() {
         },
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    let res = apply_change(deps.as_mut(), owner.as_str(), change).unwrap_err();
     assert_eq!(res, ContractError::PairConfigInvalidFeeBps {});
 
-    let info = mock_info(owner, &[]);
-    let msg = ExecuteMsg::UpdatePairConfig {
+    let change = TimelockedChange::UpdatePairConfig {
         config: pair_config.clone(),
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = apply_change(deps.as_mut(), owner.as_str(), change).unwrap();
     assert_eq!(0, res.messages.len());
 
     // It worked, let's query the state
@@ -398,12 +442,11 @@ fn update_pair_config() {
         permissioned: false,
     };
 
-    let info = mock_info(owner, &[]);
-    let msg = ExecuteMsg::UpdatePairConfig {
+    let change = TimelockedChange::UpdatePairConfig {
         config: pair_config_custom.clone(),
     };
 
-    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    apply_change(deps.as_mut(), owner.as_str(), change).unwrap();
 
     // It worked, let's query the state
     let query_res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
@@ -432,10 +475,12 @@ fn create_pair() {
         pair_configs: vec![pair_config.clone()],
         token_code_id: 123u64,
         fee_address: None,
-        owner: "owner0000".to_string(),
-        generator_address: Some(String::from("generator")),
+        owner: deps.api.addr_make("owner0000").to_string(),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -446,14 +491,13 @@ fn create_pair() {
 
     let asset_infos = vec![
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0000"),
+            contract_addr: deps.api.addr_make("asset0000"),
         },
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0001"),
+            contract_addr: deps.api.addr_make("asset0001"),
         },
     ];
 
-    let config = CONFIG.load(&deps.storage);
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
 
@@ -473,7 +517,7 @@ fn create_pair() {
 
     let res = execute(
         deps.as_mut(),
-        env,
+        env.clone(),
         info,
         ExecuteMsg::CreatePair {
             pair_type: PairType::Xyk {},
@@ -487,15 +531,15 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "asset0000-asset0001")
+            attr("pair", format!("{}-{}", asset_infos[0], asset_infos[1]))
         ]
     );
     assert_eq!(
         res.messages,
         vec![SubMsg {
-            msg: WasmMsg::Instantiate {
+            msg: WasmMsg::Instantiate2 {
                 msg: to_json_binary(&PairInstantiateMsg {
-                    factory_addr: String::from(MOCK_CONTRACT_ADDR),
+                    factory_addr: env.contract.address.to_string(),
                     asset_infos: asset_infos.clone(),
                     token_code_id: msg.token_code_id,
                     init_params: None
@@ -503,8 +547,9 @@ fn create_pair() {
                 .unwrap(),
                 code_id: pair_config.code_id,
                 funds: vec![],
-                admin: Some(config.unwrap().owner.to_string()),
+                admin: Some(env.contract.address.to_string()),
                 label: String::from("Palomadex pair"),
+                salt: pair_salt(&asset_infos, &PairType::Xyk {}, 0).into(),
             }
             .into(),
             id: 1,
@@ -517,7 +562,7 @@ fn create_pair() {
 #[test]
 fn register() {
     let mut deps = mock_dependencies(&[]);
-    let owner = "owner0000";
+    let owner = deps.api.addr_make("owner0000");
 
     let msg = InstantiateMsg {
         pair_configs: vec![PairConfig {
@@ -531,10 +576,12 @@ fn register() {
         }],
         token_code_id: 123u64,
         fee_address: None,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(deps.api.addr_make("generator").to_string()),
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let env = mock_env();
@@ -543,10 +590,10 @@ fn register() {
 
     let asset_infos = vec![
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0000"),
+            contract_addr: deps.api.addr_make("asset0000"),
         },
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0001"),
+            contract_addr: deps.api.addr_make("asset0001"),
         },
     ];
 
@@ -556,14 +603,14 @@ fn register() {
         init_params: None,
     };
 
+    let pair0_addr = predict_pair_address(deps.as_ref(), &asset_infos);
+
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    let pair0_addr = "pair0000".to_string();
     let pair0_info = PairInfo {
         asset_infos: asset_infos.clone(),
-        contract_addr: Addr::unchecked("pair0000"),
+        contract_addr: Addr::unchecked(&pair0_addr),
         liquidity_token: Addr::unchecked("liquidity0000"),
         pair_type: PairType::Xyk {},
     };
@@ -574,7 +621,7 @@ fn register() {
     deps.querier.with_palomadex_pairs(&deployed_pairs);
 
     let instantiate_reply = MsgInstantiateContractResponse {
-        contract_address: String::from("pair0000"),
+        contract_address: pair0_addr.clone(),
         data: vec![],
     };
 
@@ -598,6 +645,7 @@ fn register() {
         env.clone(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -607,7 +655,7 @@ fn register() {
         pair_res,
         PairInfo {
             liquidity_token: Addr::unchecked("liquidity0000"),
-            contract_addr: Addr::unchecked("pair0000"),
+            contract_addr: Addr::unchecked(&pair0_addr),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
         }
@@ -620,10 +668,10 @@ fn register() {
     // Store one more item to test query pairs
     let asset_infos_2 = vec![
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0000"),
+            contract_addr: deps.api.addr_make("asset0000"),
         },
         AssetInfo::Token {
-            contract_addr: Addr::unchecked("asset0002"),
+            contract_addr: deps.api.addr_make("asset0003"),
         },
     ];
 
@@ -633,14 +681,14 @@ fn register() {
         init_params: None,
     };
 
+    let pair1_addr = predict_pair_address(deps.as_ref(), &asset_infos_2);
+
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    let pair1_addr = "pair0001".to_string();
     let pair1_info = PairInfo {
         asset_infos: asset_infos_2.clone(),
-        contract_addr: Addr::unchecked("pair0001"),
+        contract_addr: Addr::unchecked(&pair1_addr),
         liquidity_token: Addr::unchecked("liquidity0001"),
         pair_type: PairType::Xyk {},
     };
@@ -651,7 +699,7 @@ fn register() {
     deps.querier.with_palomadex_pairs(&deployed_pairs);
 
     let instantiate_reply = MsgInstantiateContractResponse {
-        contract_address: String::from("pair0001"),
+        contract_address: pair1_addr.clone(),
         data: vec![],
     };
 
@@ -682,13 +730,13 @@ fn register() {
         vec![
             PairInfo {
                 liquidity_token: Addr::unchecked("liquidity0000"),
-                contract_addr: Addr::unchecked("pair0000"),
+                contract_addr: Addr::unchecked(&pair0_addr),
                 asset_infos: asset_infos.clone(),
                 pair_type: PairType::Xyk {},
             },
             PairInfo {
                 liquidity_token: Addr::unchecked("liquidity0001"),
-                contract_addr: Addr::unchecked("pair0001"),
+                contract_addr: Addr::unchecked(&pair1_addr),
                 asset_infos: asset_infos_2.clone(),
                 pair_type: PairType::Xyk {},
             }
@@ -706,7 +754,7 @@ fn register() {
         pairs_res.pairs,
         vec![PairInfo {
             liquidity_token: Addr::unchecked("liquidity0000"),
-            contract_addr: Addr::unchecked("pair0000"),
+            contract_addr: Addr::unchecked(&pair0_addr),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
         }]
@@ -723,7 +771,7 @@ fn register() {
         pairs_res.pairs,
         vec![PairInfo {
             liquidity_token: Addr::unchecked("liquidity0001"),
-            contract_addr: Addr::unchecked("pair0001"),
+            contract_addr: Addr::unchecked(&pair1_addr),
             asset_infos: asset_infos_2.clone(),
             pair_type: PairType::Xyk {},
         }]
@@ -738,6 +786,7 @@ fn register() {
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: None,
         },
     )
     .unwrap_err();
@@ -746,13 +795,14 @@ fn register() {
 
     // Proper deregister
     let env = mock_env();
-    let info = mock_info(owner, &[]);
+    let info = mock_info(owner.as_str(), &[]);
     let res = execute(
         deps.as_mut(),
         env.clone(),
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -770,9 +820,925 @@ fn register() {
         pairs_res.pairs,
         vec![PairInfo {
             liquidity_token: Addr::unchecked("liquidity0000"),
-            contract_addr: Addr::unchecked("pair0000"),
+            contract_addr: Addr::unchecked(&pair0_addr),
             asset_infos: asset_infos.clone(),
             pair_type: PairType::Xyk {},
         },]
     );
 }
+
+fn predict_pair_address(deps: Deps, asset_infos: &[AssetInfo]) -> String {
+    let res = query(
+        deps,
+        mock_env(),
+        QueryMsg::PredictPairAddress {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.to_vec(),
+        },
+    )
+    .unwrap();
+    from_json::<Addr>(&res).unwrap().to_string()
+}
+
+fn instantiate_reply(contract_address: &str) -> Reply {
+    let instantiate_reply = MsgInstantiateContractResponse {
+        contract_address: contract_address.to_string(),
+        data: vec![],
+    };
+
+    let mut encoded_instantiate_reply = Vec::<u8>::with_capacity(instantiate_reply.encoded_len());
+    instantiate_reply
+        .encode(&mut encoded_instantiate_reply)
+        .unwrap();
+
+    Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(encoded_instantiate_reply.into()),
+        }),
+    }
+}
+
+#[test]
+fn pairs_by_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = deps.api.addr_make("owner0000");
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 123u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let common_asset = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let asset_infos = [
+        vec![
+            common_asset.clone(),
+            AssetInfo::Token {
+                contract_addr: deps.api.addr_make("asset0000"),
+            },
+        ],
+        vec![
+            AssetInfo::Token {
+                contract_addr: deps.api.addr_make("asset0001"),
+            },
+            common_asset.clone(),
+        ],
+        vec![
+            AssetInfo::Token {
+                contract_addr: deps.api.addr_make("asset0000"),
+            },
+            AssetInfo::Token {
+                contract_addr: deps.api.addr_make("asset0001"),
+            },
+        ],
+    ];
+
+    let pair_addrs = asset_infos
+        .clone()
+        .map(|asset_infos| predict_pair_address(deps.as_ref(), &asset_infos));
+    let pair_infos = asset_infos
+        .iter()
+        .zip(pair_addrs.iter())
+        .map(|(asset_infos, pair_addr)| PairInfo {
+            asset_infos: asset_infos.clone(),
+            contract_addr: Addr::unchecked(pair_addr),
+            liquidity_token: Addr::unchecked(format!("liquidity_{pair_addr}")),
+            pair_type: PairType::Xyk {},
+        })
+        .collect::<Vec<_>>();
+    deps.querier
+        .with_palomadex_pairs(&pair_addrs.iter().zip(pair_infos.iter()).collect::<Vec<_>>());
+
+    for (asset_infos, pair_addr) in asset_infos.iter().zip(pair_addrs.iter()) {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CreatePair {
+                pair_type: PairType::Xyk {},
+                asset_infos: asset_infos.clone(),
+                init_params: None,
+            },
+        )
+        .unwrap();
+        reply(deps.as_mut(), env.clone(), instantiate_reply(pair_addr)).unwrap();
+    }
+
+    let query_pairs_by_asset = |deps: Deps, start_after: Option<&str>, limit: Option<u32>| {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::PairsByAsset {
+                asset_info: common_asset.clone(),
+                start_after: start_after.map(String::from),
+                limit,
+            },
+        )
+        .unwrap();
+        from_json::<PairsResponse>(&res).unwrap().pairs
+    };
+
+    // Pairs are ordered by address
+    let sorted_by_addr = |mut pairs: Vec<PairInfo>| {
+        pairs.sort_by(|a, b| a.contract_addr.cmp(&b.contract_addr));
+        pairs
+    };
+    let common_pairs = sorted_by_addr(pair_infos[..2].to_vec());
+
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), None, None),
+        common_pairs
+    );
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), None, Some(1)),
+        common_pairs[..1].to_vec()
+    );
+    assert_eq!(
+        query_pairs_by_asset(
+            deps.as_ref(),
+            Some(common_pairs[0].contract_addr.as_str()),
+            None
+        ),
+        common_pairs[1..].to_vec()
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PairsByAsset {
+            asset_info: AssetInfo::Token {
+                contract_addr: deps.api.addr_make("asset0001"),
+            },
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_json::<PairsResponse>(&res).unwrap().pairs,
+        sorted_by_addr(pair_infos[1..].to_vec())
+    );
+
+    // Deregistered pairs are removed from the index
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner.as_str(), &[]),
+        ExecuteMsg::Deregister {
+            asset_infos: asset_infos[0].clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_pairs_by_asset(deps.as_ref(), None, None),
+        pair_infos[1..2].to_vec()
+    );
+}
+
+#[test]
+fn migrate_backfills_pairs_by_asset() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: deps.api.addr_make("owner0000").to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: deps.api.addr_make("asset0000"),
+        },
+    ];
+    let pair_addr = "pair0000".to_string();
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(&pair_addr),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_palomadex_pairs(&[(&pair_addr, &pair_info)]);
+
+    // Config and pair stored by version 0.1.0, which didn't have the asset index
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:palomadex-factory",
+        "0.1.0",
+    )
+    .unwrap();
+    deps.storage.set(
+        b"config",
+        br#"{"owner":"owner0000","token_code_id":123,"generator_address":null,"fee_address":null,"whitelist_code_id":234,"coin_registry_address":"coin_registry","bonding_curve_factory_address":null,"pusd_denom":null}"#,
+    );
+    PAIRS
+        .save(
+            deps.as_mut().storage,
+            &pair_key(&asset_infos),
+            &Addr::unchecked(&pair_addr),
+        )
+        .unwrap();
+
+    let query_msg = QueryMsg::PairsByAsset {
+        asset_info: asset_infos[1].clone(),
+        start_after: None,
+        limit: None,
+    };
+    let res: PairsResponse =
+        from_json(query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap()).unwrap();
+    assert_eq!(res.pairs, vec![]);
+
+    let msg = MigrateMsg {
+        timelock_delay: Some(86_400),
+        canceller: None,
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let res: PairsResponse =
+        from_json(query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    assert_eq!(res.pairs, vec![pair_info]);
+}
+
+#[test]
+fn denom_matcher() {
+    let exact = DenomMatcher::Exact("upusd".to_string());
+    assert!(exact.matches("upusd"));
+    assert!(!exact.matches("upusd2"));
+    assert!(!exact.matches("factory/creator/upusd"));
+
+    let creator = DenomMatcher::TokenFactoryCreator("creator".to_string());
+    assert!(creator.matches("factory/creator/token"));
+    assert!(!creator.matches("factory/creator"));
+    assert!(!creator.matches("factory/creator2/token"));
+    assert!(!creator.matches("factory/other/creator"));
+    assert!(!creator.matches("ibc/creator/token"));
+    assert!(!creator.matches("creator"));
+}
+
+fn create_native_pair(
+    deps: DepsMut,
+    sender: &str,
+    denoms: [&str; 2],
+) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        mock_info(sender, &[]),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: denoms
+                .iter()
+                .map(|denom| AssetInfo::NativeToken {
+                    denom: denom.to_string(),
+                })
+                .collect(),
+            init_params: None,
+        },
+    )
+}
+
+#[test]
+fn listing_rules() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = deps.api.addr_make("owner0000");
+    let bonding_curve = deps.api.addr_make("bonding_curve");
+    let token0 = format!("factory/{bonding_curve}/token0");
+    let token1 = format!("factory/{bonding_curve}/token1");
+    let asset0 = deps.api.addr_make("asset0000");
+    let user = deps.api.addr_make("addr0000");
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 321u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(user.as_str(), &[]),
+        msg,
+    )
+    .unwrap();
+
+    let rules: Vec<ListingRule> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListingRules {}).unwrap()).unwrap();
+    assert_eq!(rules, vec![]);
+
+    // Without rules anyone can pair any assets
+    create_native_pair(deps.as_mut(), user.as_str(), [&token0, &token1]).unwrap();
+
+    let bonding_curve_rule = ExecuteMsg::SetListingRule {
+        denom: DenomMatcher::TokenFactoryCreator(bonding_curve.to_string()),
+        allowed_senders: vec![bonding_curve.to_string()],
+    };
+    let pusd_rule = ExecuteMsg::SetListingRule {
+        denom: DenomMatcher::Exact("upusd".to_string()),
+        allowed_senders: vec![],
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(user.as_str(), &[]),
+        bonding_curve_rule.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        bonding_curve_rule,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_listing_rule"),
+            attr("denom", format!("factory/{bonding_curve}/*")),
+            attr("allowed_senders", bonding_curve.as_str()),
+        ]
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        pusd_rule,
+    )
+    .unwrap();
+
+    let rules: Vec<ListingRule> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListingRules {}).unwrap()).unwrap();
+    assert_eq!(
+        rules,
+        vec![
+            ListingRule {
+                denom: DenomMatcher::TokenFactoryCreator(bonding_curve.to_string()),
+                allowed_senders: vec![bonding_curve.clone()],
+            },
+            ListingRule {
+                denom: DenomMatcher::Exact("upusd".to_string()),
+                allowed_senders: vec![],
+            },
+        ]
+    );
+
+    // Anyone can pair a bonding curve token or PUSD with an unrestricted asset
+    create_native_pair(deps.as_mut(), user.as_str(), [&token0, "uluna"]).unwrap();
+    create_native_pair(deps.as_mut(), user.as_str(), ["upusd", "uluna"]).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(user.as_str(), &[]),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: vec![
+                AssetInfo::NativeToken {
+                    denom: token0.clone(),
+                },
+                AssetInfo::Token {
+                    contract_addr: asset0.clone(),
+                },
+            ],
+            init_params: None,
+        },
+    )
+    .unwrap();
+
+    // Only the bonding curve factory can pair a bonding curve token with PUSD or another bonding curve token
+    for denoms in [[&token0, "upusd"], [&token0, &token1]] {
+        let err = create_native_pair(deps.as_mut(), user.as_str(), denoms).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        create_native_pair(deps.as_mut(), bonding_curve.as_str(), denoms).unwrap();
+    }
+
+    // Look-alike denoms are not restricted
+    for denoms in [
+        ["factory/bonding_curve2/token0", "upusd"],
+        ["ibc/bonding_curve", "upusd"],
+        [&token0, "upusd2"],
+        [&token0, "factory/other/upusd"],
+    ] {
+        create_native_pair(deps.as_mut(), user.as_str(), denoms).unwrap();
+    }
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(user.as_str(), &[]),
+        ExecuteMsg::RemoveListingRule {
+            denom: DenomMatcher::Exact("upusd".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        ExecuteMsg::RemoveListingRule {
+            denom: DenomMatcher::Exact("upusd".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "remove_listing_rule"),
+            attr("denom", "upusd"),
+        ]
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        ExecuteMsg::RemoveListingRule {
+            denom: DenomMatcher::Exact("upusd".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ListingRuleNotFound {});
+
+    create_native_pair(deps.as_mut(), user.as_str(), [&token0, "upusd"]).unwrap();
+
+    // Replacing a rule allows other senders
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        ExecuteMsg::SetListingRule {
+            denom: DenomMatcher::TokenFactoryCreator(bonding_curve.to_string()),
+            allowed_senders: vec![bonding_curve.to_string(), user.to_string()],
+        },
+    )
+    .unwrap();
+    let rules: Vec<ListingRule> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListingRules {}).unwrap()).unwrap();
+    assert_eq!(rules.len(), 1);
+    create_native_pair(deps.as_mut(), user.as_str(), [&token0, &token1]).unwrap();
+}
+
+#[test]
+fn migrate_converts_legacy_listing_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: deps.api.addr_make("owner0000").to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // Config stored by version 0.1.0
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:palomadex-factory",
+        "0.1.0",
+    )
+    .unwrap();
+    deps.storage.set(
+        b"config",
+        br#"{"owner":"owner0000","token_code_id":123,"generator_address":null,"fee_address":null,"whitelist_code_id":234,"coin_registry_address":"coin_registry","bonding_curve_factory_address":"bonding_curve","pusd_denom":"upusd"}"#,
+    );
+
+    // Version 0.1.0 had no timelock, so the migration must set its delay
+    let canceller = deps.api.addr_make("canceller");
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            timelock_delay: None,
+            canceller: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MissingTimelockDelay {});
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            timelock_delay: Some(0),
+            canceller: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TimelockDelayTooShort { min: 86_400 });
+
+    let msg = MigrateMsg {
+        timelock_delay: Some(172_800),
+        canceller: Some(canceller.to_string()),
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, Addr::unchecked("owner0000"));
+    assert_eq!(config.token_code_id, 123u64);
+    assert_eq!(config.timelock_delay, 172_800);
+    assert_eq!(config.canceller, Some(canceller));
+
+    let rules: Vec<ListingRule> =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ListingRules {}).unwrap()).unwrap();
+    assert_eq!(
+        rules,
+        vec![
+            ListingRule {
+                denom: DenomMatcher::TokenFactoryCreator("bonding_curve".to_string()),
+                allowed_senders: vec![Addr::unchecked("bonding_curve")],
+            },
+            ListingRule {
+                denom: DenomMatcher::Exact("upusd".to_string()),
+                allowed_senders: vec![],
+            },
+        ]
+    );
+
+    // The config is only converted once, migrating the current version again is a no-op
+    let msg = MigrateMsg {
+        timelock_delay: None,
+        canceller: None,
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    let config: ConfigResponse =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, Addr::unchecked("owner0000"));
+}
+
+#[test]
+fn pause_pairs() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = deps.api.addr_make("owner0000");
+    let guardian = deps.api.addr_make("guardian");
+    let pair0 = deps.api.addr_make("pair0000");
+    let pair1 = deps.api.addr_make("pair0001");
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let is_paused = |deps: Deps, pair: &Addr| -> bool {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::IsPaused {
+                pair_addr: pair.to_string(),
+            },
+        )
+        .unwrap();
+        from_json(&res).unwrap()
+    };
+    let pause = |pairs: Option<Vec<String>>| ExecuteMsg::PausePairs { pairs };
+    let unpause = |pairs: Option<Vec<String>>| ExecuteMsg::UnpausePairs { pairs };
+
+    assert!(!is_paused(deps.as_ref(), &pair0));
+
+    // Only the owner or the guardian can pause pairs
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(guardian.as_str(), &[]),
+        pause(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    apply_change(
+        deps.as_mut(),
+        owner.as_str(),
+        TimelockedChange::UpdateConfig {
+            token_code_id: None,
+            fee_address: None,
+            generator_address: None,
+            whitelist_code_id: None,
+            coin_registry_address: None,
+            guardian: Some(guardian.to_string()),
+            timelock_delay: None,
+            canceller: None,
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(guardian.as_str(), &[]),
+        pause(Some(vec![pair0.to_string()])),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause_pairs"), attr("pairs", pair0.as_str())]
+    );
+    assert!(is_paused(deps.as_ref(), &pair0));
+    assert!(!is_paused(deps.as_ref(), &pair1));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        unpause(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Pausing all pairs also pauses pairs which are not paused individually
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        pause(None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause_pairs"), attr("pairs", "all")]
+    );
+    assert!(is_paused(deps.as_ref(), &pair0));
+    assert!(is_paused(deps.as_ref(), &pair1));
+
+    // The guardian can only pause pairs
+    for pairs in [Some(vec![pair0.to_string()]), None] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(guardian.as_str(), &[]),
+            unpause(pairs),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    // Unpausing a single pair doesn't lift the pause of all pairs
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        unpause(Some(vec![pair1.to_string()])),
+    )
+    .unwrap();
+    assert!(is_paused(deps.as_ref(), &pair1));
+
+    // Unpausing all pairs doesn't lift the pauses of individual pairs
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        unpause(None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "unpause_pairs"), attr("pairs", "all")]
+    );
+    assert!(is_paused(deps.as_ref(), &pair0));
+    assert!(!is_paused(deps.as_ref(), &pair1));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        unpause(Some(vec![pair0.to_string()])),
+    )
+    .unwrap();
+    assert!(!is_paused(deps.as_ref(), &pair0));
+}
+
+#[test]
+fn roles() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = deps.api.addr_make("owner0000");
+    let fee_manager = deps.api.addr_make("fee_manager");
+    let pair = deps.api.addr_make("pair0000");
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: deps.api.addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    let pair_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: pair.clone(),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_palomadex_pairs(&[(&pair.to_string(), &pair_info)]);
+    PAIRS
+        .save(deps.as_mut().storage, &pair_key(&asset_infos), &pair)
+        .unwrap();
+
+    let set_fee = TimelockedChange::SetPairFeeOverride {
+        pair_addr: pair.to_string(),
+        total_fee_bps: 20,
+        maker_fee_bps: 10,
+    };
+    let err = apply_change(deps.as_mut(), fee_manager.as_str(), set_fee.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Only the owner can grant roles
+    let grant_msg = ExecuteMsg::GrantRole {
+        role: Role::FeeManager,
+        account: fee_manager.to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(fee_manager.as_str(), &[]),
+        grant_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Unauthorized");
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        grant_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "grant_role"),
+            attr("role", "fee_manager"),
+            attr("account", fee_manager.as_str()),
+        ]
+    );
+
+    let members: Vec<Addr> = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RoleMembers {
+                role: Role::FeeManager,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(members, vec![fee_manager.clone()]);
+
+    // Roles are granted separately
+    let has_role: bool = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::HasRole {
+                role: Role::Lister,
+                account: fee_manager.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(!has_role);
+
+    // Fee managers can change fee overrides through the timelock queue
+    apply_change(deps.as_mut(), fee_manager.as_str(), set_fee.clone()).unwrap();
+    apply_change(
+        deps.as_mut(),
+        fee_manager.as_str(),
+        TimelockedChange::RemovePairFeeOverride {
+            pair_addr: pair.to_string(),
+        },
+    )
+    .unwrap();
+
+    // The fee manager can't change anything else
+    let err = apply_change(
+        deps.as_mut(),
+        fee_manager.as_str(),
+        TimelockedChange::UpdatePair {
+            pair_addr: pair.to_string(),
+            params: Binary::default(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(fee_manager.as_str(), &[]),
+        ExecuteMsg::PausePairs { pairs: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner.as_str(), &[]),
+        ExecuteMsg::RevokeRole {
+            role: Role::FeeManager,
+            account: fee_manager.to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "revoke_role"),
+            attr("role", "fee_manager"),
+            attr("account", fee_manager.as_str()),
+        ]
+    );
+
+    let err = apply_change(deps.as_mut(), fee_manager.as_str(), set_fee).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(fee_manager.as_str(), &[]),
+        ExecuteMsg::RenounceRole {
+            role: Role::FeeManager,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Generic error: {fee_manager} doesn't have the fee_manager role")
+    );
+}
@@ -3,18 +3,32 @@
 use anyhow::Result as AnyResult;
 use cosmwasm_std::{Addr, Binary};
 use cw20::MinterResponse;
-use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use cw_multi_test::{
+    no_init, App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, Executor,
+    MockAddressGenerator, MockApiBech32, WasmKeeper,
+};
 use palomadex::asset::AssetInfo;
-use palomadex::factory::{PairConfig, PairType};
+use palomadex::factory::{ConfigResponse, PairConfig, PairType, TimelockedChange};
+
+/// Pairs are created with instantiate2, which needs an api that can humanize the predicted addresses
+pub type TestApp = App<BankKeeper, MockApiBech32>;
+
+pub fn mock_app() -> TestApp {
+    AppBuilder::new()
+        .with_api(MockApiBech32::new("cosmwasm"))
+        .with_wasm(WasmKeeper::default().with_address_generator(MockAddressGenerator))
+        .build(no_init)
+}
 
 pub struct FactoryHelper {
     // pub owner: Addr,
     pub factory: Addr,
+    pub coin_registry: Addr,
     pub cw20_token_code_id: u64,
 }
 
 impl FactoryHelper {
-    pub fn init(router: &mut App, owner: &Addr) -> Self {
+    pub fn init(router: &mut TestApp, owner: &Addr) -> Self {
         let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
             cw20_base::contract::execute,
             cw20_base::contract::instantiate,
@@ -29,7 +43,8 @@ impl FactoryHelper {
                 palomadex_pair::contract::instantiate,
                 palomadex_pair::contract::query,
             )
-            .with_reply_empty(palomadex_pair::contract::reply),
+            .with_reply_empty(palomadex_pair::contract::reply)
+            .with_migrate_empty(palomadex_pair::contract::migrate),
         );
 
         let pair_code_id = router.store_code(pair_contract);
@@ -45,6 +60,27 @@ impl FactoryHelper {
 
         let factory_code_id = router.store_code(factory_contract);
 
+        let coin_registry_contract = Box::new(ContractWrapper::new_with_empty(
+            palomadex_native_coin_registry::contract::execute,
+            palomadex_native_coin_registry::contract::instantiate,
+            palomadex_native_coin_registry::contract::query,
+        ));
+
+        let coin_registry_code_id = router.store_code(coin_registry_contract);
+
+        let coin_registry = router
+            .instantiate_contract(
+                coin_registry_code_id,
+                owner.clone(),
+                &palomadex::native_coin_registry::InstantiateMsg {
+                    owner: owner.to_string(),
+                },
+                &[],
+                String::from("Coin registry"),
+                None,
+            )
+            .unwrap();
+
         let msg = palomadex::factory::InstantiateMsg {
             pair_configs: vec![
                 PairConfig {
@@ -71,7 +107,9 @@ impl FactoryHelper {
             generator_address: None,
             owner: owner.to_string(),
             whitelist_code_id: 0,
-            coin_registry_address: "coin_registry".to_string(),
+            coin_registry_address: coin_registry.to_string(),
+            timelock_delay: 86_400,
+            canceller: None,
         };
 
         let factory = router
@@ -88,38 +126,88 @@ impl FactoryHelper {
         Self {
             // owner: owner.clone(),
             factory,
+            coin_registry,
             cw20_token_code_id,
         }
     }
 
+    /// Registers the precisions of native coins, which pairs need to observe swaps of them.
+    pub fn add_native_coins(
+        &self,
+        router: &mut TestApp,
+        owner: &Addr,
+        native_coins: &[(&str, u8)],
+    ) -> AnyResult<AppResponse> {
+        router.execute_contract(
+            owner.clone(),
+            self.coin_registry.clone(),
+            &palomadex::native_coin_registry::ExecuteMsg::Add {
+                native_coins: native_coins
+                    .iter()
+                    .map(|(denom, precision)| (denom.to_string(), *precision))
+                    .collect(),
+            },
+            &[],
+        )
+    }
+
     pub fn update_config(
         &mut self,
-        router: &mut App,
+        router: &mut TestApp,
         sender: &Addr,
         token_code_id: Option<u64>,
         fee_address: Option<String>,
         generator_address: Option<String>,
         whitelist_code_id: Option<u64>,
         coin_registry_address: Option<String>,
-        bonding_curve_factory_address: Option<String>,
-        pusd_denom: Option<String>,
+        guardian: Option<String>,
     ) -> AnyResult<AppResponse> {
-        let msg = palomadex::factory::ExecuteMsg::UpdateConfig {
+        let change = TimelockedChange::UpdateConfig {
             token_code_id,
             fee_address,
             generator_address,
             whitelist_code_id,
             coin_registry_address,
-            bonding_curve_factory_address,
-            pusd_denom,
+            guardian,
+            timelock_delay: None,
+            canceller: None,
         };
 
+        self.apply_change(router, sender, change)
+    }
+
+    /// Queues `change` and executes it once the timelock delay has passed.
+    pub fn apply_change(
+        &mut self,
+        router: &mut TestApp,
+        sender: &Addr,
+        change: TimelockedChange,
+    ) -> AnyResult<AppResponse> {
+        let config: ConfigResponse = router
+            .wrap()
+            .query_wasm_smart(&self.factory, &palomadex::factory::QueryMsg::Config {})?;
+        let msg = palomadex::factory::ExecuteMsg::ProposeChange {
+            change,
+            eta: router.block_info().time.seconds() + config.timelock_delay,
+        };
+        let res = router.execute_contract(sender.clone(), self.factory.clone(), &msg, &[])?;
+        let id = res
+            .custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == "id")
+            .unwrap()
+            .value
+            .parse()
+            .unwrap();
+
+        router.update_block(|block| block.time = block.time.plus_seconds(config.timelock_delay));
+        let msg = palomadex::factory::ExecuteMsg::ExecuteChange { id };
         router.execute_contract(sender.clone(), self.factory.clone(), &msg, &[])
     }
 
     pub fn create_pair(
         &mut self,
-        router: &mut App,
+        router: &mut TestApp,
         sender: &Addr,
         pair_type: PairType,
         tokens: [&Addr; 2],
@@ -145,7 +233,7 @@ impl FactoryHelper {
 }
 
 pub fn instantiate_token(
-    app: &mut App,
+    app: &mut TestApp,
     token_code_id: u64,
     owner: &Addr,
     token_name: &str,
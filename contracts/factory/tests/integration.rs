@@ -2,22 +2,26 @@
 
 mod factory_helper;
 
-use cosmwasm_std::{attr, Addr};
+use cosmwasm_std::{attr, coin, coins, from_json, to_json_binary, Addr, Uint128};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
-use palomadex::asset::{AssetInfo, PairInfo};
+use palomadex::asset::{Asset, AssetInfo, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
+use palomadex::common::Role;
 use palomadex::factory::{
-    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType,
+    PairsResponse, PairsToMigrateResponse, QueryMsg, QueuedChange, TimelockedChange,
+};
+use palomadex::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, MigrateMsg as PairMigrateMsg,
+    PoolResponse, QueryMsg as PairQueryMsg, XYKPoolConfig, XYKPoolUpdateParams,
 };
 
-use crate::factory_helper::{instantiate_token, FactoryHelper};
-use cw_multi_test::{App, ContractWrapper, Executor};
+use crate::factory_helper::{instantiate_token, mock_app, FactoryHelper, TestApp};
+use cw_multi_test::{ContractWrapper, Executor};
 use palomadex_factory::error::ContractError;
+use palomadex_pair::error::ContractError as PairContractError;
 
-fn mock_app() -> App {
-    App::default()
-}
-
-fn store_factory_code(app: &mut App) -> u64 {
+fn store_factory_code(app: &mut TestApp) -> u64 {
     let factory_contract = Box::new(
         ContractWrapper::new_with_empty(
             palomadex_factory::contract::execute,
@@ -34,7 +38,7 @@ fn store_factory_code(app: &mut App) -> u64 {
 fn proper_initialization() {
     let mut app = mock_app();
 
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
 
     let factory_code_id = store_factory_code(&mut app);
 
@@ -53,9 +57,11 @@ fn proper_initialization() {
         token_code_id: 123,
         fee_address: None,
         owner: owner.to_string(),
-        generator_address: Some(String::from("generator")),
+        generator_address: Some(app.api().addr_make("generator").to_string()),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: app.api().addr_make("coin_registry").to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = app
@@ -83,8 +89,10 @@ fn proper_initialization() {
 #[test]
 fn update_config() {
     let mut app = mock_app();
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
+    let fee = app.api().addr_make("fee");
+    let generator = app.api().addr_make("generator");
 
     // Update config
     helper
@@ -92,9 +100,8 @@ fn update_config() {
             &mut app,
             &owner,
             Some(200u64),
-            Some("fee".to_string()),
-            Some("generator".to_string()),
-            None,
+            Some(fee.to_string()),
+            Some(generator.to_string()),
             None,
             None,
             None,
@@ -107,25 +114,13 @@ fn update_config() {
         .unwrap();
 
     assert_eq!(200u64, config_res.token_code_id);
-    assert_eq!("fee", config_res.fee_address.unwrap().to_string());
-    assert_eq!(
-        "generator",
-        config_res.generator_address.unwrap().to_string()
-    );
+    assert_eq!(fee, config_res.fee_address.unwrap());
+    assert_eq!(generator, config_res.generator_address.unwrap());
 
     // Unauthorized err
+    let not_owner = app.api().addr_make("not_owner");
     let res = helper
-        .update_config(
-            &mut app,
-            &Addr::unchecked("not_owner"),
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-        )
+        .update_config(&mut app, &not_owner, None, None, None, None, None, None)
         .unwrap_err();
     assert_eq!(res.root_cause().to_string(), "Unauthorized");
 }
@@ -133,7 +128,7 @@ fn update_config() {
 #[test]
 fn test_create_pair() {
     let mut app = mock_app();
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
 
     let token1 = instantiate_token(
@@ -159,6 +154,28 @@ fn test_create_pair() {
         "Doubling assets in asset infos"
     );
 
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+    let factory = helper.factory.clone();
+    let predict_pair_address = |app: &TestApp, pair_type: PairType| -> Addr {
+        app.wrap()
+            .query_wasm_smart(
+                &factory,
+                &QueryMsg::PredictPairAddress {
+                    pair_type,
+                    asset_infos: asset_infos.clone(),
+                },
+            )
+            .unwrap()
+    };
+    let predicted = predict_pair_address(&app, PairType::Xyk {});
+
     let res = helper
         .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
         .unwrap();
@@ -179,41 +196,65 @@ fn test_create_pair() {
         .query_wasm_smart(
             helper.factory.clone(),
             &QueryMsg::Pair {
-                asset_infos: vec![
-                    AssetInfo::Token {
-                        contract_addr: token1.clone(),
-                    },
-                    AssetInfo::Token {
-                        contract_addr: token2.clone(),
-                    },
-                ],
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
             },
         )
         .unwrap();
 
-    // In multitest, contract names are counted in the order in which contracts are created
-    assert_eq!("contract0", helper.factory.to_string());
-    assert_eq!("contract3", res.contract_addr.to_string());
-    assert_eq!("contract4", res.liquidity_token.to_string());
+    // The pair is instantiated at the predicted address
+    assert_eq!(predicted, res.contract_addr);
 
-    // Create disabled pair type
+    // Other pair types are instantiated at different addresses
+    let predicted = predict_pair_address(&app, PairType::Custom("transmuter".to_string()));
+    assert_ne!(predicted, res.contract_addr);
+
+    // A pair recreated after deregistering is instantiated at a new address
     app.execute_contract(
         owner.clone(),
         helper.factory.clone(),
-        &ExecuteMsg::UpdatePairConfig {
-            config: PairConfig {
-                code_id: 0,
-                pair_type: PairType::Custom("Custom".to_string()),
-                total_fee_bps: 100,
-                maker_fee_bps: 40,
-                is_disabled: true,
-                is_generator_disabled: false,
-                permissioned: false,
-            },
+        &ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
         &[],
     )
     .unwrap();
+    let predicted = predict_pair_address(&app, PairType::Xyk {});
+    assert_ne!(predicted, res.contract_addr);
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let recreated: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            helper.factory.clone(),
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(recreated.contract_addr, predicted);
+
+    // Create disabled pair type
+    helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::UpdatePairConfig {
+                config: PairConfig {
+                    code_id: 0,
+                    pair_type: PairType::Custom("Custom".to_string()),
+                    total_fee_bps: 100,
+                    maker_fee_bps: 40,
+                    is_disabled: true,
+                    is_generator_disabled: false,
+                    permissioned: false,
+                },
+            },
+        )
+        .unwrap();
 
     let token3 = instantiate_token(
         &mut app,
@@ -223,10 +264,11 @@ fn test_create_pair() {
         Some(18),
     );
 
+    let someone = app.api().addr_make("someone");
     let err = helper
         .create_pair(
             &mut app,
-            &Addr::unchecked("someone"),
+            &someone,
             PairType::Custom("Custom".to_string()),
             [&token1, &token3],
             None,
@@ -241,6 +283,7 @@ fn test_create_pair() {
             &helper.factory,
             &QueryMsg::FeeInfo {
                 pair_type: PairType::Custom("Custom".to_string()),
+                pair_addr: None,
             },
         )
         .unwrap();
@@ -258,10 +301,10 @@ fn test_create_pair() {
 #[test]
 fn check_update_owner() {
     let mut app = mock_app();
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let helper = FactoryHelper::init(&mut app, &owner);
 
-    let new_owner = String::from("new_owner");
+    let new_owner = app.api().addr_make("new_owner").to_string();
 
     // New owner
     let msg = ExecuteMsg::ProposeNewOwner {
@@ -272,7 +315,7 @@ fn check_update_owner() {
     // Unauthed check
     let err = app
         .execute_contract(
-            Addr::unchecked("not_owner"),
+            app.api().addr_make("not_owner"),
             helper.factory.clone(),
             &msg,
             &[],
@@ -295,13 +338,18 @@ fn check_update_owner() {
     );
 
     // Propose new owner
-    app.execute_contract(Addr::unchecked("owner"), helper.factory.clone(), &msg, &[])
-        .unwrap();
+    app.execute_contract(
+        app.api().addr_make("owner"),
+        helper.factory.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
 
     // Claim from invalid addr
     let err = app
         .execute_contract(
-            Addr::unchecked("invalid_addr"),
+            app.api().addr_make("invalid_addr"),
             helper.factory.clone(),
             &ExecuteMsg::ClaimOwnership {},
             &[],
@@ -344,8 +392,13 @@ fn check_update_owner() {
     );
 
     // Propose new owner again
-    app.execute_contract(Addr::unchecked("owner"), helper.factory.clone(), &msg, &[])
-        .unwrap();
+    app.execute_contract(
+        app.api().addr_make("owner"),
+        helper.factory.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
     // Claim ownership
     app.execute_contract(
         Addr::unchecked(new_owner.clone()),
@@ -365,16 +418,17 @@ fn check_update_owner() {
 #[test]
 fn test_create_permissioned_pair() {
     let mut app = mock_app();
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
 
     let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
     let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
 
+    let random_stranger = app.api().addr_make("random_stranger");
     let err = helper
         .create_pair(
             &mut app,
-            &Addr::unchecked("random_stranger"),
+            &random_stranger,
             PairType::Custom("transmuter".to_string()),
             [&token1, &token2],
             None,
@@ -394,4 +448,1279 @@ fn test_create_permissioned_pair() {
             None,
         )
         .unwrap();
+
+    // Listers can create permissioned pairs as well
+    let lister = app.api().addr_make("lister");
+    let token3 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenZ", None);
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::GrantRole {
+            role: Role::Lister,
+            account: lister.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &lister,
+            PairType::Custom("transmuter".to_string()),
+            [&token1, &token3],
+            None,
+        )
+        .unwrap();
+}
+
+#[test]
+fn test_create_pairs_of_different_types() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token1.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: token2.clone(),
+        },
+    ];
+    let transmuter = PairType::Custom("transmuter".to_string());
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            transmuter.clone(),
+            [&token2, &token1],
+            None,
+        )
+        .unwrap();
+
+    let err = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            transmuter.clone(),
+            [&token1, &token2],
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairWasCreated {}
+    );
+
+    let query_pair = |app: &TestApp, pair_type: Option<PairType>| {
+        app.wrap().query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type,
+            },
+        )
+    };
+
+    // The original pool is returned when no pair type is specified
+    let xyk_pair = query_pair(&app, None).unwrap();
+    assert_eq!(query_pair(&app, Some(PairType::Xyk {})).unwrap(), xyk_pair);
+
+    let transmuter_pair = query_pair(&app, Some(transmuter.clone())).unwrap();
+    assert_ne!(transmuter_pair.contract_addr, xyk_pair.contract_addr);
+
+    query_pair(&app, Some(PairType::Stable {})).unwrap_err();
+
+    let res: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.pairs, vec![xyk_pair.clone(), transmuter_pair.clone()]);
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some(transmuter.clone()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    query_pair(&app, Some(transmuter)).unwrap_err();
+    assert_eq!(query_pair(&app, None).unwrap(), xyk_pair);
+}
+
+#[test]
+fn test_pair_fee_override() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: token1.clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: token2.clone(),
+                    },
+                ],
+                pair_type: None,
+            },
+        )
+        .unwrap();
+
+    let factory = helper.factory.clone();
+    let query_fee_info = |app: &TestApp, pair_addr: Option<String>| -> FeeInfoResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &factory,
+                &QueryMsg::FeeInfo {
+                    pair_type: PairType::Xyk {},
+                    pair_addr,
+                },
+            )
+            .unwrap()
+    };
+
+    let set_override = TimelockedChange::SetPairFeeOverride {
+        pair_addr: pair.contract_addr.to_string(),
+        total_fee_bps: 5,
+        maker_fee_bps: 1,
+    };
+
+    let not_owner = app.api().addr_make("not_owner");
+    let err = helper
+        .apply_change(&mut app, &not_owner, set_override.clone())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::SetPairFeeOverride {
+                pair_addr: pair.contract_addr.to_string(),
+                total_fee_bps: 10_001,
+                maker_fee_bps: 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairConfigInvalidFeeBps {}
+    );
+
+    // Only registered pairs can get a fee override
+    let err = helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::SetPairFeeOverride {
+                pair_addr: token1.to_string(),
+                total_fee_bps: 5,
+                maker_fee_bps: 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairNotRegistered {
+            pair_addr: token1.to_string()
+        }
+    );
+
+    // Fee overrides only take effect after the timelock delay
+    let eta = app.block_info().time.seconds() + 86_400;
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ProposeChange {
+            change: set_override.clone(),
+            eta,
+        },
+        &[],
+    )
+    .unwrap();
+    let fee_info = query_fee_info(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(100, fee_info.total_fee_bps);
+    assert_eq!(10, fee_info.maker_fee_bps);
+
+    let queued: Vec<QueuedChange> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::QueuedChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(86_400));
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ExecuteChange {
+            id: queued.last().unwrap().id,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_info = query_fee_info(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(5, fee_info.total_fee_bps);
+    assert_eq!(1, fee_info.maker_fee_bps);
+
+    // Pair type fees are unchanged
+    let fee_info = query_fee_info(&app, None);
+    assert_eq!(100, fee_info.total_fee_bps);
+    assert_eq!(10, fee_info.maker_fee_bps);
+
+    helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::RemovePairFeeOverride {
+                pair_addr: pair.contract_addr.to_string(),
+            },
+        )
+        .unwrap();
+
+    let fee_info = query_fee_info(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(100, fee_info.total_fee_bps);
+    assert_eq!(10, fee_info.maker_fee_bps);
+
+    // Deregistering the pair drops its fee override
+    helper.apply_change(&mut app, &owner, set_override).unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: pair.asset_infos.clone(),
+            pair_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_info = query_fee_info(&app, Some(pair.contract_addr.to_string()));
+    assert_eq!(100, fee_info.total_fee_bps);
+    assert_eq!(10, fee_info.maker_fee_bps);
+}
+
+#[test]
+fn test_migrate_pairs() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC", "tokenD"]
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None));
+
+    for pair_tokens in [
+        [&tokens[0], &tokens[1]],
+        [&tokens[0], &tokens[2]],
+        [&tokens[0], &tokens[3]],
+    ] {
+        helper
+            .create_pair(&mut app, &owner, PairType::Xyk {}, pair_tokens, None)
+            .unwrap();
+    }
+    helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Custom("transmuter".to_string()),
+            [&tokens[1], &tokens[2]],
+            None,
+        )
+        .unwrap();
+
+    let new_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            palomadex_pair::contract::execute,
+            palomadex_pair::contract::instantiate,
+            palomadex_pair::contract::query,
+        )
+        .with_reply_empty(palomadex_pair::contract::reply)
+        .with_migrate_empty(palomadex_pair::contract::migrate),
+    ));
+
+    let migrate_msg = |pair_type: PairType| ExecuteMsg::MigratePairs {
+        pair_type,
+        new_code_id,
+        msg: to_json_binary(&PairMigrateMsg {}).unwrap(),
+        limit: Some(2),
+    };
+
+    let err = app
+        .execute_contract(
+            app.api().addr_make("not_owner"),
+            helper.factory.clone(),
+            &migrate_msg(PairType::Xyk {}),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Pairs can't be migrated until the migration is approved through the timelock
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &migrate_msg(PairType::Xyk {}),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationNotApproved {
+            pair_type: "xyk".to_string()
+        }
+    );
+
+    let approve_migration = |pair_type: PairType| TimelockedChange::MigratePairs {
+        pair_type,
+        new_code_id,
+        msg: to_json_binary(&PairMigrateMsg {}).unwrap(),
+    };
+    helper
+        .apply_change(&mut app, &owner, approve_migration(PairType::Xyk {}))
+        .unwrap();
+
+    let pending: PairsToMigrateResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairsToMigrate {})
+        .unwrap();
+    assert_eq!(pending.pair_type, Some(PairType::Xyk {}));
+    assert_eq!(pending.new_code_id, Some(new_code_id));
+    assert_eq!(pending.pairs.len(), 3);
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &migrate_msg(PairType::Xyk {}),
+        &[],
+    )
+    .unwrap();
+
+    let pending: PairsToMigrateResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairsToMigrate {})
+        .unwrap();
+    assert_eq!(pending.pairs.len(), 1);
+
+    // The migration must be continued with the same code ID and message
+    for msg in [
+        ExecuteMsg::MigratePairs {
+            pair_type: PairType::Xyk {},
+            new_code_id: new_code_id + 1,
+            msg: to_json_binary(&PairMigrateMsg {}).unwrap(),
+            limit: Some(2),
+        },
+        ExecuteMsg::MigratePairs {
+            pair_type: PairType::Xyk {},
+            new_code_id,
+            msg: to_json_binary(&[0u8]).unwrap(),
+            limit: Some(2),
+        },
+    ] {
+        let err = app
+            .execute_contract(owner.clone(), helper.factory.clone(), &msg, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::MigrationMismatch {
+                pair_type: "xyk".to_string(),
+                new_code_id
+            }
+        );
+    }
+
+    // Another pair type can't be migrated until the current migration is finished
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &migrate_msg(PairType::Custom("transmuter".to_string())),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationInProgress {
+            pair_type: "xyk".to_string()
+        }
+    );
+    let err = helper
+        .apply_change(
+            &mut app,
+            &owner,
+            approve_migration(PairType::Custom("transmuter".to_string())),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MigrationInProgress {
+            pair_type: "xyk".to_string()
+        }
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &migrate_msg(PairType::Xyk {}),
+        &[],
+    )
+    .unwrap();
+
+    let pending: PairsToMigrateResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairsToMigrate {})
+        .unwrap();
+    assert_eq!(
+        pending,
+        PairsToMigrateResponse {
+            pair_type: None,
+            new_code_id: None,
+            pairs: vec![],
+        }
+    );
+
+    let res: PairsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pairs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    for pair in res.pairs {
+        let code_id = app
+            .wrap()
+            .query_wasm_contract_info(&pair.contract_addr)
+            .unwrap()
+            .code_id;
+        let pair_type: PairType = if pair.asset_infos.contains(&AssetInfo::Token {
+            contract_addr: tokens[0].clone(),
+        }) {
+            PairType::Xyk {}
+        } else {
+            PairType::Custom("transmuter".to_string())
+        };
+        assert_eq!(code_id == new_code_id, pair_type == PairType::Xyk {});
+    }
+}
+
+#[test]
+fn test_deregister_drops_pending_migration() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC"]
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None));
+    for pair_tokens in [[&tokens[0], &tokens[1]], [&tokens[0], &tokens[2]]] {
+        helper
+            .create_pair(&mut app, &owner, PairType::Xyk {}, pair_tokens, None)
+            .unwrap();
+    }
+
+    let new_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            palomadex_pair::contract::execute,
+            palomadex_pair::contract::instantiate,
+            palomadex_pair::contract::query,
+        )
+        .with_reply_empty(palomadex_pair::contract::reply)
+        .with_migrate_empty(palomadex_pair::contract::migrate),
+    ));
+
+    helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::MigratePairs {
+                pair_type: PairType::Xyk {},
+                new_code_id,
+                msg: to_json_binary(&PairMigrateMsg {}).unwrap(),
+            },
+        )
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::MigratePairs {
+            pair_type: PairType::Xyk {},
+            new_code_id,
+            msg: to_json_binary(&PairMigrateMsg {}).unwrap(),
+            limit: Some(1),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let pending: PairsToMigrateResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairsToMigrate {})
+        .unwrap();
+    assert_eq!(pending.pairs.len(), 1);
+    let pair: PairInfo = app
+        .wrap()
+        .query_wasm_smart(&pending.pairs[0], &PairQueryMsg::Pair {})
+        .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: pair.asset_infos,
+            pair_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // The deregistered pair was the last pending one, so the migration is finished
+    let pending: PairsToMigrateResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::PairsToMigrate {})
+        .unwrap();
+    assert_eq!(
+        pending,
+        PairsToMigrateResponse {
+            pair_type: None,
+            new_code_id: None,
+            pairs: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_create_pair_and_provide() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let user = app.api().addr_make("user");
+    let receiver = app.api().addr_make("receiver");
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &user, coins(2_000_000, "uluna"))
+            .unwrap()
+    });
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let token = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    app.execute_contract(
+        owner.clone(),
+        token.clone(),
+        &Cw20ExecuteMsg::Mint {
+            recipient: user.to_string(),
+            amount: Uint128::new(1_000_000),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: token.clone(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    let assets = vec![
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::new(1_000_000),
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    ];
+    let msg = ExecuteMsg::CreatePairAndProvide {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        init_params: None,
+        assets: assets.clone(),
+        receiver: Some(receiver.to_string()),
+    };
+
+    // Native coins must match the assets
+    let err = app
+        .execute_contract(
+            user.clone(),
+            helper.factory.clone(),
+            &msg,
+            &coins(500_000, "uluna"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Native token balance mismatch between the argument (1000000uluna) and the transferred (500000uluna)"
+    );
+
+    // The factory isn't allowed to transfer cw20 tokens yet, so the pair isn't created either
+    app.execute_contract(
+        user.clone(),
+        helper.factory.clone(),
+        &msg,
+        &coins(1_000_000, "uluna"),
+    )
+    .unwrap_err();
+    let pair_query = QueryMsg::Pair {
+        asset_infos: asset_infos.clone(),
+        pair_type: None,
+    };
+    app.wrap()
+        .query_wasm_smart::<PairInfo>(&helper.factory, &pair_query)
+        .unwrap_err();
+
+    app.execute_contract(
+        user.clone(),
+        token.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: helper.factory.to_string(),
+            amount: Uint128::new(1_000_000),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user.clone(),
+        helper.factory.clone(),
+        &msg,
+        &coins(1_000_000, "uluna"),
+    )
+    .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &pair_query)
+        .unwrap();
+
+    let pool: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_info.contract_addr, &PairQueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.assets, assets);
+
+    // LP tokens are minted to the receiver, nothing is left in the factory
+    let lp_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_info.liquidity_token,
+            &Cw20QueryMsg::Balance {
+                address: receiver.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        lp_balance.balance,
+        Uint128::new(1_000_000) - MINIMUM_LIQUIDITY_AMOUNT
+    );
+
+    let factory_balance: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &token,
+            &Cw20QueryMsg::Balance {
+                address: helper.factory.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(factory_balance.balance, Uint128::zero());
+    assert_eq!(
+        app.wrap()
+            .query_balance(&helper.factory, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_pause_pairs() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let guardian = app.api().addr_make("guardian");
+    let user = app.api().addr_make("user");
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &user,
+                vec![coin(10_000_000, "uluna"), coin(10_000_000, "uusd")],
+            )
+            .unwrap()
+    });
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+    helper
+        .add_native_coins(&mut app, &owner, &[("uluna", 6), ("uusd", 6)])
+        .unwrap();
+    helper
+        .update_config(
+            &mut app,
+            &owner,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(guardian.to_string()),
+        )
+        .unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+    let assets = vec![
+        Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::new(1_000_000),
+        },
+        Asset {
+            info: asset_infos[1].clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    ];
+    let funds = vec![coin(1_000_000, "uluna"), coin(1_000_000, "uusd")];
+    app.execute_contract(
+        user.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::CreatePairAndProvide {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+            assets: assets.clone(),
+            receiver: None,
+        },
+        &funds,
+    )
+    .unwrap();
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap();
+    let pair = pair_info.contract_addr.clone();
+
+    let swap_msg = PairExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: asset_infos[0].clone(),
+            amount: Uint128::new(1_000),
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let provide_msg = PairExecuteMsg::ProvideLiquidity {
+        assets,
+        slippage_tolerance: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let withdraw_msg = Cw20ExecuteMsg::Send {
+        contract: pair.to_string(),
+        amount: Uint128::new(1_000),
+        msg: to_json_binary(&PairCw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+    };
+    let is_paused = |app: &TestApp| -> bool {
+        app.wrap()
+            .query_wasm_smart(
+                &helper.factory,
+                &QueryMsg::IsPaused {
+                    pair_addr: pair.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    // Only the owner or the guardian can pause pairs
+    let err = app
+        .execute_contract(
+            user.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::PausePairs {
+                pairs: Some(vec![pair.to_string()]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        guardian.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::PausePairs {
+            pairs: Some(vec![pair.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(is_paused(&app));
+
+    let err = app
+        .execute_contract(
+            user.clone(),
+            pair.clone(),
+            &swap_msg,
+            &coins(1_000, "uluna"),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<PairContractError>().unwrap(),
+        PairContractError::Paused {}
+    );
+    let err = app
+        .execute_contract(user.clone(), pair.clone(), &provide_msg, &funds)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<PairContractError>().unwrap(),
+        PairContractError::Paused {}
+    );
+
+    // Liquidity can still be withdrawn from a paused pair
+    app.execute_contract(
+        user.clone(),
+        pair_info.liquidity_token.clone(),
+        &withdraw_msg,
+        &[],
+    )
+    .unwrap();
+
+    // Only the owner can unpause pairs
+    let err = app
+        .execute_contract(
+            guardian.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::UnpausePairs {
+                pairs: Some(vec![pair.to_string()]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UnpausePairs {
+            pairs: Some(vec![pair.to_string()]),
+        },
+        &[],
+    )
+    .unwrap();
+    assert!(!is_paused(&app));
+    app.execute_contract(
+        user.clone(),
+        pair.clone(),
+        &swap_msg,
+        &coins(1_000, "uluna"),
+    )
+    .unwrap();
+
+    // The owner can pause all pairs at once
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::PausePairs { pairs: None },
+        &[],
+    )
+    .unwrap();
+    assert!(is_paused(&app));
+    let err = app
+        .execute_contract(user.clone(), pair.clone(), &provide_msg, &funds)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<PairContractError>().unwrap(),
+        PairContractError::Paused {}
+    );
+    app.execute_contract(
+        user.clone(),
+        pair_info.liquidity_token.clone(),
+        &withdraw_msg,
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::UnpausePairs { pairs: None },
+        &[],
+    )
+    .unwrap();
+    assert!(!is_paused(&app));
+    app.execute_contract(user.clone(), pair.clone(), &provide_msg, &funds)
+        .unwrap();
+}
+
+#[test]
+fn test_timelocked_changes() {
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
+    let canceller = app.api().addr_make("canceller");
+    let someone = app.api().addr_make("someone");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let token1 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenX", None);
+    let token2 = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "tokenY", None);
+    helper
+        .create_pair(&mut app, &owner, PairType::Xyk {}, [&token1, &token2], None)
+        .unwrap();
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::Pair {
+                asset_infos: vec![
+                    AssetInfo::Token {
+                        contract_addr: token1.clone(),
+                    },
+                    AssetInfo::Token {
+                        contract_addr: token2.clone(),
+                    },
+                ],
+                pair_type: None,
+            },
+        )
+        .unwrap();
+    let pair = pair_info.contract_addr;
+
+    // The delay itself goes through the queue
+    helper
+        .apply_change(
+            &mut app,
+            &owner,
+            TimelockedChange::UpdateConfig {
+                token_code_id: None,
+                fee_address: None,
+                generator_address: None,
+                whitelist_code_id: None,
+                coin_registry_address: None,
+                guardian: None,
+                timelock_delay: Some(86400),
+                canceller: Some(canceller.to_string()),
+            },
+        )
+        .unwrap();
+
+    // Pairs only accept config updates forwarded by the factory
+    let params = to_json_binary(&XYKPoolUpdateParams::EnableAssetBalancesTracking).unwrap();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &PairExecuteMsg::UpdateConfig {
+                params: params.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<PairContractError>().unwrap(),
+        PairContractError::Unauthorized {}
+    );
+
+    let now = app.block_info().time.seconds();
+    let change = TimelockedChange::UpdatePair {
+        pair_addr: pair.to_string(),
+        params,
+    };
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ProposeChange {
+                change: change.clone(),
+                eta: now + 86399,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockEtaTooEarly {
+            earliest: now + 86400
+        }
+    );
+
+    let err = app
+        .execute_contract(
+            someone.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ProposeChange {
+                change: change.clone(),
+                eta: now + 86400,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    for _ in 0..2 {
+        app.execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ProposeChange {
+                change: change.clone(),
+                eta: now + 86400,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let queued: Vec<QueuedChange> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::QueuedChanges {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let ids: Vec<u64> = queued.iter().map(|queued| queued.id).collect();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(queued[0].eta, now + 86400);
+    assert_eq!(queued[0].change, change);
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ExecuteChange { id: ids[0] },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockNotReady {
+            id: ids[0],
+            eta: now + 86400
+        }
+    );
+
+    // Only the owner or the canceller may cancel
+    let err = app
+        .execute_contract(
+            someone.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::CancelChange { id: ids[1] },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(
+        canceller.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::CancelChange { id: ids[1] },
+        &[],
+    )
+    .unwrap();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ExecuteChange { id: ids[1] },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::QueuedChangeNotFound { id: ids[1] }
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(86400));
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ExecuteChange { id: ids[0] },
+        &[],
+    )
+    .unwrap();
+
+    let config: palomadex::pair::ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair, &PairQueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert!(params.track_asset_balances);
+
+    // A change that is not executed within the grace period expires
+    let now = app.block_info().time.seconds();
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ProposeChange {
+            change: change.clone(),
+            eta: now + 86400,
+        },
+        &[],
+    )
+    .unwrap();
+    let queued: Vec<QueuedChange> = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::QueuedChanges {
+                start_after: Some(ids[1]),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(queued.len(), 1);
+    let id = queued[0].id;
+
+    app.update_block(|block| block.time = block.time.plus_seconds(86400 + 1_209_601));
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ExecuteChange { id },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockExpired {
+            id,
+            expired_at: now + 86400 + 1_209_600
+        }
+    );
+
+    // Only registered pairs can be updated
+    let now = app.block_info().time.seconds();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ProposeChange {
+                change: TimelockedChange::UpdatePair {
+                    pair_addr: token1.to_string(),
+                    params: to_json_binary(&XYKPoolUpdateParams::EnableAssetBalancesTracking)
+                        .unwrap(),
+                },
+                eta: now + 86400,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairNotRegistered {
+            pair_addr: token1.to_string()
+        }
+    );
+
+    // A pair deregistered while the change is queued can't be updated anymore
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::ProposeChange {
+            change: change.clone(),
+            eta: now + 86400,
+        },
+        &[],
+    )
+    .unwrap();
+    let id = app
+        .wrap()
+        .query_wasm_smart::<Vec<QueuedChange>>(
+            &helper.factory,
+            &QueryMsg::QueuedChanges {
+                start_after: Some(id),
+                limit: None,
+            },
+        )
+        .unwrap()[0]
+        .id;
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::Deregister {
+            asset_infos: pair_info.asset_infos.clone(),
+            pair_type: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(86400));
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::ExecuteChange { id },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PairNotRegistered {
+            pair_addr: pair.to_string()
+        }
+    );
 }
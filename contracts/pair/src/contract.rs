@@ -9,7 +9,7 @@ use cosmwasm_std::{
     Env, Fraction, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult,
     SubMsg, SubMsgResponse, SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 
 use cw_utils::parse_instantiate_response_data;
@@ -17,20 +17,27 @@ use palomadex::asset::{
     addr_opt_validate, check_swap_parameters, format_lp_token_name, Asset, AssetInfo, CoinsExt,
     PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
+use palomadex::cosmwasm_ext::IntegerToDecimal;
 use palomadex::factory::PairType;
+use palomadex::observation::{
+    accumulate_swap_sizes, init_candles, observation_asset_indexes, query_candles,
+    query_observation, query_observation_stats, query_observations, query_twap, set_ema_half_life,
+    update_candles, PrecommitObservation, OBSERVATIONS_SIZE,
+};
 use palomadex::pair::{
-    ConfigResponse, FeeShareConfig, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
+    check_deadline, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
 };
 use palomadex::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
+    ConfigResponse, FeeShareConfig, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
+    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS, MIN_TRADE_SIZE,
 };
-use palomadex::querier::{query_factory_config, query_fee_info, query_supply};
+use palomadex::querier::{query_factory_config, query_fee_info, query_pair_paused, query_supply};
 use palomadex::{token::InstantiateMsg as TokenInstantiateMsg, U256};
+use palomadex_circular_buffer::BufferManager;
 
 use crate::error::ContractError;
-use crate::state::{Config, BALANCES, CONFIG};
+use crate::state::{get_precision, Config, BALANCES, CONFIG, OBSERVATIONS};
 
 /// A `reply` call code ID used for sub-messages.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
@@ -39,7 +46,6 @@ const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 const CONTRACT_NAME: &str = "crates.io:palomadex-pair";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -88,6 +94,8 @@ pub fn instantiate(
     }
 
     CONFIG.save(deps.storage, &config)?;
+    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+    init_candles(deps.storage)?;
 
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
@@ -164,7 +172,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 /// Exposes all the execute functions available in the contract.
 ///
 /// ## Variants
-/// * **ExecuteMsg::UpdateConfig { params: Binary }** Not supported.
+/// * **ExecuteMsg::UpdateConfig { params: Binary }** Updates the contract configuration with the specified
+///   input parameters. Forwarded by the factory through its timelock queue.
 ///
 /// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
 ///   it depending on the received template.
@@ -173,6 +182,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             assets,
 ///             slippage_tolerance,
 ///             receiver,
+///             min_lp_to_receive,
+///             deadline,
 ///         }** Provides liquidity in the pair with the specified input parameters.
 ///
 /// * **ExecuteMsg::Swap {
@@ -180,7 +191,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             belief_price,
 ///             max_spread,
 ///             to,
+///             deadline,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut { ask_asset, max_offer, to, deadline }** Swaps just enough native tokens to receive the specified ask amount.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -194,14 +208,27 @@ pub fn execute(
             assets,
             slippage_tolerance,
             receiver,
-        } => provide_liquidity(deps, env, info, assets, slippage_tolerance, receiver),
+            min_lp_to_receive,
+            deadline,
+        } => provide_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            slippage_tolerance,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            check_deadline(&env, deadline)?;
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -220,6 +247,30 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            check_deadline(&env, deadline)?;
+            max_offer.info.check(deps.api)?;
+            if !max_offer.is_native_token() {
+                return Err(ContractError::Cw20DirectSwap {});
+            }
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                max_offer,
+                ask_asset,
+                to_addr,
+            )
+        }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
     }
 }
@@ -238,23 +289,12 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
             ..
         } => {
+            check_deadline(&env, deadline)?;
             // Only asset contract can execute this message
-            let mut authorized = false;
-            let config = CONFIG.load(deps.storage)?;
-
-            for pool in config.pair_info.asset_infos {
-                if let AssetInfo::Token { contract_addr, .. } = &pool {
-                    if contract_addr == info.sender {
-                        authorized = true;
-                    }
-                }
-            }
-
-            if !authorized {
-                return Err(ContractError::Unauthorized {});
-            }
+            check_cw20_in_pool(&CONFIG.load(deps.storage)?, &info.sender)?;
 
             let to_addr = addr_opt_validate(deps.api, &to)?;
             let contract_addr = info.sender.clone();
@@ -273,6 +313,31 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
+        Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        } => {
+            check_deadline(&env, deadline)?;
+            // Only asset contract can execute this message
+            check_cw20_in_pool(&CONFIG.load(deps.storage)?, &info.sender)?;
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+            let contract_addr = info.sender.clone();
+
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset,
+                to_addr,
+            )
+        }
         Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
             deps,
             env,
@@ -281,7 +346,22 @@ pub fn receive_cw20(
             cw20_msg.amount,
             assets,
         ),
+        Cw20HookMsg::WithdrawLiquidityOneCoin { .. } => Err(ContractError::NonSupported {}),
+    }
+}
+
+/// Checks that the cw20 token is part of the pool.
+///
+/// * **cw20_sender** is the cw20 token address which is being checked.
+fn check_cw20_in_pool(config: &Config, cw20_sender: &Addr) -> Result<(), ContractError> {
+    for asset_info in &config.pair_info.asset_infos {
+        match asset_info {
+            AssetInfo::Token { contract_addr } if contract_addr == cw20_sender => return Ok(()),
+            _ => {}
+        }
     }
+
+    Err(ContractError::Unauthorized {})
 }
 
 /// Provides liquidity in the pair with the specified input parameters.
@@ -297,7 +377,12 @@ pub fn receive_cw20(
 /// * **receiver** is an optional parameter which defines the receiver of the LP tokens.
 ///   If no custom receiver is specified, the pair will mint LP tokens for the function caller.
 ///
+/// * **min_lp_to_receive** is an optional lower bound on the amount of LP tokens minted.
+///
+/// * **deadline** is an optional block time (in seconds) after which the provision is rejected.
+///
 /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
@@ -305,7 +390,11 @@ pub fn provide_liquidity(
     assets: Vec<Asset>,
     slippage_tolerance: Option<Decimal>,
     receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    check_deadline(&env, deadline)?;
+
     if assets.len() != 2 {
         return Err(StdError::generic_err("asset_infos must contain exactly two elements").into());
     }
@@ -313,6 +402,11 @@ pub fn provide_liquidity(
     assets[1].info.check(deps.api)?;
 
     let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &env.contract.address)? {
+        return Err(ContractError::Paused {});
+    }
+
     info.funds
         .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
     let mut pools = config
@@ -393,6 +487,14 @@ pub fn provide_liquidity(
         )
     };
 
+    let min_lp_to_receive = min_lp_to_receive.unwrap_or_default();
+    if share < min_lp_to_receive {
+        return Err(ContractError::ProvideSlippageViolation(
+            share,
+            min_lp_to_receive,
+        ));
+    }
+
     // Mint LP tokens for the sender or for the receiver (if set)
     let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
     messages.extend(mint_liquidity_token_message(&config, &receiver, share)?);
@@ -472,11 +574,25 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.querier, &config)?;
 
-    let refund_assets = if assets.is_empty() {
+    let (refund_assets, burn_amount) = if assets.is_empty() {
         // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount, total_share)
+        (get_share_in_assets(&pools, amount, total_share), amount)
     } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
+        let fee_info = query_fee_info(
+            &deps.querier,
+            &config.factory_addr,
+            config.pair_info.pair_type.clone(),
+            &config.pair_info.contract_addr,
+        )?;
+        let (refund_assets, burn_amount) =
+            compute_imbalanced_withdraw(&pools, &assets, total_share, fee_info.total_fee_rate)?;
+        if burn_amount > amount {
+            return Err(ContractError::NotEnoughLpTokens {
+                required: burn_amount,
+                provided: amount,
+            });
+        }
+        (refund_assets, burn_amount)
     };
 
     if config.track_asset_balances {
@@ -501,20 +617,33 @@ pub fn withdraw_liquidity(
     }
 
     // Update the pool info
-    let messages: Vec<CosmosMsg> = vec![
-        refund_assets[0].clone().into_msg(sender.clone())?,
-        refund_assets[1].clone().into_msg(sender.clone())?,
-        CosmosMsg::Wasm(WasmMsg::Execute {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for refund_asset in refund_assets.iter().filter(|asset| !asset.amount.is_zero()) {
+        messages.push(refund_asset.clone().into_msg(sender.clone())?);
+    }
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount,
+        })?,
+        funds: vec![],
+    }));
+    // Return LP tokens that were not needed for an imbalanced withdrawal
+    if burn_amount < amount {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: config.pair_info.liquidity_token.to_string(),
-            msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: amount - burn_amount,
+            })?,
             funds: vec![],
-        }),
-    ];
+        }));
+    }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "withdraw_liquidity"),
         attr("sender", sender),
-        attr("withdrawn_share", amount),
+        attr("withdrawn_share", burn_amount),
         attr(
             "refund_assets",
             format!("{}, {}", refund_assets[0], refund_assets[1]),
@@ -546,6 +675,88 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
         .collect()
 }
 
+/// Returns the assets refunded and the amount of LP tokens burned by an imbalanced withdrawal.
+/// The withdrawal is priced as a balanced withdrawal followed by a swap of the surplus of one
+/// asset into the other against the remaining pool, with the swap charged `commission_rate`.
+///
+/// * **pools** is the array with assets in the pool.
+///
+/// * **assets** are the assets to withdraw. An asset that is not specified is not withdrawn.
+///
+/// * **total_share** is the total amount of LP tokens currently minted.
+///
+/// * **commission_rate** is the total amount of fees charged for swaps.
+pub fn compute_imbalanced_withdraw(
+    pools: &[Asset],
+    assets: &[Asset],
+    total_share: Uint128,
+    commission_rate: Decimal,
+) -> Result<(Vec<Asset>, Uint128), ContractError> {
+    if assets.len() > 2 {
+        return Err(ContractError::AssetMismatch {});
+    }
+    if assets.len() == 2 && assets[0].info.equal(&assets[1].info) {
+        return Err(ContractError::DoublingAssets {});
+    }
+    if assets
+        .iter()
+        .any(|asset| !pools.iter().any(|pool| pool.info.equal(&asset.info)))
+    {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .map(|pool| Asset {
+            info: pool.info.clone(),
+            amount: assets
+                .iter()
+                .find(|asset| asset.info.equal(&pool.info))
+                .map(|asset| asset.amount)
+                .unwrap_or_default(),
+        })
+        .collect();
+    if refund_assets.iter().all(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if refund_assets
+        .iter()
+        .zip(pools)
+        .any(|(refund, pool)| refund.amount >= pool.amount)
+    {
+        return Err(ContractError::WithdrawExceedsReserves {});
+    }
+
+    // The asset withdrawn in a larger proportion is bought with the surplus of the other one
+    let (out_idx, in_idx) = if Decimal256::from_ratio(refund_assets[0].amount, pools[0].amount)
+        >= Decimal256::from_ratio(refund_assets[1].amount, pools[1].amount)
+    {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let out_pool = pools[out_idx].amount;
+    let in_pool = pools[in_idx].amount;
+    let out_left = out_pool - refund_assets[out_idx].amount;
+    let in_left = in_pool - refund_assets[in_idx].amount;
+
+    // Keeping a share u of the LP supply and swapping with a fee f satisfies
+    // (1 - f) * in_pool / in_left * u^2 + f * u - out_left / out_pool = 0
+    let fee = Decimal256::from(commission_rate);
+    let a = (Decimal256::one() - fee) * Decimal256::from_ratio(in_pool, in_left);
+    let c = Decimal256::from_ratio(out_left, out_pool);
+    let four = Decimal256::from_ratio(4u8, 1u8);
+    let u = ((fee * fee + four * a * c).sqrt() - fee) / (a + a);
+
+    let share_left: Uint128 = (Decimal256::from_ratio(total_share, 1u8) * u)
+        .to_uint_floor()
+        .try_into()
+        .map_err(StdError::from)?;
+    let burn_amount = total_share.checked_sub(share_left)?;
+
+    Ok((refund_assets, burn_amount))
+}
+
 /// Performs an swap operation with the specified parameters. The trader must approve the
 /// pool contract to transfer offer assets from their wallet.
 ///
@@ -573,8 +784,76 @@ pub fn swap(
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
+    execute_swap(
+        deps,
+        env,
+        sender,
+        offer_asset,
+        SwapTarget::ExactIn {
+            belief_price,
+            max_spread,
+        },
+        to,
+    )
+}
+
+/// Swaps just enough of the offer asset to receive exactly the specified ask amount.
+/// The unused part of the offer asset is refunded to the sender.
+///
+/// * **sender** is the sender of the swap operation.
+///
+/// * **max_offer** asset sent for the swap, its amount is the most that can be spent.
+///
+/// * **ask_asset** asset and amount to receive.
+///
+/// * **to** sets the recipient of the swap operation.
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    max_offer: Asset,
+    ask_asset: Asset,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    max_offer.assert_sent_native_token_balance(&info)?;
+
+    execute_swap(
+        deps,
+        env,
+        sender,
+        max_offer,
+        SwapTarget::ExactOut(ask_asset),
+        to,
+    )
+}
+
+/// Describes which side of a swap is fixed by the trader.
+enum SwapTarget {
+    /// The whole offer amount is swapped, the return is checked against the spread limits
+    ExactIn {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    },
+    /// Exactly this asset is received, the offer amount is the most that can be spent
+    ExactOut(Asset),
+}
+
+/// Performs a swap of the deposited `offer_asset`, the contract balance already includes the deposit.
+fn execute_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    target: SwapTarget,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &env.contract.address)? {
+        return Err(ContractError::Paused {});
+    }
+
     // If the asset balance is already increased, we should subtract the user deposit from the pool amount
     let pools = config
         .pair_info
@@ -606,9 +885,31 @@ pub fn swap(
         &deps.querier,
         &config.factory_addr,
         config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
     )?;
 
-    let offer_amount = offer_asset.amount;
+    let offer_amount = match &target {
+        SwapTarget::ExactIn { .. } => offer_asset.amount,
+        SwapTarget::ExactOut(ask_asset) => {
+            if !ask_asset.info.equal(&ask_pool.info) {
+                return Err(ContractError::AssetMismatch {});
+            }
+
+            let required = compute_exact_out_offer_amount(
+                offer_pool.amount,
+                ask_pool.amount,
+                ask_asset.amount,
+                fee_info.total_fee_rate,
+            )?;
+            if required > offer_asset.amount {
+                return Err(ContractError::MaxOfferExceeded {
+                    required,
+                    max_offer: offer_asset.amount,
+                });
+            }
+            required
+        }
+    };
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
@@ -618,13 +919,19 @@ pub fn swap(
     )?;
 
     // Check the max spread limit (if it was specified)
-    assert_max_spread(
+    if let SwapTarget::ExactIn {
         belief_price,
         max_spread,
-        offer_amount,
-        return_amount + commission_amount,
-        spread_amount,
-    )?;
+    } = target
+    {
+        assert_max_spread(
+            belief_price,
+            max_spread,
+            offer_amount,
+            return_amount + commission_amount,
+            spread_amount,
+        )?;
+    }
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -693,7 +1000,7 @@ pub fn swap(
 
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
-        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+        accumulate_prices(env.clone(), &config, pools[0].amount, pools[1].amount)?
     {
         config.price0_cumulative_last = price0_cumulative_new;
         config.price1_cumulative_last = price1_cumulative_new;
@@ -701,10 +1008,53 @@ pub fn swap(
         CONFIG.save(deps.storage, &config)?;
     }
 
+    // Store observation from precommit data
+    accumulate_swap_sizes(deps.storage, &env, OBSERVATIONS)?;
+
+    // Store time series data in precommit observation.
+    // Skipping small unsafe values which can seriously mess oracle price due to rounding errors.
+    // This data will be reflected in observations on the next action.
+    let offer_precision = get_precision(
+        deps.storage,
+        &deps.querier,
+        &offer_pool.info,
+        &config.factory_addr,
+    )?;
+    let ask_precision = get_precision(
+        deps.storage,
+        &deps.querier,
+        &ask_pool.info,
+        &config.factory_addr,
+    )?;
+    if offer_amount.to_decimal256(offer_precision)? >= MIN_TRADE_SIZE
+        && return_amount.to_decimal256(ask_precision)? >= MIN_TRADE_SIZE
+    {
+        let (base_amount, quote_amount) = if offer_pool.info.equal(&pools[0].info) {
+            (offer_amount, return_amount)
+        } else {
+            (return_amount, offer_amount)
+        };
+        PrecommitObservation::save(deps.storage, &env, base_amount, quote_amount)?;
+        update_candles(deps.storage, &env, base_amount, quote_amount)?;
+    }
+
+    // Refund the part of the deposit that was not needed for an exact output swap
+    let refund_amount = offer_asset.amount.checked_sub(offer_amount)?;
+    if !refund_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: refund_amount,
+            }
+            .into_msg(sender.clone())?,
+        );
+    }
+
     Ok(Response::new()
         .add_messages(
             // 1. send collateral tokens from the contract to a user
             // 2. send inactive commission fees to the Maker contract
+            // 3. refund the unused offer amount
             messages,
         )
         .add_attributes(vec![
@@ -725,6 +1075,9 @@ pub fn swap(
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values.
+///
+/// ## Executor
+/// Only the factory can execute this, it forwards updates queued by the owner after the timelock delay.
 pub fn update_config(
     deps: DepsMut,
     env: Env,
@@ -732,9 +1085,9 @@ pub fn update_config(
     params: Binary,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
-    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
 
-    if info.sender != factory_config.owner {
+    // Config updates are forwarded by the factory after its timelock delay
+    if info.sender != config.factory_addr {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -800,6 +1153,18 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        XYKPoolUpdateParams::SetEmaHalfLife { ema_half_life } => {
+            if ema_half_life == 0 {
+                return Err(ContractError::InvalidEmaHalfLife {});
+            }
+            set_ema_half_life(deps.storage, ema_half_life)?;
+            response
+                .attributes
+                .push(attr("action", "set_ema_half_life"));
+            response
+                .attributes
+                .push(attr("ema_half_life", ema_half_life.to_string()));
+        }
     }
 
     Ok(response)
@@ -892,6 +1257,22 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
 ///   pool using a [`CumulativePricesResponse`] object.
 ///
+/// * **QueryMsg::Observe { seconds_ago, base_asset, quote_asset }** Returns the price of `quote_asset`
+///   denominated in `base_asset` recorded `seconds_ago` seconds ago. Swaps are observed with the first pool
+///   asset as the base one, so the price is inverted if the second pool asset is requested as the base one.
+///
+/// * **QueryMsg::Twap { start_seconds_ago, end_seconds_ago }** Returns the time-weighted average price
+///   over the window using a `TwapResponse` object.
+///
+/// * **QueryMsg::ObservationStats { window_seconds }** Returns the price EMA and realized volatility
+///   over the window using an `ObservationStatsResponse` object.
+///
+/// * **QueryMsg::Candles { resolution, start_after, limit }** Returns OHLC candles of the given resolution
+///   using a `CandlesResponse` object.
+///
+/// * **QueryMsg::Observations { start_after_ts, limit, order }** Returns raw price observations
+///   using an `ObservationsResponse` object.
+///
 /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified asset that was in the pool
@@ -909,11 +1290,60 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_json_binary(&query_reverse_simulation(deps, ask_asset)?)
         }
         QueryMsg::CumulativePrices {} => to_json_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Observe {
+            seconds_ago,
+            base_asset,
+            quote_asset,
+        } => {
+            let asset_infos = CONFIG.load(deps.storage)?.pair_info.asset_infos;
+            let indexes = observation_asset_indexes(&asset_infos, base_asset, quote_asset)?;
+            let observation = query_observation(deps, env, OBSERVATIONS, seconds_ago)?;
+            if indexes == (0, 1) {
+                to_json_binary(&observation)
+            } else {
+                to_json_binary(&observation.invert()?)
+            }
+        }
+        QueryMsg::Twap {
+            start_seconds_ago,
+            end_seconds_ago,
+        } => to_json_binary(&query_twap(
+            deps,
+            env,
+            OBSERVATIONS,
+            start_seconds_ago,
+            end_seconds_ago,
+        )?),
+        QueryMsg::ObservationStats { window_seconds } => to_json_binary(&query_observation_stats(
+            deps,
+            env,
+            OBSERVATIONS,
+            window_seconds,
+        )?),
+        QueryMsg::Candles {
+            resolution,
+            start_after,
+            limit,
+        } => to_json_binary(&query_candles(deps, resolution, start_after, limit)?),
+        QueryMsg::Observations {
+            start_after_ts,
+            limit,
+            order,
+        } => to_json_binary(&query_observations(
+            deps,
+            OBSERVATIONS,
+            start_after_ts,
+            limit,
+            order,
+        )?),
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
         QueryMsg::AssetBalanceAt {
             asset_info,
             block_height,
         } => to_json_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::SimulateWithdraw { assets } => {
+            to_json_binary(&query_simulate_withdraw(deps, assets)?)
+        }
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -944,6 +1374,27 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
     Ok(refund_assets)
 }
 
+/// Returns the amount of LP tokens that must be burned to withdraw the specified assets.
+///
+/// * **assets** are the assets to withdraw. An asset that is not specified is not withdrawn.
+pub fn query_simulate_withdraw(deps: Deps, assets: Vec<Asset>) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type,
+        &config.pair_info.contract_addr,
+    )?;
+
+    let (_, burn_amount) =
+        compute_imbalanced_withdraw(&pools, &assets, total_share, fee_info.total_fee_rate)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(burn_amount)
+}
+
 /// Returns information about a swap simulation in a [`SimulationResponse`] object.
 ///
 /// * **offer_asset** is the asset to swap as well as an amount of the said asset.
@@ -973,6 +1424,7 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
         &deps.querier,
         config.factory_addr,
         config.pair_info.pair_type,
+        &config.pair_info.contract_addr,
     )?;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
@@ -1022,6 +1474,7 @@ pub fn query_reverse_simulation(
         &deps.querier,
         config.factory_addr,
         config.pair_info.pair_type,
+        &config.pair_info.contract_addr,
     )?;
 
     let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
@@ -1200,6 +1653,47 @@ pub fn compute_offer_amount(
     Ok((offer_amount, spread_amount, commission_amount.try_into()?))
 }
 
+/// Returns the smallest offer amount for which [`compute_swap`] returns at least `ask_amount`
+/// after the commission is deducted.
+///
+/// * **offer_pool** total amount of offer assets in the pool.
+///
+/// * **ask_pool** total amount of ask assets in the pool.
+///
+/// * **ask_amount** amount of ask assets to receive after the commission.
+///
+/// * **commission_rate** total amount of fees charged for the swap.
+pub fn compute_exact_out_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Uint128> {
+    check_swap_parameters(vec![offer_pool, ask_pool], ask_amount)?;
+
+    // compute_swap rounds the return up and the commission down, so the amount received is
+    // ceil(return_amount * (1 - commission_rate)). It reaches ask_amount as soon as
+    // return_amount > (ask_amount - 1) / (1 - commission_rate)
+    let before_commission = (ask_amount - Uint128::one())
+        .checked_div_floor(Decimal::one() - commission_rate)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        + Uint128::one();
+    if before_commission >= ask_pool {
+        return Err(StdError::generic_err(
+            "Ask amount exceeds the available liquidity",
+        ));
+    }
+
+    // return_amount = ceil(ask_pool - cp / (offer_pool + offer_amount)) reaches before_commission
+    // as soon as offer_pool + offer_amount > cp / (ask_pool - before_commission + 1)
+    let cp = Uint256::from(offer_pool) * Uint256::from(ask_pool);
+    let offer_amount = (cp / Uint256::from(ask_pool - before_commission + Uint128::one())
+        + Uint256::one())
+    .checked_sub(offer_pool.into())?;
+
+    Ok(offer_amount.try_into()?)
+}
+
 /// If `belief_price` and `max_spread` are both specified, we compute a new spread,
 /// otherwise we just use the swap spread to check `max_spread`.
 ///
@@ -1284,6 +1778,35 @@ pub fn assert_slippage_tolerance(
     Ok(())
 }
 
+/// Manages the contract migration.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    match contract_version.contract.as_ref() {
+        CONTRACT_NAME => match contract_version.version.as_ref() {
+            "0.1.0" => {
+                // Pairs instantiated before observations were recorded have no buffer yet
+                if BufferManager::new(deps.storage, OBSERVATIONS).is_err() {
+                    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+                }
+                init_candles(deps.storage)?;
+            }
+            "0.2.0" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
+
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
 pub fn pool_info(querier: QuerierWrapper, config: &Config) -> StdResult<(Vec<Asset>, Uint128)> {
     let pools = config
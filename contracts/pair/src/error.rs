@@ -1,5 +1,9 @@
-use cosmwasm_std::{OverflowError, StdError};
-use palomadex::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use palomadex::{
+    asset::MINIMUM_LIQUIDITY_AMOUNT,
+    pair::{DeadlineExpired, MAX_FEE_SHARE_BPS},
+};
+use palomadex_circular_buffer::error::BufferError;
 use thiserror::Error;
 
 /// This enum describes pair contract errors
@@ -8,9 +12,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    CircularBuffer(#[from] BufferError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    Paused {},
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -29,6 +39,27 @@ pub enum ContractError {
     #[error("Operation exceeds max splippage tolerance")]
     MaxSlippageAssertion {},
 
+    #[error("Withdrawing the requested assets requires {required} LP tokens, but only {provided} were sent")]
+    NotEnoughLpTokens {
+        required: Uint128,
+        provided: Uint128,
+    },
+
+    #[error("Requested withdrawal exceeds the pool reserves")]
+    WithdrawExceedsReserves {},
+
+    #[error("Slippage is more than expected: received {0}, expected {1} LP tokens")]
+    ProvideSlippageViolation(Uint128, Uint128),
+
+    #[error("Swap requires {required} of the offer asset, but the maximum offer is {max_offer}")]
+    MaxOfferExceeded {
+        required: Uint128,
+        max_offer: Uint128,
+    },
+
+    #[error("{0}")]
+    DeadlineExpired(#[from] DeadlineExpired),
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -58,6 +89,9 @@ pub enum ContractError {
         MAX_FEE_SHARE_BPS
     )]
     FeeShareOutOfBounds {},
+
+    #[error("EMA half-life must be greater than zero")]
+    InvalidEmaHalfLife {},
 }
 
 impl From<OverflowError> for ContractError {
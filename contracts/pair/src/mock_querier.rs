@@ -7,8 +7,8 @@ use cosmwasm_std::{
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
-use palomadex::factory::FeeInfoResponse;
-use palomadex::factory::QueryMsg::FeeInfo;
+use palomadex::factory::QueryMsg::{FeeInfo, IsPaused};
+use palomadex::factory::{Config as FactoryConfig, FeeInfoResponse};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
 /// This uses the palomadex CustomQuerier.
@@ -90,6 +90,7 @@ impl WasmMockQuerier {
                             })
                             .into(),
                         ),
+                        IsPaused { .. } => SystemResult::Ok(to_json_binary(&false).into()),
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 } else {
@@ -143,9 +144,28 @@ impl WasmMockQuerier {
                     }
                 }
             }
-            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, .. }) => {
+            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
                 if contract_addr == "factory" {
-                    SystemResult::Ok(to_json_binary(&Vec::<Addr>::new()).into())
+                    if key.as_slice() == b"config".as_slice() {
+                        SystemResult::Ok(
+                            to_json_binary(&FactoryConfig {
+                                owner: Addr::unchecked("owner"),
+                                token_code_id: 0,
+                                fee_address: Some(Addr::unchecked("fee_address")),
+                                generator_address: None,
+                                whitelist_code_id: 0,
+                                coin_registry_address: Addr::unchecked("coin_registry"),
+                                guardian: None,
+                                timelock_delay: 0,
+                                canceller: None,
+                            })
+                            .into(),
+                        )
+                    } else {
+                        SystemResult::Ok(to_json_binary(&Vec::<Addr>::new()).into())
+                    }
+                } else if contract_addr == "coin_registry" {
+                    SystemResult::Ok(to_json_binary(&6).into())
                 } else {
                     panic!("DO NOT ENTER HERE");
                 }
@@ -1,10 +1,12 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, SnapshotMap};
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotMap};
 use palomadex::{
     asset::{AssetInfo, PairInfo},
+    observation::Observation,
     pair::FeeShareConfig,
 };
+use palomadex_circular_buffer::CircularBuffer;
 
 /// This structure stores the main config parameters for a constant product pair contract.
 #[cw_serde]
@@ -28,6 +30,26 @@ pub struct Config {
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Stores map of AssetInfo (as String) -> precision
+pub const PRECISIONS: Map<String, u8> = Map::new("precisions");
+
+/// Loads precision of the given asset info. The precision is queried and cached on first use.
+pub(crate) fn get_precision(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    asset_info: &AssetInfo,
+    factory_addr: &Addr,
+) -> StdResult<u8> {
+    if let Some(precision) = PRECISIONS.may_load(storage, asset_info.to_string())? {
+        return Ok(precision);
+    }
+
+    let precision = asset_info.decimals(querier, factory_addr)?;
+    PRECISIONS.save(storage, asset_info.to_string(), &precision)?;
+
+    Ok(precision)
+}
+
 /// Stores asset balances to query them later at any block height
 pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances",
@@ -35,3 +57,7 @@ pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances_change",
     cw_storage_plus::Strategy::EveryBlock,
 );
+
+/// Circular buffer to store trade size observations
+pub const OBSERVATIONS: CircularBuffer<Observation> =
+    CircularBuffer::new("observations_state", "observations_buffer");
@@ -1,28 +1,36 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_json_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal, DepsMut, Env, Reply,
-    ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Timestamp, Uint128, WasmMsg,
+    attr, coin, from_json, to_json_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal,
+    DepsMut, Env, Fraction, OwnedDeps, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse,
+    SubMsgResult, Timestamp, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use proptest::prelude::*;
 
 use palomadex::asset::{Asset, AssetInfo, PairInfo};
 use palomadex::factory::PairType;
+use palomadex::observation::{
+    CandleResolution, CandlesResponse, Observation, ObservationStatsResponse, OracleObservation,
+    PrecommitObservation, TwapResponse,
+};
 use palomadex::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse, TWAP_PRECISION,
+    Cw20HookMsg, DeadlineExpired, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, XYKPoolUpdateParams, TWAP_PRECISION,
 };
 use palomadex::token::InstantiateMsg as TokenInstantiateMsg;
+use palomadex_circular_buffer::BufferManager;
 
 use crate::contract::compute_offer_amount;
 use crate::contract::reply;
 use crate::contract::{
-    accumulate_prices, assert_max_spread, compute_swap, execute, instantiate, query_pool,
-    query_reverse_simulation, query_share, query_simulation,
+    accumulate_prices, assert_max_spread, compute_exact_out_offer_amount,
+    compute_imbalanced_withdraw, compute_swap, execute, get_share_in_assets, instantiate, migrate,
+    query, query_pool, query_reverse_simulation, query_share, query_simulate_withdraw,
+    query_simulation,
 };
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
-use crate::state::{Config, CONFIG};
+use crate::state::{Config, CONFIG, OBSERVATIONS};
 
 use prost::Message;
 
@@ -189,6 +197,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -303,6 +313,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(50)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -374,6 +386,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -436,6 +450,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -476,6 +492,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -516,6 +534,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -555,6 +575,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -584,6 +606,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
     let info = mock_info(
         "addr0001",
@@ -612,6 +636,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(51)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
     let info = mock_info(
         "addr0001",
@@ -624,6 +650,112 @@ fn provide_liquidity() {
     assert_eq!(err, ContractError::AllowedSpreadAssertion {});
 }
 
+#[test]
+fn provide_liquidity_with_min_lp_and_deadline() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100_000000000000000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    amount: Uint128::from(100_000000000000000000u128),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(100_000000000000000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100_000000000000000000u128),
+        }],
+    );
+    let env = mock_env();
+    let now = env.block.time.seconds();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        provide_msg(None, Some(now - 1)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DeadlineExpired(DeadlineExpired {
+            deadline: now - 1,
+            block_time: now,
+        })
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        provide_msg(Some(Uint128::from(100_000000000000000000u128)), None),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ProvideSlippageViolation(
+            Uint128::from(99_999999999999999000u128),
+            Uint128::from(100_000000000000000000u128)
+        )
+    );
+
+    // The deadline is inclusive and the exact share satisfies the minimum
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        provide_msg(Some(Uint128::from(99_999999999999999000u128)), Some(now)),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+}
+
 #[test]
 fn withdraw_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
@@ -741,6 +873,143 @@ fn withdraw_liquidity() {
     );
 }
 
+#[test]
+fn imbalanced_withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &Uint128::new(1_000_000u128))],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let withdraw_msg = |assets: Vec<Asset>, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity { assets }).unwrap(),
+            amount: Uint128::new(amount),
+        })
+    };
+    let uusd = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    // Withdrawing 10% of one asset burns more than the fee-free 5.13% of the LP supply
+    let burn_amount = query_simulate_withdraw(deps.as_ref(), vec![uusd(100_000)]).unwrap();
+    assert_eq!(burn_amount, Uint128::new(51_394));
+
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(vec![uusd(100_000)], 50_000),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEnoughLpTokens {
+            required: Uint128::new(51_394),
+            provided: Uint128::new(50_000),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(vec![uusd(1_000_000)], 1_000_000),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WithdrawExceedsReserves {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(
+            vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: Uint128::new(100),
+            }],
+            100_000,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        withdraw_msg(vec![uusd(100_000)], 100_000),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(100_000),
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(51_394),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::new(48_606),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(res.attributes[2], attr("withdrawn_share", "51394"));
+}
+
 #[test]
 fn try_native_to_token() {
     let total_share = Uint128::new(30000000000u128);
@@ -798,6 +1067,7 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1005,6 +1275,7 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -1020,6 +1291,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1143,6 +1415,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1152,6 +1425,393 @@ fn try_token_to_native() {
     assert_eq!(res, ContractError::Unauthorized {});
 }
 
+#[test]
+fn try_swap_exact_out() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let max_offer_amount = Uint128::new(2000000000u128);
+    let ask_amount = Uint128::new(900000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + max_offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let swap_msg = |max_offer_amount: Uint128| ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: ask_amount,
+        },
+        max_offer: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: max_offer_amount,
+        },
+        to: None,
+        deadline: None,
+    };
+    let offer_amount = compute_exact_out_offer_amount(
+        collateral_pool_amount,
+        asset_pool_amount,
+        ask_amount,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    let (return_amount, ..) = compute_swap(
+        collateral_pool_amount,
+        asset_pool_amount,
+        offer_amount,
+        Decimal::permille(3),
+    )
+    .unwrap();
+    assert_eq!(return_amount, ask_amount);
+
+    // The maximum offer is not enough to buy the ask amount
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin(collateral_pool_amount.u128() + 1000000000, "uusd")],
+    )]);
+    let info = mock_info("addr0000", &[coin(1000000000, "uusd")]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        swap_msg(Uint128::new(1000000000)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MaxOfferExceeded {
+            required: offer_amount,
+            max_offer: Uint128::new(1000000000),
+        }
+    );
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin(
+            (collateral_pool_amount + max_offer_amount).u128(),
+            "uusd",
+        )],
+    )]);
+    let info = mock_info("addr0000", &[coin(max_offer_amount.u128(), "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(max_offer_amount)).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }
+        .into()
+    );
+    assert_eq!(
+        res.messages.last().unwrap().msg,
+        BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![coin((max_offer_amount - offer_amount).u128(), "uusd")],
+        }
+        .into()
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("offer_amount", offer_amount.to_string())));
+}
+
+#[test]
+fn swaps_record_observations() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let set_ema_half_life = |ema_half_life: u64| ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::SetEmaHalfLife { ema_half_life }).unwrap(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory", &[]),
+        set_ema_half_life(0),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidEmaHalfLife {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory", &[]),
+        set_ema_half_life(600),
+    )
+    .unwrap();
+
+    let observe = |deps: &OwnedDeps<_, _, _>, env: Env, seconds_ago: u64| {
+        from_json::<OracleObservation>(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::Observe {
+                    seconds_ago,
+                    base_asset: None,
+                    quote_asset: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Observe {
+            seconds_ago: 0,
+            base_asset: None,
+            quote_asset: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Buffer is empty"));
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        deadline: None,
+    };
+    let (return_amount, ..) = compute_swap(
+        collateral_pool_amount,
+        asset_pool_amount,
+        offer_amount,
+        Decimal::permille(3),
+    )
+    .unwrap();
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin((collateral_pool_amount + offer_amount).u128(), "uusd")],
+    )]);
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[coin(offer_amount.u128(), "uusd")]);
+    execute(deps.as_mut(), env.clone(), info.clone(), swap_msg.clone()).unwrap();
+
+    // The swap is only precommitted until the next action, but it is already observable
+    env.block.time = env.block.time.plus_seconds(100);
+    let swap_price = Decimal::from_ratio(offer_amount, return_amount);
+    assert_eq!(
+        observe(&deps, env.clone(), 50),
+        OracleObservation {
+            timestamp: env.block.time.seconds() - 50,
+            price: swap_price,
+        }
+    );
+
+    assert_eq!(
+        from_json::<TwapResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Twap {
+                    start_seconds_ago: 50,
+                    end_seconds_ago: 0,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .price,
+        swap_price
+    );
+
+    // The next swap commits the previous observation
+    execute(deps.as_mut(), env.clone(), info, swap_msg).unwrap();
+    let buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    assert_eq!(
+        buffer.read_last(&deps.storage).unwrap(),
+        Some(Observation {
+            ts: mock_env().block.time.seconds(),
+            price: swap_price,
+            price_sma: swap_price,
+            price_ema: swap_price,
+            sq_returns_cumulative: Default::default(),
+        })
+    );
+    assert_eq!(
+        observe(&deps, env.clone(), 0),
+        OracleObservation {
+            timestamp: env.block.time.seconds(),
+            price: swap_price,
+        }
+    );
+    // Requesting the assets the other way around inverts the price
+    let reversed = from_json::<OracleObservation>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Observe {
+                seconds_ago: 0,
+                base_asset: Some(AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                }),
+                quote_asset: Some(AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                }),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reversed.price, swap_price.inv().unwrap());
+    assert_eq!(
+        from_json::<ObservationStatsResponse>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ObservationStats {
+                    window_seconds: 100
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        ObservationStatsResponse {
+            start_timestamp: mock_env().block.time.seconds(),
+            end_timestamp: mock_env().block.time.seconds(),
+            returns_count: 0,
+            price_ema: swap_price,
+            ema_half_life: 600,
+            variance: Decimal::zero(),
+            volatility: Decimal::zero(),
+        }
+    );
+
+    // Both swaps landed in the same hour
+    let candles = |deps: &OwnedDeps<_, _, _>, env: Env| {
+        from_json::<CandlesResponse>(
+            &query(
+                deps.as_ref(),
+                env,
+                QueryMsg::Candles {
+                    resolution: CandleResolution::Hour,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .candles
+    };
+    let hourly = candles(&deps, env.clone());
+    assert_eq!(hourly.len(), 1);
+    assert_eq!(hourly[0].open, swap_price);
+    assert_eq!(hourly[0].volume, offer_amount + offer_amount);
+
+    // Trades below the minimum trade size are not observed
+    let dust_amount = Uint128::new(5);
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin((collateral_pool_amount + dust_amount).u128(), "uusd")],
+    )]);
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[coin(dust_amount.u128(), "uusd")]),
+        ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: dust_amount,
+            },
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            to: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(candles(&deps, env.clone()), hourly);
+    assert_eq!(
+        PrecommitObservation::may_load(&deps.storage)
+            .unwrap()
+            .unwrap()
+            .precommit_ts,
+        env.block.time.seconds() - 100
+    );
+}
+
 #[test]
 fn test_max_spread() {
     assert_max_spread(
@@ -1482,6 +2142,96 @@ proptest! {
     }
 }
 
+proptest! {
+    #[test]
+    fn compute_imbalanced_withdraw_matches_withdraw_and_swap(
+        pool0 in 1_000_000..1_000_000_000_000_000u128,
+        pool1 in 1_000_000..1_000_000_000_000_000u128,
+        total_share in 1_000_000..1_000_000_000_000_000u128,
+        ratio0 in 0..900u128,
+        ratio1 in 0..900u128,
+    ) {
+        prop_assume!(ratio0 + ratio1 > 0);
+
+        let commission_rate = Decimal::permille(3);
+        let pools = [pool0, pool1].map(|amount| Asset {
+            info: AssetInfo::NativeToken {
+                denom: format!("denom{amount}"),
+            },
+            amount: Uint128::new(amount),
+        });
+        prop_assume!(!pools[0].info.equal(&pools[1].info));
+        let assets = vec![
+            Asset {
+                info: pools[0].info.clone(),
+                amount: Uint128::new(pool0 * ratio0 / 1000),
+            },
+            Asset {
+                info: pools[1].info.clone(),
+                amount: Uint128::new(pool1 * ratio1 / 1000),
+            },
+        ];
+        let total_share = Uint128::new(total_share);
+
+        let (refund_assets, burn_amount) =
+            compute_imbalanced_withdraw(&pools, &assets, total_share, commission_rate).unwrap();
+        prop_assert_eq!(&refund_assets, &assets);
+
+        // A balanced withdrawal of the burned LP plus a swap of the surplus covers the request
+        let share = get_share_in_assets(&pools, burn_amount, total_share);
+        let (out_idx, in_idx) = if share[0].amount < assets[0].amount { (0, 1) } else { (1, 0) };
+        let deficit = assets[out_idx].amount.saturating_sub(share[out_idx].amount);
+        let surplus = share[in_idx].amount - assets[in_idx].amount;
+        let return_amount = if surplus.is_zero() {
+            Uint128::zero()
+        } else {
+            compute_swap(
+                pools[in_idx].amount - share[in_idx].amount,
+                pools[out_idx].amount - share[out_idx].amount,
+                surplus,
+                commission_rate,
+            )
+            .unwrap()
+            .0
+        };
+
+        // Allow for the value of a couple of LP units and the precision of the square root
+        let tolerance = Uint128::new(2)
+            + pools[out_idx].amount.multiply_ratio(4u8, total_share)
+            + deficit.multiply_ratio(1u8, 1_000_000u128);
+        prop_assert!(return_amount + tolerance >= deficit);
+        prop_assert!(return_amount <= deficit + tolerance);
+    }
+
+    #[test]
+    fn compute_exact_out_offer_amount_is_minimal(
+        offer_pool in 1_000_000..1_000_000_000_000_000u128,
+        ask_pool in 1_000_000..1_000_000_000_000_000u128,
+        ratio in 1..900u128,
+        fee_bps in 0..1000u64,
+    ) {
+        let commission_rate = Decimal::from_ratio(fee_bps, 10000u64);
+        let ask_amount = Uint128::new(ask_pool * ratio / 1000);
+        prop_assume!(!ask_amount.is_zero());
+        let (offer_pool, ask_pool) = (Uint128::new(offer_pool), Uint128::new(ask_pool));
+
+        let offer_amount =
+            compute_exact_out_offer_amount(offer_pool, ask_pool, ask_amount, commission_rate)
+                .unwrap();
+        let (return_amount, ..) =
+            compute_swap(offer_pool, ask_pool, offer_amount, commission_rate).unwrap();
+        prop_assert!(return_amount >= ask_amount);
+
+
+        // One offer unit less is not enough
+        prop_assume!(offer_amount > Uint128::one());
+        let (return_amount, ..) =
+            compute_swap(offer_pool, ask_pool, offer_amount - Uint128::one(), commission_rate)
+                .unwrap();
+        prop_assert!(return_amount < ask_amount);
+    }
+}
+
 #[test]
 fn ensure_useful_error_messages_are_given_on_swaps() {
     const OFFER: Uint128 = Uint128::new(1_000_000_000000);
@@ -1552,3 +2302,25 @@ fn ensure_useful_error_messages_are_given_on_swaps() {
     );
     compute_offer_amount(OFFER, ASK, AMOUNT, DZERO).unwrap();
 }
+
+#[test]
+fn migrate_checks_contract_version() {
+    let mut deps = mock_dependencies(&[]);
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:palomadex-pair", "0.1.0").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    // The observations buffer is created once and survives repeated migrations
+    BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:palomadex-pair", "0.0.1").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:another-contract", "0.1.0")
+        .unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+}
@@ -9,7 +9,7 @@ use cosmwasm_std::{
     Decimal, Decimal256, Deps, DepsMut, Env, Fraction, MessageInfo, QuerierWrapper, Reply,
     Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use cw_utils::parse_instantiate_response_data;
 use itertools::Itertools;
@@ -19,33 +19,44 @@ use palomadex::asset::{
     Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 
+use palomadex::common::Role;
 use palomadex::cosmwasm_ext::IntegerToDecimal;
 use palomadex::factory::PairType;
 use palomadex::pair::{
-    ConfigResponse, CumulativePricesResponse, FeeShareConfig, InstantiateMsg, StablePoolParams,
-    StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
-    MIN_TRADE_SIZE,
+    ConfigResponse, CumulativePricesResponse, FeeShareConfig, InstantiateMsg, RateProviderParams,
+    StablePoolParams, StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+    MAX_FEE_SHARE_BPS, MIN_TRADE_SIZE,
 };
 
-use palomadex::observation::{query_observation, PrecommitObservation, OBSERVATIONS_SIZE};
+use palomadex::observation::{
+    accumulate_swap_sizes, init_candles, observation_asset_indexes, query_candles,
+    query_observation, query_observation_stats, query_observations, query_twap,
+    resize_observations, set_ema_half_life, update_candles, OracleObservation,
+    PrecommitObservation, OBSERVATIONS_SIZE,
+};
 use palomadex::pair::{
-    Cw20HookMsg, ExecuteMsg, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
-    StablePoolConfig,
+    check_deadline, Cw20HookMsg, ExecuteMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideResponse, SimulationResponse, StablePoolConfig,
+};
+use palomadex::querier::{
+    query_factory_config, query_factory_has_role, query_fee_info, query_pair_paused, query_supply,
+    FeeInfo,
 };
-use palomadex::querier::{query_factory_config, query_fee_info, query_supply};
 use palomadex::token::InstantiateMsg as TokenInstantiateMsg;
 use palomadex::DecimalCheckedOps;
 use palomadex_circular_buffer::BufferManager;
 
 use crate::error::ContractError;
 use crate::math::{
-    calc_y, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME,
+    calc_y, compute_d, compute_imbalance_fees, compute_withdraw_imbalance_d,
+    compute_withdraw_one_coin, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME,
 };
-use crate::state::{get_precision, store_precisions, Config, CONFIG, OBSERVATIONS};
+use crate::state::{get_precision, store_precisions, Config, RateProvider, CONFIG, OBSERVATIONS};
 use crate::utils::{
-    accumulate_prices, accumulate_swap_sizes, adjust_precision, check_asset_infos, check_assets,
-    check_cw20_in_pool, compute_current_amp, compute_swap, determine_base_quote_amount,
-    get_share_in_assets, mint_liquidity_token_message, select_pools, SwapResult,
+    accumulate_prices, adjust_precision, check_asset_infos, check_assets, check_cw20_in_pool,
+    compute_current_amp, compute_exact_out_offer_amount, compute_swap, determine_base_quote_amount,
+    get_share_in_assets, mint_liquidity_token_message, query_exchange_rate, query_exchange_rates,
+    select_pools, ExchangeRates, SwapResult,
 };
 
 /// A `reply` call code ID of sub-message.
@@ -81,6 +92,31 @@ pub fn instantiate(
         return Err(ContractError::IncorrectAmp {});
     }
 
+    let mut rate_providers: Vec<RateProvider> = vec![];
+    for provider in params.rate_providers.unwrap_or_default() {
+        if !msg.asset_infos.contains(&provider.asset_info) {
+            return Err(ContractError::InvalidAsset(provider.asset_info.to_string()));
+        }
+        if rate_providers
+            .iter()
+            .any(|p| p.asset_info == provider.asset_info)
+        {
+            return Err(ContractError::DoublingRateProviders(
+                provider.asset_info.to_string(),
+            ));
+        }
+        if provider.max_staleness == 0 {
+            return Err(ContractError::InvalidMaxStaleness(
+                provider.asset_info.to_string(),
+            ));
+        }
+        rate_providers.push(RateProvider {
+            asset_info: provider.asset_info,
+            contract_addr: deps.api.addr_validate(&provider.contract_addr)?,
+            max_staleness: provider.max_staleness,
+        });
+    }
+
     let factory_addr = deps.api.addr_validate(&msg.factory_addr)?;
     let greatest_precision = store_precisions(deps.branch(), &msg.asset_infos, &factory_addr)?;
 
@@ -111,10 +147,12 @@ pub fn instantiate(
         greatest_precision,
         cumulative_prices,
         fee_share: None,
+        rate_providers,
     };
 
     CONFIG.save(deps.storage, &config)?;
     BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+    init_candles(deps.storage)?;
 
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
@@ -185,6 +223,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             slippage_tolerance,
 ///             auto_stake,
 ///             receiver,
+///             min_lp_to_receive,
+///             deadline,
 ///         }** Provides liquidity in the pair using the specified input parameters.
 ///
 /// * **ExecuteMsg::Swap {
@@ -192,7 +232,10 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             belief_price,
 ///             max_spread,
 ///             to,
+///             deadline,
 ///         }** Performs an swap using the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut { ask_asset, max_offer, to, deadline }** Swaps just enough native tokens to receive the specified ask amount.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -204,16 +247,29 @@ pub fn execute(
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
-            assets, receiver, ..
-        } => provide_liquidity(deps, env, info, assets, receiver),
+            assets,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+            ..
+        } => provide_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
         ExecuteMsg::Swap {
             offer_asset,
             ask_asset_info,
             belief_price,
             max_spread,
             to,
-            ..
+            deadline,
         } => {
+            check_deadline(&env, deadline)?;
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -233,6 +289,23 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            check_deadline(&env, deadline)?;
+            max_offer.info.check(deps.api)?;
+            if !max_offer.is_native_token() {
+                return Err(ContractError::Cw20DirectSwap {});
+            }
+            max_offer.assert_sent_native_token_balance(&info)?;
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+
+            swap_exact_out(deps, env, info.sender, max_offer, ask_asset, to_addr)
+        }
     }
 }
 
@@ -251,7 +324,9 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            deadline,
         } => {
+            check_deadline(&env, deadline)?;
             let config = CONFIG.load(deps.storage)?;
 
             // Only asset contract can execute this message
@@ -274,6 +349,32 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
+        Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        } => {
+            check_deadline(&env, deadline)?;
+            let config = CONFIG.load(deps.storage)?;
+
+            // Only asset contract can execute this message
+            check_cw20_in_pool(&config, &info.sender)?;
+
+            let to_addr = addr_opt_validate(deps.api, &to)?;
+            swap_exact_out(
+                deps,
+                env,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: info.sender,
+                    },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset,
+                to_addr,
+            )
+        }
         Cw20HookMsg::WithdrawLiquidity { assets } => withdraw_liquidity(
             deps,
             env,
@@ -282,6 +383,18 @@ pub fn receive_cw20(
             cw20_msg.amount,
             assets,
         ),
+        Cw20HookMsg::WithdrawLiquidityOneCoin {
+            ask_asset_info,
+            min_return,
+        } => withdraw_liquidity_one_coin(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            ask_asset_info,
+            min_return,
+        ),
     }
 }
 
@@ -294,6 +407,10 @@ pub fn receive_cw20(
 ///
 /// * **receiver** address that receives LP tokens. If this address isn't specified, the function will default to the caller.
 ///
+/// * **min_lp_to_receive** minimum amount of LP tokens to mint, otherwise the provision fails.
+///
+/// * **deadline** block time (in seconds) after which the provision is rejected.
+///
 /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
 pub fn provide_liquidity(
     deps: DepsMut,
@@ -301,10 +418,19 @@ pub fn provide_liquidity(
     info: MessageInfo,
     assets: Vec<Asset>,
     receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    check_deadline(&env, deadline)?;
+
     check_assets(deps.api, &assets)?;
 
     let mut config = CONFIG.load(deps.storage)?;
+
+    if query_pair_paused(&deps.querier, &config.factory_addr, &env.contract.address)? {
+        return Err(ContractError::Paused {});
+    }
+
     info.funds
         .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
 
@@ -387,64 +513,50 @@ pub fn provide_liquidity(
         }
     }
 
-    let assets_collection = assets_collection
-        .iter()
-        .cloned()
-        .map(|(asset, pool)| {
-            let coin_precision = get_precision(deps.storage, &asset.info)?;
-            Ok((
-                asset.to_decimal_asset(coin_precision)?,
-                Decimal256::with_precision(pool, coin_precision)?,
-            ))
-        })
-        .collect::<StdResult<Vec<(DecimalAsset, Decimal256)>>>()?;
-
-    let amp = compute_current_amp(&config, &env)?;
-
-    // Invariant (D) after deposit added
-    let new_balances = assets_collection
-        .iter()
-        .map(|(deposit, pool)| Ok(pool + deposit.amount))
-        .collect::<StdResult<Vec<_>>>()?;
-    let deposit_d = compute_d(amp, &new_balances)?;
-
     let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
-    let share = if total_share.is_zero() {
-        let share = deposit_d
-            .to_uint128_with_precision(config.greatest_precision)?
-            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
-            .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
+    )?;
+    let rates = query_exchange_rates(&deps.querier, &env, &config)?;
+    let (share, fees) = calculate_provide(
+        deps.as_ref(),
+        &env,
+        &config,
+        &fee_info,
+        &assets_collection,
+        total_share,
+        &rates,
+    )?;
 
-        // share cannot become zero after minimum liquidity subtraction
-        if share.is_zero() {
-            return Err(ContractError::MinimumLiquidityAmountError {});
-        }
+    let min_lp_to_receive = min_lp_to_receive.unwrap_or_default();
+    if share < min_lp_to_receive {
+        return Err(ContractError::ProvideSlippageViolation(
+            share,
+            min_lp_to_receive,
+        ));
+    }
 
+    if total_share.is_zero() {
         messages.extend(mint_liquidity_token_message(
             &config,
             &env.contract.address,
             MINIMUM_LIQUIDITY_AMOUNT,
         )?);
+    }
 
-        share
-    } else {
-        // Initial invariant (D)
-        let old_balances = assets_collection
-            .iter()
-            .map(|(_, pool)| *pool)
-            .collect_vec();
-        let init_d = compute_d(amp, &old_balances)?;
-
-        let share = Decimal256::with_precision(total_share, config.greatest_precision)?
-            .checked_multiply_ratio(deposit_d.saturating_sub(init_d), init_d)?
-            .to_uint128_with_precision(config.greatest_precision)?;
-
-        if share.is_zero() {
-            return Err(ContractError::LiquidityAmountTooSmall {});
+    // The Maker part of the imbalance fee leaves the pool, the rest stays with LPs
+    let mut maker_fees = vec![];
+    if let Some(fee_address) = &fee_info.fee_address {
+        for fee in &fees {
+            if let Some(f) = calculate_maker_fee(&fee.info, fee.amount, fee_info.maker_fee_rate) {
+                messages.push(f.clone().into_msg(fee_address)?);
+                maker_fees.push(f);
+            }
         }
-
-        share
-    };
+    }
 
     // Mint LP token for the caller (or for the receiver if it was set)
     let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
@@ -461,7 +573,7 @@ pub fn provide_liquidity(
         })
         .collect::<StdResult<Vec<_>>>()?;
 
-    if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
+    if accumulate_prices(deps.storage, &env, &mut config, &pools, &rates)? {
         CONFIG.save(deps.storage, &config)?;
     }
 
@@ -471,9 +583,107 @@ pub fn provide_liquidity(
         attr("receiver", receiver),
         attr("assets", assets.iter().join(", ")),
         attr("share", share),
+        attr("imbalance_fee", fees.iter().join(", ")),
+        attr("maker_fee", maker_fees.iter().join(", ")),
     ]))
 }
 
+/// Returns the amount of LP tokens minted for a deposit and the imbalance fee charged on each asset.
+/// Deposits that deviate from the pool ratio are charged the Curve imbalance fee, which is taken
+/// from the deposit before the new invariant is computed.
+///
+/// * **assets_collection** contains each deposit with the pool amount before the deposit.
+///
+/// * **total_share** is the total amount of LP tokens currently issued by the pool.
+///
+/// * **rates** are the exchange rates of the pool assets.
+fn calculate_provide(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    fee_info: &FeeInfo,
+    assets_collection: &[(Asset, Uint128)],
+    total_share: Uint128,
+    rates: &ExchangeRates,
+) -> Result<(Uint128, Vec<Asset>), ContractError> {
+    let mut precisions = vec![];
+    let mut old_balances = vec![];
+    let mut new_balances = vec![];
+    for (deposit, pool) in assets_collection {
+        let precision = get_precision(deps.storage, &deposit.info)?;
+        let pool = Decimal256::with_precision(*pool, precision)?;
+        let new_pool = pool + deposit.to_decimal_asset(precision)?.amount;
+        precisions.push(precision);
+        old_balances.push(rates.scale(&deposit.info, pool)?);
+        new_balances.push(rates.scale(&deposit.info, new_pool)?);
+    }
+
+    let amp = compute_current_amp(config, env)?;
+
+    if total_share.is_zero() {
+        // Invariant (D) after deposit added
+        let deposit_d = compute_d(amp, &new_balances)?;
+        let share = deposit_d
+            .to_uint128_with_precision(config.greatest_precision)?
+            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+            .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+
+        // share cannot become zero after minimum liquidity subtraction
+        if share.is_zero() {
+            return Err(ContractError::MinimumLiquidityAmountError {});
+        }
+
+        let fees = assets_collection
+            .iter()
+            .map(|(deposit, _)| Asset {
+                info: deposit.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect();
+
+        return Ok((share, fees));
+    }
+
+    // Initial invariant (D) and the fees for the deviation from the pool ratio
+    let (init_d, fees) = compute_imbalance_fees(
+        amp,
+        &old_balances,
+        &new_balances,
+        fee_info.total_fee_rate.into(),
+    )?;
+
+    // Invariant (D) after deposit added and fees charged
+    let new_balances = new_balances
+        .iter()
+        .zip(&fees)
+        .map(|(balance, fee)| balance.checked_sub(*fee))
+        .collect::<Result<Vec<_>, _>>()?;
+    let deposit_d = compute_d(amp, &new_balances)?;
+
+    let share = Decimal256::with_precision(total_share, config.greatest_precision)?
+        .checked_multiply_ratio(deposit_d.saturating_sub(init_d), init_d)?
+        .to_uint128_with_precision(config.greatest_precision)?;
+
+    if share.is_zero() {
+        return Err(ContractError::LiquidityAmountTooSmall {});
+    }
+
+    let fees = assets_collection
+        .iter()
+        .zip(fees)
+        .zip(precisions)
+        .map(|(((deposit, _), fee), precision)| {
+            Ok(Asset {
+                info: deposit.info.clone(),
+                amount: rates
+                    .unscale_floor(&deposit.info, fee.to_uint128_with_precision(precision)?)?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((share, fees))
+}
+
 /// Withdraw liquidity from the pool.
 /// * **sender** is the address that will receive assets back from the pair contract.
 ///
@@ -494,26 +704,107 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.querier, &config)?;
 
-    let refund_assets = if assets.is_empty() {
-        // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount, total_share)
+    let (refund_assets, burn_amount, rates) = if assets.is_empty() {
+        // Usual withdraw (balanced), it doesn't depend on the exchange rates
+        // so it remains available while a rate provider is stale
+        (
+            get_share_in_assets(&pools, amount, total_share),
+            amount,
+            query_exchange_rates(&deps.querier, &env, &config).ok(),
+        )
     } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
+        let rates = query_exchange_rates(&deps.querier, &env, &config)?;
+        let (refund_assets, burn_amount) = calculate_imbalanced_withdraw(
+            deps.as_ref(),
+            &env,
+            &config,
+            &pools,
+            total_share,
+            &assets,
+            &rates,
+        )?;
+        if burn_amount > amount {
+            return Err(ContractError::NotEnoughLpTokens {
+                required: burn_amount,
+                provided: amount,
+            });
+        }
+        (refund_assets, burn_amount, Some(rates))
     };
 
-    let mut messages = refund_assets
-        .clone()
-        .into_iter()
-        .map(|asset| asset.into_msg(&sender))
-        .collect::<StdResult<Vec<_>>>()?;
-    messages.push(
-        wasm_execute(
-            &config.pair_info.liquidity_token,
-            &Cw20ExecuteMsg::Burn { amount },
-            vec![],
-        )?
-        .into(),
-    );
+    let messages = withdraw_messages(&config, &sender, &refund_assets, burn_amount, amount)?;
+
+    let pools = pools
+        .iter()
+        .map(|pool| {
+            let precision = get_precision(deps.storage, &pool.info)?;
+            pool.to_decimal_asset(precision)
+        })
+        .collect::<StdResult<Vec<DecimalAsset>>>()?;
+
+    if let Some(rates) = rates {
+        if accumulate_prices(deps.storage, &env, &mut config, &pools, &rates)? {
+            CONFIG.save(deps.storage, &config)?;
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_liquidity"),
+        attr("sender", sender),
+        attr("withdrawn_share", burn_amount),
+        attr("refund_assets", refund_assets.iter().join(", ")),
+    ]))
+}
+
+/// Withdraw liquidity from the pool in a single asset.
+/// * **sender** is the address that will receive the asset back from the pair contract.
+///
+/// * **amount** is the amount of LP tokens to burn.
+///
+/// * **ask_asset_info** is the asset to withdraw.
+///
+/// * **min_return** is the minimum amount of the asset to receive.
+pub fn withdraw_liquidity_one_coin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+    min_return: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+    let rates = query_exchange_rates(&deps.querier, &env, &config)?;
+
+    let refund_asset = calculate_withdraw_one_coin(
+        deps.as_ref(),
+        &env,
+        &config,
+        &pools,
+        amount,
+        total_share,
+        &ask_asset_info,
+        &rates,
+    )?;
+    if let Some(min_return) = min_return {
+        if refund_asset.amount < min_return {
+            return Err(ContractError::MaxSlippageAssertion {});
+        }
+    }
+
+    let messages = withdraw_messages(
+        &config,
+        &sender,
+        std::slice::from_ref(&refund_asset),
+        amount,
+        amount,
+    )?;
 
     let pools = pools
         .iter()
@@ -523,7 +814,7 @@ pub fn withdraw_liquidity(
         })
         .collect::<StdResult<Vec<DecimalAsset>>>()?;
 
-    if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
+    if accumulate_prices(deps.storage, &env, &mut config, &pools, &rates)? {
         CONFIG.save(deps.storage, &config)?;
     }
 
@@ -531,10 +822,174 @@ pub fn withdraw_liquidity(
         attr("action", "withdraw_liquidity"),
         attr("sender", sender),
         attr("withdrawn_share", amount),
-        attr("refund_assets", refund_assets.iter().join(", ")),
+        attr("refund_assets", refund_asset.to_string()),
     ]))
 }
 
+/// Builds the messages that send `refund_assets` to the `sender`, burn `burn_amount` LP tokens
+/// and return the LP tokens out of `amount` that were not burned.
+fn withdraw_messages(
+    config: &Config,
+    sender: &Addr,
+    refund_assets: &[Asset],
+    burn_amount: Uint128,
+    amount: Uint128,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = refund_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(sender))
+        .collect::<StdResult<Vec<_>>>()?;
+    messages.push(
+        wasm_execute(
+            &config.pair_info.liquidity_token,
+            &Cw20ExecuteMsg::Burn {
+                amount: burn_amount,
+            },
+            vec![],
+        )?
+        .into(),
+    );
+    if burn_amount < amount {
+        messages.push(
+            wasm_execute(
+                &config.pair_info.liquidity_token,
+                &Cw20ExecuteMsg::Transfer {
+                    recipient: sender.to_string(),
+                    amount: amount - burn_amount,
+                },
+                vec![],
+            )?
+            .into(),
+        );
+    }
+
+    Ok(messages)
+}
+
+/// Returns the assets refunded and the amount of LP tokens burned when withdrawing
+/// exactly `assets` from the pool. Assets that are not specified are not withdrawn.
+fn calculate_imbalanced_withdraw(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset],
+    total_share: Uint128,
+    assets: &[Asset],
+    rates: &ExchangeRates,
+) -> Result<(Vec<Asset>, Uint128), ContractError> {
+    if assets.len() > N_COINS {
+        return Err(ContractError::InvalidNumberOfAssets(N_COINS));
+    }
+    check_assets(deps.api, assets)?;
+    if let Some(asset) = assets
+        .iter()
+        .find(|asset| !pools.iter().any(|pool| pool.info.eq(&asset.info)))
+    {
+        return Err(ContractError::InvalidAsset(asset.info.to_string()));
+    }
+
+    let refund_assets = pools
+        .iter()
+        .map(|pool| Asset {
+            info: pool.info.clone(),
+            amount: assets
+                .iter()
+                .find(|asset| asset.info.eq(&pool.info))
+                .map(|asset| asset.amount)
+                .unwrap_or_default(),
+        })
+        .collect_vec();
+    if refund_assets.iter().all(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    if refund_assets
+        .iter()
+        .zip(pools)
+        .any(|(refund, pool)| refund.amount >= pool.amount)
+    {
+        return Err(ContractError::WithdrawExceedsReserves {});
+    }
+
+    let mut xp = vec![];
+    let mut amounts = vec![];
+    for (pool, refund) in pools.iter().zip(&refund_assets) {
+        let precision = get_precision(deps.storage, &pool.info)?;
+        xp.push(rates.scale(&pool.info, pool.to_decimal_asset(precision)?.amount)?);
+        amounts.push(rates.scale(&refund.info, refund.to_decimal_asset(precision)?.amount)?);
+    }
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
+    )?;
+    let (d0, d2) = compute_withdraw_imbalance_d(
+        compute_current_amp(config, env)?,
+        &xp,
+        &amounts,
+        fee_info.total_fee_rate.into(),
+    )?;
+
+    // Add one to round the burn amount in favour of the pool
+    let burn_amount = Decimal256::with_precision(total_share, config.greatest_precision)?
+        .checked_multiply_ratio(d0.saturating_sub(d2), d0)?
+        .to_uint128_with_precision(config.greatest_precision)?
+        .checked_add(Uint128::one())?;
+
+    Ok((refund_assets, burn_amount))
+}
+
+/// Returns the asset refunded when burning `amount` LP tokens for the asset `ask_asset_info` only.
+#[allow(clippy::too_many_arguments)]
+fn calculate_withdraw_one_coin(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset],
+    amount: Uint128,
+    total_share: Uint128,
+    ask_asset_info: &AssetInfo,
+    rates: &ExchangeRates,
+) -> Result<Asset, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let (i, _) = pools
+        .iter()
+        .find_position(|pool| pool.info.eq(ask_asset_info))
+        .ok_or_else(|| ContractError::InvalidAsset(ask_asset_info.to_string()))?;
+
+    let xp = pools
+        .iter()
+        .map(|pool| {
+            let precision = get_precision(deps.storage, &pool.info)?;
+            rates.scale(&pool.info, pool.to_decimal_asset(precision)?.amount)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
+    )?;
+    let return_amount = compute_withdraw_one_coin(
+        compute_current_amp(config, env)?,
+        &xp,
+        i,
+        Decimal256::from_ratio(amount, total_share),
+        fee_info.total_fee_rate.into(),
+        get_precision(deps.storage, ask_asset_info)?,
+    )?;
+
+    Ok(Asset {
+        info: ask_asset_info.clone(),
+        amount: rates.unscale_floor(ask_asset_info, return_amount)?,
+    })
+}
+
 /// Performs an swap operation with the specified parameters.
 ///
 /// * **sender** is the sender of the swap operation.
@@ -558,9 +1013,76 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    execute_swap(
+        deps,
+        env,
+        sender,
+        offer_asset,
+        SwapTarget::ExactIn {
+            ask_asset_info,
+            belief_price,
+            max_spread,
+        },
+        to,
+    )
+}
+
+/// Swaps just enough of the offer asset to receive exactly the specified ask amount.
+/// The unused part of the offer asset is refunded to the sender.
+///
+/// * **sender** is the sender of the swap operation.
+///
+/// * **max_offer** asset sent for the swap, its amount is the most that can be spent.
+///
+/// * **ask_asset** asset and amount to receive.
+///
+/// * **to** sets the recipient of the swap operation.
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    max_offer: Asset,
+    ask_asset: Asset,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    execute_swap(
+        deps,
+        env,
+        sender,
+        max_offer,
+        SwapTarget::ExactOut(ask_asset),
+        to,
+    )
+}
+
+/// Describes which side of a swap is fixed by the trader.
+enum SwapTarget {
+    /// The whole offer amount is swapped, the return is checked against the spread limits
+    ExactIn {
+        ask_asset_info: Option<AssetInfo>,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    },
+    /// Exactly this asset is received, the offer amount is the most that can be spent
+    ExactOut(Asset),
+}
+
+/// Performs a swap of the deposited `offer_asset`, the contract balance already includes the deposit.
+fn execute_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    target: SwapTarget,
+    to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
+    if query_pair_paused(&deps.querier, &config.factory_addr, &env.contract.address)? {
+        return Err(ContractError::Paused {});
+    }
+
     // If the asset balance already increased
     // We should subtract the user deposit from the pool offer asset amount
     let pools = config
@@ -579,8 +1101,11 @@ pub fn swap(
         })
         .collect::<StdResult<Vec<_>>>()?;
 
-    let (offer_pool, ask_pool) =
-        select_pools(Some(&offer_asset.info), ask_asset_info.as_ref(), &pools)?;
+    let ask_asset_info = match &target {
+        SwapTarget::ExactIn { ask_asset_info, .. } => ask_asset_info.as_ref(),
+        SwapTarget::ExactOut(ask_asset) => Some(&ask_asset.info),
+    };
+    let (offer_pool, ask_pool) = select_pools(Some(&offer_asset.info), ask_asset_info, &pools)?;
 
     let offer_precision = get_precision(deps.storage, &offer_pool.info)?;
 
@@ -596,7 +1121,42 @@ pub fn swap(
         offer_asset.amount,
     )?;
 
-    let offer_asset_dec = offer_asset.to_decimal_asset(offer_precision)?;
+    // Get fee info from the factory
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
+    )?;
+    let rates = query_exchange_rates(&deps.querier, &env, &config)?;
+
+    let offer_amount = match &target {
+        SwapTarget::ExactIn { .. } => offer_asset.amount,
+        SwapTarget::ExactOut(ask_asset) => {
+            let required = compute_exact_out_offer_amount(
+                deps.storage,
+                &env,
+                &config,
+                &offer_pool,
+                &ask_pool,
+                &pools,
+                ask_asset.amount,
+                fee_info.total_fee_rate,
+                &rates,
+            )?;
+            if required > offer_asset.amount {
+                return Err(ContractError::MaxOfferExceeded {
+                    required,
+                    max_offer: offer_asset.amount,
+                });
+            }
+            required
+        }
+    };
+    let offer_asset_dec = DecimalAsset {
+        info: offer_asset.info.clone(),
+        amount: offer_amount.to_decimal256(offer_precision)?,
+    };
 
     let SwapResult {
         return_amount,
@@ -609,25 +1169,27 @@ pub fn swap(
         &offer_pool,
         &ask_pool,
         &pools,
+        &rates,
     )?;
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        &config.factory_addr,
-        config.pair_info.pair_type.clone(),
-    )?;
     let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
     let return_amount = return_amount.saturating_sub(commission_amount);
 
     // Check the max spread limit (if it was specified)
-    assert_max_spread(
+    if let SwapTarget::ExactIn {
         belief_price,
         max_spread,
-        offer_asset.amount,
-        return_amount + commission_amount,
-        spread_amount,
-    )?;
+        ..
+    } = target
+    {
+        assert_max_spread(
+            belief_price,
+            max_spread,
+            offer_amount,
+            return_amount + commission_amount,
+            spread_amount,
+        )?;
+    }
 
     let receiver = to.unwrap_or_else(|| sender.clone());
 
@@ -679,12 +1241,12 @@ pub fn swap(
         }
     }
 
-    if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
+    if accumulate_prices(deps.storage, &env, &mut config, &pools, &rates)? {
         CONFIG.save(deps.storage, &config)?;
     }
 
     // Store observation from precommit data
-    accumulate_swap_sizes(deps.storage, &env)?;
+    accumulate_swap_sizes(deps.storage, &env, OBSERVATIONS)?;
 
     // Store time series data in precommit observation.
     // Skipping small unsafe values which can seriously mess oracle price due to rounding errors.
@@ -694,15 +1256,33 @@ pub fn swap(
         && return_amount.to_decimal256(ask_precision)? >= MIN_TRADE_SIZE
     {
         // Store time series data
+        let swapped_offer = Asset {
+            info: offer_asset.info.clone(),
+            amount: offer_amount,
+        };
         let (base_amount, quote_amount) =
-            determine_base_quote_amount(&pools, &offer_asset, return_amount)?;
+            determine_base_quote_amount(&pools, &swapped_offer, return_amount)?;
         PrecommitObservation::save(deps.storage, &env, base_amount, quote_amount)?;
+        update_candles(deps.storage, &env, base_amount, quote_amount)?;
+    }
+
+    // Refund the part of the deposit that was not needed for an exact output swap
+    let refund_amount = offer_asset.amount.checked_sub(offer_amount)?;
+    if !refund_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: refund_amount,
+            }
+            .into_msg(sender.clone())?,
+        );
     }
 
     Ok(Response::new()
         .add_messages(
             // 1. send collateral tokens from the contract to a user
             // 2. send inactive commission fees to the Maker contract
+            // 3. refund the unused offer amount
             messages,
         )
         .add_attributes(vec![
@@ -711,7 +1291,7 @@ pub fn swap(
             attr("receiver", receiver),
             attr("offer_asset", offer_asset.info.to_string()),
             attr("ask_asset", ask_pool.info.to_string()),
-            attr("offer_amount", offer_asset.amount),
+            attr("offer_amount", offer_amount),
             attr("return_amount", return_amount),
             attr("spread_amount", spread_amount),
             attr("commission_amount", commission_amount),
@@ -783,11 +1363,66 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             offer_asset_info,
         )?),
         QueryMsg::CumulativePrices {} => to_json_binary(&query_cumulative_prices(deps, env)?),
-        QueryMsg::Observe { seconds_ago } => {
-            to_json_binary(&query_observation(deps, env, OBSERVATIONS, seconds_ago)?)
-        }
+        QueryMsg::Observe {
+            seconds_ago,
+            base_asset,
+            quote_asset,
+        } => to_json_binary(&query_observe(
+            deps,
+            env,
+            seconds_ago,
+            base_asset,
+            quote_asset,
+        )?),
+        QueryMsg::Twap {
+            start_seconds_ago,
+            end_seconds_ago,
+        } => to_json_binary(&query_twap(
+            deps,
+            env,
+            OBSERVATIONS,
+            start_seconds_ago,
+            end_seconds_ago,
+        )?),
+        QueryMsg::ObservationStats { window_seconds } => to_json_binary(&query_observation_stats(
+            deps,
+            env,
+            OBSERVATIONS,
+            window_seconds,
+        )?),
+        QueryMsg::Candles {
+            resolution,
+            start_after,
+            limit,
+        } => to_json_binary(&query_candles(deps, resolution, start_after, limit)?),
+        QueryMsg::Observations {
+            start_after_ts,
+            limit,
+            order,
+        } => to_json_binary(&query_observations(
+            deps,
+            OBSERVATIONS,
+            start_after_ts,
+            limit,
+            order,
+        )?),
         QueryMsg::Config {} => to_json_binary(&query_config(deps, env)?),
         QueryMsg::QueryComputeD {} => to_json_binary(&query_compute_d(deps, env)?),
+        QueryMsg::SimulateProvide { assets } => {
+            to_json_binary(&query_simulate_provide(deps, env, assets)?)
+        }
+        QueryMsg::SimulateWithdraw { assets } => {
+            to_json_binary(&query_simulate_withdraw(deps, env, assets)?)
+        }
+        QueryMsg::SimulateWithdrawOneCoin {
+            amount,
+            ask_asset_info,
+        } => to_json_binary(&query_simulate_withdraw_one_coin(
+            deps,
+            env,
+            amount,
+            ask_asset_info,
+        )?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -818,6 +1453,120 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
     Ok(refund_assets)
 }
 
+/// Returns the amount of LP tokens minted for a deposit and the imbalance fee charged on each asset
+/// in a [`SimulateProvideResponse`] object.
+///
+/// * **assets** are the assets to deposit. An asset that is not specified is not deposited.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: Vec<Asset>,
+) -> StdResult<SimulateProvideResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    if assets.len() > pools.len() {
+        return Err(StdError::generic_err(
+            ContractError::InvalidNumberOfAssets(pools.len()).to_string(),
+        ));
+    }
+    if let Some(asset) = assets
+        .iter()
+        .find(|asset| !pools.iter().any(|pool| pool.info.eq(&asset.info)))
+    {
+        return Err(StdError::generic_err(
+            ContractError::InvalidAsset(asset.info.to_string()).to_string(),
+        ));
+    }
+    let assets_collection = pools
+        .into_iter()
+        .map(|pool| {
+            let amount = assets
+                .iter()
+                .find(|asset| asset.info.eq(&pool.info))
+                .map(|asset| asset.amount)
+                .unwrap_or_default();
+            (
+                Asset {
+                    info: pool.info,
+                    amount,
+                },
+                pool.amount,
+            )
+        })
+        .collect_vec();
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
+    )?;
+    let (share, fees) = query_exchange_rates(&deps.querier, &env, &config)
+        .and_then(|rates| {
+            calculate_provide(
+                deps,
+                &env,
+                &config,
+                &fee_info,
+                &assets_collection,
+                total_share,
+                &rates,
+            )
+        })
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(SimulateProvideResponse { share, fees })
+}
+
+/// Returns the amount of LP tokens that must be burned to withdraw the specified assets.
+///
+/// * **assets** are the assets to withdraw. An asset that is not specified is not withdrawn.
+pub fn query_simulate_withdraw(deps: Deps, env: Env, assets: Vec<Asset>) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    let (_, burn_amount) = query_exchange_rates(&deps.querier, &env, &config)
+        .and_then(|rates| {
+            calculate_imbalanced_withdraw(deps, &env, &config, &pools, total_share, &assets, &rates)
+        })
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(burn_amount)
+}
+
+/// Returns the amount of an asset received when burning LP tokens for that asset only.
+///
+/// * **amount** is the amount of LP tokens to burn.
+///
+/// * **ask_asset_info** is the asset to withdraw.
+pub fn query_simulate_withdraw_one_coin(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+) -> StdResult<Uint128> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    let refund_asset = query_exchange_rates(&deps.querier, &env, &config)
+        .and_then(|rates| {
+            calculate_withdraw_one_coin(
+                deps,
+                &env,
+                &config,
+                &pools,
+                amount,
+                total_share,
+                &ask_asset_info,
+                &rates,
+            )
+        })
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok(refund_asset.amount)
+}
+
 /// Returns information about a swap simulation in a [`SimulationResponse`] object.
 ///
 /// * **offer_asset** is the asset to swap as well as an amount of the said asset.
@@ -862,22 +1611,27 @@ pub fn query_simulation(
     let SwapResult {
         return_amount,
         spread_amount,
-    } = compute_swap(
-        deps.storage,
-        &env,
-        &config,
-        &offer_asset.to_decimal_asset(offer_precision)?,
-        &offer_pool,
-        &ask_pool,
-        &pools,
-    )
-    .map_err(|err| StdError::generic_err(format!("{err}")))?;
+    } = query_exchange_rates(&deps.querier, &env, &config)
+        .and_then(|rates| {
+            compute_swap(
+                deps.storage,
+                &env,
+                &config,
+                &offer_asset.to_decimal_asset(offer_precision)?,
+                &offer_pool,
+                &ask_pool,
+                &pools,
+                &rates,
+            )
+        })
+        .map_err(|err| StdError::generic_err(format!("{err}")))?;
 
     // Get fee info from factory
     let fee_info = query_fee_info(
         &deps.querier,
         &config.factory_addr,
         config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
     )?;
 
     let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
@@ -941,6 +1695,7 @@ pub fn query_reverse_simulation(
         &deps.querier,
         &config.factory_addr,
         config.pair_info.pair_type.clone(),
+        &config.pair_info.contract_addr,
     )?;
     let before_commission = (Decimal256::one()
         - Decimal256::new(fee_info.total_fee_rate.atomics().into()))
@@ -948,31 +1703,64 @@ pub fn query_reverse_simulation(
     .ok_or_else(|| StdError::generic_err("The pool must have less than 100% fee!"))?
     .checked_mul(Decimal256::with_precision(ask_asset.amount, ask_precision)?)?;
 
-    let xp = pools.into_iter().map(|pool| pool.amount).collect_vec();
+    let rates = query_exchange_rates(&deps.querier, &env, &config)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let xp = rates.scale_pools(&pools)?;
     let new_offer_pool_amount = calc_y(
         compute_current_amp(&config, &env)?,
-        ask_pool.amount - before_commission,
+        rates.scale(&ask_pool.info, ask_pool.amount - before_commission)?,
         &xp,
         config.greatest_precision,
     )?;
 
-    let offer_amount = new_offer_pool_amount.checked_sub(
-        offer_pool
-            .amount
-            .to_uint128_with_precision(config.greatest_precision)?,
-    )?;
+    let offer_amount = rates
+        .unscale_ceil(&offer_pool.info, new_offer_pool_amount)?
+        .checked_sub(
+            offer_pool
+                .amount
+                .to_uint128_with_precision(config.greatest_precision)?,
+        )?;
     let offer_amount = adjust_precision(offer_amount, config.greatest_precision, offer_precision)?;
 
+    // The offer amount without spread is worth the ask amount before commission
+    let ideal_offer_amount = rates.unscale_floor(
+        &offer_pool.info,
+        rates
+            .scale(&ask_asset.info, before_commission)?
+            .to_uint128_with_precision(offer_precision)?,
+    )?;
+
     Ok(ReverseSimulationResponse {
         offer_amount,
-        spread_amount: offer_amount
-            .saturating_sub(before_commission.to_uint128_with_precision(offer_precision)?),
+        spread_amount: offer_amount.saturating_sub(ideal_offer_amount),
         commission_amount: fee_info
             .total_fee_rate
             .checked_mul_uint128(before_commission.to_uint128_with_precision(ask_precision)?)?,
     })
 }
 
+/// Returns the price of `quote_asset` denominated in `base_asset` (i.e. the amount of `base_asset`
+/// paid per one `quote_asset`) observed `seconds_ago` seconds ago.
+/// Swaps are observed with the first pool asset as the base one,
+/// so the price is inverted if the assets are requested the other way around.
+pub fn query_observe(
+    deps: Deps,
+    env: Env,
+    seconds_ago: u64,
+    base_asset: Option<AssetInfo>,
+    quote_asset: Option<AssetInfo>,
+) -> StdResult<OracleObservation> {
+    let config = CONFIG.load(deps.storage)?;
+    let indexes =
+        observation_asset_indexes(&config.pair_info.asset_infos, base_asset, quote_asset)?;
+    let observation = query_observation(deps, env, OBSERVATIONS, seconds_ago)?;
+    if indexes == (0, 1) {
+        Ok(observation)
+    } else {
+        observation.invert()
+    }
+}
+
 /// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
 pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
     let mut config = CONFIG.load(deps.storage)?;
@@ -986,7 +1774,10 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         })
         .collect::<StdResult<Vec<DecimalAsset>>>()?;
 
-    accumulate_prices(deps.storage, &env, &mut config, &decimal_assets)
+    query_exchange_rates(&deps.querier, &env, &config)
+        .and_then(|rates| {
+            accumulate_prices(deps.storage, &env, &mut config, &decimal_assets, &rates)
+        })
         .map_err(|err| StdError::generic_err(format!("{err}")))?;
 
     Ok(CumulativePricesResponse {
@@ -1005,6 +1796,15 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
         params: Some(to_json_binary(&StablePoolConfig {
             amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
             fee_share: config.fee_share,
+            rate_providers: config
+                .rate_providers
+                .into_iter()
+                .map(|provider| RateProviderParams {
+                    asset_info: provider.asset_info,
+                    contract_addr: provider.contract_addr.into_string(),
+                    max_staleness: provider.max_staleness,
+                })
+                .collect(),
         })?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
@@ -1060,6 +1860,32 @@ pub fn assert_max_spread(
     Ok(())
 }
 
+/// Manages the contract migration.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    match contract_version.contract.as_ref() {
+        CONTRACT_NAME => match contract_version.version.as_ref() {
+            "0.1.0" => {
+                // Pairs instantiated before candles were recorded have no candle buffers yet
+                init_candles(deps.storage)?;
+            }
+            "0.2.0" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
+
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
 pub fn pool_info(querier: QuerierWrapper, config: &Config) -> StdResult<(Vec<Asset>, Uint128)> {
     let pools = config
@@ -1073,28 +1899,51 @@ pub fn pool_info(querier: QuerierWrapper, config: &Config) -> StdResult<(Vec<Ass
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values.
+///
+/// ## Executor
+/// Only the factory, which forwards updates queued by its owner, or the pair owner if one is set can execute this.
+/// Amp managers of the factory can also start and stop changing the amplification.
 pub fn update_config(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     params: Binary,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
-    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
-
-    if info.sender
-        != if let Some(ref owner) = config.owner {
-            owner.to_owned()
-        } else {
-            factory_config.owner
-        }
+    let params = from_json::<StablePoolUpdateParams>(&params)?;
+
+    // Updates of the factory owner are forwarded by the factory after its timelock delay.
+    // Rate providers reprice the pool, so only the factory may change them.
+    // Amp managers of the factory may change the amplification, everything else is up to the owner
+    let is_amp_update = matches!(
+        params,
+        StablePoolUpdateParams::StartChangingAmp { .. }
+            | StablePoolUpdateParams::StopChangingAmp {}
+    );
+    let is_rate_provider_update = matches!(
+        params,
+        StablePoolUpdateParams::SetRateProvider { .. }
+            | StablePoolUpdateParams::RemoveRateProvider { .. }
+    );
+    if is_rate_provider_update && info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+    if info.sender != config.factory_addr
+        && Some(&info.sender) != config.owner.as_ref()
+        && !(is_amp_update
+            && query_factory_has_role(
+                &deps.querier,
+                &config.factory_addr,
+                Role::AmpManager,
+                &info.sender,
+            )?)
     {
         return Err(ContractError::Unauthorized {});
     }
 
     let mut response = Response::default();
 
-    match from_json::<StablePoolUpdateParams>(&params)? {
+    match params {
         StablePoolUpdateParams::StartChangingAmp {
             next_amp,
             next_amp_time,
@@ -1138,11 +1987,110 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        StablePoolUpdateParams::SetEmaHalfLife { ema_half_life } => {
+            if ema_half_life == 0 {
+                return Err(ContractError::InvalidEmaHalfLife {});
+            }
+            set_ema_half_life(deps.storage, ema_half_life)?;
+            response
+                .attributes
+                .push(attr("action", "set_ema_half_life"));
+            response
+                .attributes
+                .push(attr("ema_half_life", ema_half_life.to_string()));
+        }
+        StablePoolUpdateParams::ResizeObservations { capacity } => {
+            resize_observations(deps.storage, OBSERVATIONS, capacity)?;
+            response
+                .attributes
+                .push(attr("action", "resize_observations"));
+            response
+                .attributes
+                .push(attr("capacity", capacity.to_string()));
+        }
+        StablePoolUpdateParams::SetRateProvider { provider } => {
+            if !config.pair_info.asset_infos.contains(&provider.asset_info) {
+                return Err(ContractError::InvalidAsset(provider.asset_info.to_string()));
+            }
+            if provider.max_staleness == 0 {
+                return Err(ContractError::InvalidMaxStaleness(
+                    provider.asset_info.to_string(),
+                ));
+            }
+            let rate_provider = RateProvider {
+                asset_info: provider.asset_info,
+                contract_addr: deps.api.addr_validate(&provider.contract_addr)?,
+                max_staleness: provider.max_staleness,
+            };
+            // Make sure the new provider reports a usable rate before the pool relies on it
+            query_exchange_rate(&deps.querier, &env, &rate_provider)?;
+
+            accumulate_prices_before_rates_change(deps.branch(), &env, &mut config)?;
+            config
+                .rate_providers
+                .retain(|p| p.asset_info != rate_provider.asset_info);
+            response
+                .attributes
+                .push(attr("action", "set_rate_provider"));
+            response
+                .attributes
+                .push(attr("asset_info", rate_provider.asset_info.to_string()));
+            response.attributes.push(attr(
+                "contract_addr",
+                rate_provider.contract_addr.to_string(),
+            ));
+            config.rate_providers.push(rate_provider);
+            CONFIG.save(deps.storage, &config)?;
+        }
+        StablePoolUpdateParams::RemoveRateProvider { asset_info } => {
+            if !config
+                .rate_providers
+                .iter()
+                .any(|p| p.asset_info == asset_info)
+            {
+                return Err(ContractError::RateProviderNotFound(asset_info.to_string()));
+            }
+
+            accumulate_prices_before_rates_change(deps.branch(), &env, &mut config)?;
+            config.rate_providers.retain(|p| p.asset_info != asset_info);
+            CONFIG.save(deps.storage, &config)?;
+            response
+                .attributes
+                .push(attr("action", "remove_rate_provider"));
+            response
+                .attributes
+                .push(attr("asset_info", asset_info.to_string()));
+        }
     }
 
     Ok(response)
 }
 
+/// Accumulates prices with the exchange rates in use before the rate providers change,
+/// so the elapsed period is not priced with the new rates.
+/// Rates which can't be queried are skipped, so that a broken rate provider can still be replaced.
+fn accumulate_prices_before_rates_change(
+    deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+) -> Result<(), ContractError> {
+    let Ok(rates) = query_exchange_rates(&deps.querier, env, config) else {
+        return Ok(());
+    };
+
+    let (pools, _) = pool_info(deps.querier, config)?;
+    let pools = pools
+        .iter()
+        .map(|pool| {
+            let precision = get_precision(deps.storage, &pool.info)?;
+            pool.to_decimal_asset(precision)
+        })
+        .collect::<StdResult<Vec<DecimalAsset>>>()?;
+    accumulate_prices(deps.storage, env, config, &pools, &rates)?;
+
+    Ok(())
+}
+
 /// Start changing the AMP value.
 ///
 /// * **next_amp** new value for AMP.
@@ -1207,12 +2155,13 @@ fn query_compute_d(deps: Deps, env: Env) -> StdResult<Uint128> {
     let config = CONFIG.load(deps.storage)?;
 
     let amp = compute_current_amp(&config, &env)?;
-    let pools = config
-        .pair_info
-        .query_pools_decimal(&deps.querier, env.contract.address, &config.factory_addr)?
-        .into_iter()
-        .map(|pool| pool.amount)
-        .collect::<Vec<_>>();
+    let rates = query_exchange_rates(&deps.querier, &env, &config)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let pools = rates.scale_pools(&config.pair_info.query_pools_decimal(
+        &deps.querier,
+        &env.contract.address,
+        &config.factory_addr,
+    )?)?;
 
     compute_d(amp, &pools)
         .map_err(|_| StdError::generic_err("Failed to calculate the D"))?
@@ -1,7 +1,12 @@
-use cosmwasm_std::{CheckedMultiplyRatioError, ConversionOverflowError, OverflowError, StdError};
+use cosmwasm_std::{
+    CheckedMultiplyRatioError, ConversionOverflowError, OverflowError, StdError, Uint128,
+};
 use thiserror::Error;
 
-use palomadex::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
+use palomadex::{
+    asset::MINIMUM_LIQUIDITY_AMOUNT,
+    pair::{DeadlineExpired, MAX_FEE_SHARE_BPS},
+};
 use palomadex_circular_buffer::error::BufferError;
 
 use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
@@ -21,15 +26,42 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Swaps and liquidity provision are paused")]
+    Paused {},
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
+    #[error("Slippage is more than expected: received {0}, expected {1} LP tokens")]
+    ProvideSlippageViolation(Uint128, Uint128),
+
+    #[error("Swap requires {required} of the offer asset, but the maximum offer is {max_offer}")]
+    MaxOfferExceeded {
+        required: Uint128,
+        max_offer: Uint128,
+    },
+
+    #[error("{0}")]
+    DeadlineExpired(#[from] DeadlineExpired),
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
     #[error("Event of zero transfer")]
     InvalidZeroAmount {},
 
+    #[error("Withdrawing the requested assets requires {required} LP tokens, but only {provided} were sent")]
+    NotEnoughLpTokens {
+        required: Uint128,
+        provided: Uint128,
+    },
+
+    #[error("Requested withdrawal exceeds the pool reserves")]
+    WithdrawExceedsReserves {},
+
+    #[error("Operation exceeds max splippage tolerance")]
+    MaxSlippageAssertion {},
+
     #[error("Insufficient amount of liquidity")]
     LiquidityAmountTooSmall {},
 
@@ -95,6 +127,30 @@ pub enum ContractError {
         MAX_FEE_SHARE_BPS
     )]
     FeeShareOutOfBounds {},
+
+    #[error("EMA half-life must be greater than zero")]
+    InvalidEmaHalfLife {},
+
+    #[error("Rate provider for {0} is specified more than once")]
+    DoublingRateProviders(String),
+
+    #[error("Maximum staleness of the rate provider for {0} must be greater than zero")]
+    InvalidMaxStaleness(String),
+
+    #[error("No rate provider is set for {0}")]
+    RateProviderNotFound(String),
+
+    #[error("Rate provider for {0} reported a zero exchange rate")]
+    ZeroExchangeRate(String),
+
+    #[error(
+        "Exchange rate of {asset} updated at {updated_at} is stale, the block time is {block_time}"
+    )]
+    StaleExchangeRate {
+        asset: String,
+        updated_at: u64,
+        block_time: u64,
+    },
 }
 
 impl From<OverflowError> for ContractError {
@@ -3,7 +3,7 @@ use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint64};
 use palomadex::asset::Decimal256Ext;
 
 /// The maximum number of calculation steps for Newton's method.
-const ITERATIONS: u8 = 64;
+pub(crate) const ITERATIONS: u8 = 64;
 
 pub const MAX_AMP: u64 = 1_000_000;
 pub const MAX_AMP_CHANGE: u64 = 10;
@@ -89,6 +89,22 @@ pub(crate) fn calc_y(
     target_precision: u8,
 ) -> StdResult<Uint128> {
     let d = compute_d(amp, xp)?;
+    calc_y_d(amp, new_amount, d, target_precision)
+}
+
+/// Compute the amount `y` of one asset that keeps the invariant at `d` when the other asset amount is `new_amount`.
+///
+/// * **Solve for y**
+///
+/// y**2 + y * (sum' - (A*n**n - 1) * D / (A * n**n)) = D ** (n + 1) / (n ** (2 * n) * prod' * A)
+///
+/// y**2 + b*y = c
+pub(crate) fn calc_y_d(
+    amp: Uint64,
+    new_amount: Decimal256,
+    d: Decimal256,
+    target_precision: u8,
+) -> StdResult<Uint128> {
     let leverage = Decimal256::from_ratio(amp, 1u8) * N_COINS;
     let amp_prec = Decimal256::from_ratio(AMP_PRECISION, 1u8);
 
@@ -117,3 +133,112 @@ pub(crate) fn calc_y(
     // Should definitely converge in 64 iterations.
     Err(StdError::generic_err("y is not converging"))
 }
+
+/// Returns the fee charged on the deviation of each pool from its ideal balance after an imbalanced
+/// liquidity change: fee * n / (4 * (n - 1)).
+pub(crate) fn imbalance_fee_rate(fee: Decimal256) -> Decimal256 {
+    fee * N_COINS / (Decimal256::from_ratio(4u8, 1u8) * (N_COINS - Decimal256::one()))
+}
+
+/// Computes the invariant (D) of `old_balances` and the imbalance fee charged on each pool when the
+/// pools change to `new_balances`. The fee is charged on the difference between the new balances
+/// and the balances that keep the pool ratio.
+///
+/// * **fee** the total swap fee rate of the pair.
+pub(crate) fn compute_imbalance_fees(
+    amp: Uint64,
+    old_balances: &[Decimal256],
+    new_balances: &[Decimal256],
+    fee: Decimal256,
+) -> StdResult<(Decimal256, Vec<Decimal256>)> {
+    let d0 = compute_d(amp, old_balances)?;
+    let d1 = compute_d(amp, new_balances)?;
+
+    let fee = imbalance_fee_rate(fee);
+    let fees = old_balances
+        .iter()
+        .zip(new_balances)
+        .map(|(old_balance, new_balance)| {
+            let ideal_balance = old_balance.checked_multiply_ratio(d1, d0)?;
+            Ok(fee * ideal_balance.abs_diff(*new_balance))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((d0, fees))
+}
+
+/// Computes the invariant (D) before and after withdrawing `amounts` from the pools.
+/// The imbalance fee stays in the pool, so the second value is the invariant the LP burn is based on.
+///
+/// * **xp** the current pool amounts.
+///
+/// * **amounts** the amounts to withdraw, in the same order as `xp`.
+///
+/// * **fee** the total swap fee rate of the pair.
+pub(crate) fn compute_withdraw_imbalance_d(
+    amp: Uint64,
+    xp: &[Decimal256],
+    amounts: &[Decimal256],
+    fee: Decimal256,
+) -> StdResult<(Decimal256, Decimal256)> {
+    let new_balances = xp
+        .iter()
+        .zip(amounts)
+        .map(|(pool, amount)| pool.checked_sub(*amount))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (d0, fees) = compute_imbalance_fees(amp, xp, &new_balances, fee)?;
+
+    let new_balances = new_balances
+        .iter()
+        .zip(fees)
+        .map(|(balance, fee)| balance.checked_sub(fee))
+        .collect::<Result<Vec<_>, _>>()?;
+    let d2 = compute_d(amp, &new_balances)?;
+
+    Ok((d0, d2))
+}
+
+/// Computes the amount of the asset at index `i` returned when burning a `share` of the LP supply
+/// for that asset only. The imbalance fee is charged in the same way as for imbalanced withdrawals.
+///
+/// * **xp** the current pool amounts.
+///
+/// * **share** the share of the LP supply that is burned.
+///
+/// * **fee** the total swap fee rate of the pair.
+///
+/// * **target_precision** the precision of the returned asset.
+pub(crate) fn compute_withdraw_one_coin(
+    amp: Uint64,
+    xp: &[Decimal256],
+    i: usize,
+    share: Decimal256,
+    fee: Decimal256,
+    target_precision: u8,
+) -> StdResult<Uint128> {
+    let j = 1 - i;
+    let d0 = compute_d(amp, xp)?;
+    let d1 = d0.checked_sub(d0.checked_mul(share)?)?;
+    let new_y = Decimal256::with_precision(
+        calc_y_d(amp, xp[j], d1, target_precision)?,
+        target_precision,
+    )?;
+
+    let fee = imbalance_fee_rate(fee);
+    let mut xp_reduced = xp.to_vec();
+    for (k, pool) in xp_reduced.iter_mut().enumerate() {
+        let ideal_balance = xp[k].checked_multiply_ratio(d1, d0)?;
+        let dx_expected = if k == i {
+            ideal_balance.saturating_sub(new_y)
+        } else {
+            xp[k].checked_sub(ideal_balance)?
+        };
+        *pool = pool.checked_sub(fee * dx_expected)?;
+    }
+
+    let y = calc_y_d(amp, xp_reduced[j], d1, target_precision)?;
+
+    Ok(xp_reduced[i]
+        .to_uint128_with_precision(target_precision)?
+        .saturating_sub(y))
+}
@@ -7,7 +7,7 @@ use cosmwasm_std::{
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
-use palomadex::factory::QueryMsg::{Config, FeeInfo};
+use palomadex::factory::QueryMsg::{Config, FeeInfo, IsPaused};
 use palomadex::factory::{Config as FactoryConfig, ConfigResponse, FeeInfoResponse};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
@@ -90,6 +90,7 @@ impl WasmMockQuerier {
                             })
                             .into(),
                         ),
+                        IsPaused { .. } => SystemResult::Ok(to_json_binary(&false).into()),
                         Config {} => SystemResult::Ok(
                             to_json_binary(&ConfigResponse {
                                 owner: Addr::unchecked("owner"),
@@ -99,8 +100,9 @@ impl WasmMockQuerier {
                                 generator_address: None,
                                 whitelist_code_id: 0,
                                 coin_registry_address: Addr::unchecked("coin_registry"),
-                                bonding_curve_factory_address: None,
-                                pusd_denom: None,
+                                guardian: None,
+                                timelock_delay: 0,
+                                canceller: None,
                             })
                             .into(),
                         ),
@@ -168,8 +170,9 @@ impl WasmMockQuerier {
                                 generator_address: None,
                                 whitelist_code_id: 0,
                                 coin_registry_address: Addr::unchecked("coin_registry"),
-                                bonding_curve_factory_address: None,
-                                pusd_denom: None,
+                                guardian: None,
+                                timelock_delay: 0,
+                                canceller: None,
                             })
                             .into(),
                         )
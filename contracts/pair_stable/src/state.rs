@@ -33,6 +33,20 @@ pub struct Config {
     pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The rate providers of yield-bearing pool assets
+    #[serde(default)]
+    pub rate_providers: Vec<RateProvider>,
+}
+
+/// This structure stores a contract which reports the exchange rate of a pool asset.
+#[cw_serde]
+pub struct RateProvider {
+    /// The pool asset whose exchange rate is reported
+    pub asset_info: AssetInfo,
+    /// The rate provider contract address
+    pub contract_addr: Addr,
+    /// The maximum age (in seconds) of a rate that the pool accepts
+    pub max_staleness: u64,
 }
 
 /// Circular buffer to store trade size observations
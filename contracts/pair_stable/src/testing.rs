@@ -2,34 +2,43 @@ use std::error::Error;
 use std::str::FromStr;
 
 use basic_stableswap_sim::StableSwapModel;
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal, DepsMut, Env,
-    Reply, ReplyOn, Response, SubMsg, SubMsgResponse, SubMsgResult, Timestamp, Uint128, WasmMsg,
+    attr, coin, from_json, to_json_binary, Addr, BankMsg, BlockInfo, Coin, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, OwnedDeps, Reply, ReplyOn, Response, SubMsg, SubMsgResponse,
+    SubMsgResult, Timestamp, Uint128, Uint64, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use itertools::Itertools;
 use proptest::prelude::*;
 use prost::Message;
 
-use palomadex::asset::{native_asset, native_asset_info, Asset, AssetInfo};
+use palomadex::asset::{native_asset, native_asset_info, Asset, AssetInfo, Decimal256Ext};
 use palomadex::observation::query_observation;
 use palomadex::observation::Observation;
 use palomadex::observation::OracleObservation;
+use palomadex::observation::{query_twap, PrecommitObservation, TwapResponse};
+use palomadex::observation::{ObservationsResponse, OrderBy};
 use palomadex::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, SimulationResponse, StablePoolParams,
+    Cw20HookMsg, DeadlineExpired, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    RateProviderParams, SimulationResponse, StablePoolParams, StablePoolUpdateParams,
 };
 use palomadex::token::InstantiateMsg as TokenInstantiateMsg;
+use palomadex_circular_buffer::error::BufferError;
 use palomadex_circular_buffer::BufferManager;
 
 use crate::contract::{
-    assert_max_spread, execute, instantiate, query_pool, query_reverse_simulation, query_share,
-    query_simulation, reply,
+    assert_max_spread, execute, instantiate, migrate, query, query_pool, query_reverse_simulation,
+    query_share, query_simulate_withdraw, query_simulate_withdraw_one_coin, query_simulation,
+    reply,
 };
 use crate::error::ContractError;
-use crate::mock_querier::mock_dependencies;
-use crate::state::{CONFIG, OBSERVATIONS};
-use crate::utils::{compute_swap, select_pools};
+use crate::math::{compute_withdraw_one_coin, AMP_PRECISION};
+use crate::mock_querier::{mock_dependencies, WasmMockQuerier};
+use crate::state::{RateProvider, CONFIG, OBSERVATIONS};
+use crate::utils::{
+    compute_exact_out_offer_amount, compute_swap, query_exchange_rates, select_pools,
+};
 
 #[derive(Clone, PartialEq, Message)]
 struct MsgInstantiateContractResponse {
@@ -85,6 +94,7 @@ fn proper_initialization() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -142,6 +152,129 @@ fn proper_initialization() {
     );
 }
 
+#[test]
+fn instantiate_validates_rate_providers() {
+    let mut deps = mock_dependencies(&[]);
+    let rate_provider = |denom: &str| RateProviderParams {
+        asset_info: native_asset_info(denom.to_string()),
+        contract_addr: String::from("rate_provider"),
+        max_staleness: 3600,
+    };
+    let msg = |rate_providers: Vec<RateProviderParams>| InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uluna".to_string()),
+        ],
+        token_code_id: 10u64,
+        init_params: Some(
+            to_json_binary(&StablePoolParams {
+                amp: 100,
+                owner: None,
+                rate_providers: Some(rate_providers),
+            })
+            .unwrap(),
+        ),
+    };
+
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg(vec![rate_provider("ibc/usd")]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAsset("ibc/usd".to_string()));
+
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg(vec![rate_provider("uluna"), rate_provider("uluna")]),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DoublingRateProviders("uluna".to_string())
+    );
+
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg(vec![RateProviderParams {
+            max_staleness: 0,
+            ..rate_provider("uluna")
+        }]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMaxStaleness("uluna".to_string()));
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg(vec![rate_provider("uluna")]),
+    )
+    .unwrap();
+    assert_eq!(
+        CONFIG.load(deps.as_ref().storage).unwrap().rate_providers,
+        vec![RateProvider {
+            asset_info: native_asset_info("uluna".to_string()),
+            contract_addr: Addr::unchecked("rate_provider"),
+            max_staleness: 3600,
+        }]
+    );
+}
+
+#[test]
+fn pool_owner_cannot_change_rate_providers() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uluna".to_string()),
+        ],
+        token_code_id: 10u64,
+        init_params: Some(
+            to_json_binary(&StablePoolParams {
+                amp: 100,
+                owner: Some(String::from("pool_owner")),
+                rate_providers: None,
+            })
+            .unwrap(),
+        ),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // Rate provider changes only come through the factory timelock
+    let updates = [
+        StablePoolUpdateParams::SetRateProvider {
+            provider: RateProviderParams {
+                asset_info: native_asset_info("uluna".to_string()),
+                contract_addr: String::from("rate_provider"),
+                max_staleness: 3600,
+            },
+        },
+        StablePoolUpdateParams::RemoveRateProvider {
+            asset_info: native_asset_info("uluna".to_string()),
+        },
+    ];
+    for params in updates {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("pool_owner", &[]),
+            ExecuteMsg::UpdateConfig {
+                params: to_json_binary(&params).unwrap(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+}
+
 #[test]
 fn provide_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
@@ -175,6 +308,7 @@ fn provide_liquidity() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -206,6 +340,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -322,6 +458,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -335,7 +473,8 @@ fn provide_liquidity() {
 
     let res: Response = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let maker_fee_msgs = &res.messages[1..3];
+    let mint_msg = res.messages.get(3).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg {
@@ -362,7 +501,7 @@ fn provide_liquidity() {
                 contract_addr: String::from("liquidity0000"),
                 msg: to_json_binary(&Cw20ExecuteMsg::Mint {
                     recipient: String::from("addr0000"),
-                    amount: Uint128::new(74_981_956_874_579_206461),
+                    amount: Uint128::new(74_944_452_888_487_171363),
                 })
                 .unwrap(),
                 funds: vec![],
@@ -373,6 +512,32 @@ fn provide_liquidity() {
             reply_on: ReplyOn::Never,
         }
     );
+    // The 1:2 deposit deviates from the 1:1 pool ratio, so it is charged the imbalance fee
+    assert_eq!(
+        res.attributes[5],
+        attr(
+            "imbalance_fee",
+            "74945870623737619asset0000, 75054129376262380uusd"
+        )
+    );
+    assert_eq!(
+        maker_fee_msgs,
+        [
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: Uint128::new(12441014523540444),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("fee_address"),
+                amount: vec![coin(12458985476459555, "uusd")],
+            }),
+        ]
+    );
 
     // Check wrong argument
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -392,6 +557,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -467,6 +634,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -506,6 +675,8 @@ fn provide_liquidity() {
         ],
         slippage_tolerance: Some(Decimal::percent(1)),
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -552,6 +723,7 @@ fn withdraw_liquidity() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -679,6 +851,7 @@ fn try_native_to_token() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -704,6 +877,7 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -836,6 +1010,7 @@ fn try_token_to_native() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -861,6 +1036,7 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -876,6 +1052,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -974,6 +1151,7 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1058,6 +1236,7 @@ fn test_query_pool() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -1129,6 +1308,7 @@ fn test_query_share() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -1174,6 +1354,7 @@ fn observations_full_buffer() {
             ts: env.block.time.seconds() + i * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i, i * i),
+            ..Default::default()
         })
         .collect_vec();
     buffer.push_many(&array);
@@ -1222,6 +1403,7 @@ fn observations_incomplete_buffer() {
             ts: env.block.time.seconds() + i * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i, i * i),
+            ..Default::default()
         })
         .collect_vec();
     buffer.push_many(&array);
@@ -1246,6 +1428,154 @@ fn observations_incomplete_buffer() {
     );
 }
 
+#[test]
+fn twap_over_observations() {
+    let mut deps = mock_dependencies(&[]);
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100_000);
+    BufferManager::init(&mut deps.storage, OBSERVATIONS, 20).unwrap();
+
+    let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+
+    let err = query_twap(deps.as_ref(), env.clone(), OBSERVATIONS, 1000, 0).unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Buffer is empty");
+
+    // The buffer wraps around and keeps observations from 111_000 to 130_000 only
+    let array = (1..=30)
+        .map(|i| Observation {
+            ts: env.block.time.seconds() + i * 1000,
+            price: Decimal::from_ratio(i, 1u8),
+            price_sma: Default::default(),
+            ..Default::default()
+        })
+        .collect_vec();
+    buffer.push_many(&array);
+    buffer.commit(&mut deps.storage).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(31_000);
+
+    assert_eq!(
+        TwapResponse {
+            start_timestamp: 111_000,
+            end_timestamp: 131_000,
+            price: f64_to_dec(20.5),
+        },
+        query_twap(deps.as_ref(), env.clone(), OBSERVATIONS, 20_000, 0).unwrap()
+    );
+    assert_eq!(
+        TwapResponse {
+            start_timestamp: 115_500,
+            end_timestamp: 120_500,
+            price: f64_to_dec(17.5),
+        },
+        query_twap(deps.as_ref(), env.clone(), OBSERVATIONS, 15_500, 10_500).unwrap()
+    );
+
+    let err = query_twap(deps.as_ref(), env.clone(), OBSERVATIONS, 20_001, 0).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Requested window is not covered by observations. Oldest observation is at 111000"
+    );
+    let err = query_twap(deps.as_ref(), env.clone(), OBSERVATIONS, 100, 100).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: start_seconds_ago must be greater than end_seconds_ago"
+    );
+
+    // A precommitted observation is taken into account until it gets saved
+    let mut precommit_env = env.clone();
+    precommit_env.block.time = Timestamp::from_seconds(130_500);
+    PrecommitObservation::save(
+        &mut deps.storage,
+        &precommit_env,
+        62u128.into(),
+        2u128.into(),
+    )
+    .unwrap();
+    assert_eq!(
+        TwapResponse {
+            start_timestamp: 111_000,
+            end_timestamp: 131_000,
+            price: f64_to_dec(20.525),
+        },
+        query_twap(deps.as_ref(), env, OBSERVATIONS, 20_000, 0).unwrap()
+    );
+}
+
+#[test]
+fn raw_observations_pagination() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    BufferManager::init(&mut deps.storage, OBSERVATIONS, 10).unwrap();
+
+    let query_page = |deps: Deps, start_after_ts, limit, order| {
+        let msg = QueryMsg::Observations {
+            start_after_ts,
+            limit,
+            order,
+        };
+        from_json::<ObservationsResponse>(query(deps, mock_env(), msg).unwrap())
+            .unwrap()
+            .observations
+            .into_iter()
+            .map(|obs| obs.ts)
+            .collect_vec()
+    };
+    assert_eq!(
+        query_page(deps.as_ref(), None, None, None),
+        Vec::<u64>::new()
+    );
+
+    // The buffer wraps around and keeps observations from 111_000 to 120_000 only
+    let array = (1..=20)
+        .map(|i| Observation {
+            ts: 100_000 + i * 1000,
+            price: Decimal::from_ratio(i, 1u8),
+            price_sma: Decimal::from_ratio(i, 2u8),
+            ..Default::default()
+        })
+        .collect_vec();
+    let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    buffer.push_many(&array);
+    buffer.commit(&mut deps.storage).unwrap();
+
+    let res: ObservationsResponse = from_json(
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Observations {
+                start_after_ts: None,
+                limit: Some(1),
+                order: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.observations, vec![array[10]]);
+
+    assert_eq!(
+        query_page(deps.as_ref(), Some(115_000), Some(3), Some(OrderBy::Asc)),
+        vec![116_000, 117_000, 118_000]
+    );
+    assert_eq!(
+        query_page(deps.as_ref(), Some(118_000), None, None),
+        vec![119_000, 120_000]
+    );
+    assert_eq!(
+        query_page(deps.as_ref(), None, Some(3), Some(OrderBy::Desc)),
+        vec![120_000, 119_000, 118_000]
+    );
+    assert_eq!(
+        query_page(deps.as_ref(), Some(113_500), None, Some(OrderBy::Desc)),
+        vec![113_000, 112_000, 111_000]
+    );
+    assert_eq!(
+        query_page(deps.as_ref(), None, None, None),
+        (111_000..=120_000).step_by(1000).collect_vec()
+    );
+}
+
 #[test]
 fn observations_checking_triple_capacity_step_by_step() {
     let mut deps = mock_dependencies(&[]);
@@ -1264,6 +1594,7 @@ fn observations_checking_triple_capacity_step_by_step() {
             ts: ts + i as u64 * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i * i, i),
+            ..Default::default()
         })
         .collect_vec();
 
@@ -1327,7 +1658,14 @@ proptest! {
             factory_addr: String::from("factory"),
             asset_infos: vec![offer_asset.info.clone(), ask_asset.clone()],
             token_code_id: 10u64,
-            init_params: Some(to_json_binary(&StablePoolParams { amp, owner: None }).unwrap()),
+            init_params: Some(
+                to_json_binary(&StablePoolParams {
+                    amp,
+                    owner: None,
+                    rate_providers: None,
+                })
+                .unwrap(),
+            ),
         };
 
         let env = mock_env();
@@ -1340,6 +1678,7 @@ proptest! {
             .pair_info
             .query_pools_decimal(&deps.as_ref().querier, &env.contract.address, &config.factory_addr)
             .unwrap();
+        let rates = query_exchange_rates(&deps.as_ref().querier, &env, &config).unwrap();
         let (offer_pool, ask_pool) =
         select_pools(Some(&offer_asset.info), None, &pools).unwrap();
 
@@ -1351,6 +1690,7 @@ proptest! {
             &offer_pool,
             &ask_pool,
             &pools,
+            &rates,
         )
         .unwrap();
 
@@ -1396,3 +1736,570 @@ proptest! {
         );
     }
 }
+
+/// Instantiates a uusd/uluna pool holding `balances` with `total_share` LP tokens owned by addr0000.
+fn mock_stable_pool(
+    balances: [u128; 2],
+    total_share: u128,
+    amp: u64,
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies(&[coin(balances[0], "uusd"), coin(balances[1], "uluna")]);
+    deps.querier.with_token_balances(&[(
+        &String::from("liquidity0000"),
+        &[(&String::from("addr0000"), &Uint128::new(total_share))],
+    )]);
+
+    let msg = InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: vec![
+            native_asset_info("uusd".to_string()),
+            native_asset_info("uluna".to_string()),
+        ],
+        token_code_id: 10u64,
+        init_params: Some(
+            to_json_binary(&StablePoolParams {
+                amp,
+                owner: None,
+                rate_providers: None,
+            })
+            .unwrap(),
+        ),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    deps
+}
+
+#[test]
+fn withdraw_liquidity_imbalanced_and_one_coin() {
+    let mut deps = mock_stable_pool([1_000_000_000, 1_000_000_000], 2_000_000_000, 100);
+    let info = mock_info("liquidity0000", &[]);
+    let withdraw_msg = |msg: Cw20HookMsg, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_json_binary(&msg).unwrap(),
+            amount: Uint128::new(amount),
+        })
+    };
+
+    // Withdrawing 10% of uusd only costs more than the 5% of the LP supply a balanced withdrawal would
+    let assets = vec![native_asset("uusd".to_string(), Uint128::new(100_000_000))];
+    let burn_amount = query_simulate_withdraw(deps.as_ref(), mock_env(), assets.clone()).unwrap();
+    assert!(burn_amount > Uint128::new(100_000_000));
+    assert!(burn_amount < Uint128::new(100_500_000));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(
+            Cw20HookMsg::WithdrawLiquidity {
+                assets: assets.clone(),
+            },
+            100_000_000,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotEnoughLpTokens {
+            required: burn_amount,
+            provided: Uint128::new(100_000_000),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(
+            Cw20HookMsg::WithdrawLiquidity {
+                assets: vec![native_asset(
+                    "uusd".to_string(),
+                    Uint128::new(1_000_000_000),
+                )],
+            },
+            2_000_000_000,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WithdrawExceedsReserves {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(
+            Cw20HookMsg::WithdrawLiquidity {
+                assets: assets.clone(),
+            },
+            200_000_000,
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![coin(100_000_000, "uusd")],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: burn_amount
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::new(200_000_000) - burn_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // Burning the same LP amount for uusd only returns the requested amount within 0.001%
+    let return_amount = query_simulate_withdraw_one_coin(
+        deps.as_ref(),
+        mock_env(),
+        burn_amount,
+        native_asset_info("uusd".to_string()),
+    )
+    .unwrap();
+    assert!(return_amount.abs_diff(Uint128::new(100_000_000)) <= Uint128::new(1_000));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        withdraw_msg(
+            Cw20HookMsg::WithdrawLiquidityOneCoin {
+                ask_asset_info: native_asset_info("uusd".to_string()),
+                min_return: Some(return_amount + Uint128::one()),
+            },
+            burn_amount.u128(),
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxSlippageAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        withdraw_msg(
+            Cw20HookMsg::WithdrawLiquidityOneCoin {
+                ask_asset_info: native_asset_info("uusd".to_string()),
+                min_return: Some(return_amount),
+            },
+            burn_amount.u128(),
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![coin(return_amount.u128(), "uusd")],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_json_binary(&Cw20ExecuteMsg::Burn {
+                    amount: burn_amount
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+}
+
+proptest! {
+    #[test]
+    fn compute_exact_out_offer_amount_is_close_to_minimal(
+        balance_in in 1_000_000..1_000_000_000_000_000u128,
+        balance_ratio in 10..1000u128,
+        ratio in 1..900u128,
+        amp in 1..150u64
+    ) {
+        let balance_out = balance_in * balance_ratio / 100;
+        let ask_amount = Uint128::new(balance_out * ratio / 1000);
+
+        let deps = mock_stable_pool([balance_in, balance_out], 1_000_000, amp);
+        let env = mock_env();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        let pools = config
+            .pair_info
+            .query_pools_decimal(&deps.as_ref().querier, &env.contract.address, &config.factory_addr)
+            .unwrap();
+        let rates = query_exchange_rates(&deps.as_ref().querier, &env, &config).unwrap();
+        let (offer_pool, ask_pool) = select_pools(None, Some(&pools[1].info), &pools).unwrap();
+        let commission_rate = Decimal::permille(3);
+
+        let return_amount = |offer_amount: Uint128| {
+            let offer_asset = native_asset("uusd".to_string(), offer_amount)
+                .to_decimal_asset(6u8)
+                .unwrap();
+            let return_amount = compute_swap(
+                deps.as_ref().storage,
+                &env,
+                &config,
+                &offer_asset,
+                &offer_pool,
+                &ask_pool,
+                &pools,
+                &rates,
+            )
+            .unwrap()
+            .return_amount;
+            return_amount - commission_rate * return_amount
+        };
+
+        let offer_amount = compute_exact_out_offer_amount(
+            deps.as_ref().storage,
+            &env,
+            &config,
+            &offer_pool,
+            &ask_pool,
+            &pools,
+            ask_amount,
+            commission_rate,
+            &rates,
+        )
+        .unwrap();
+        prop_assert!(return_amount(offer_amount) >= ask_amount);
+        // The invariant solver tolerance can make the estimate overshoot slightly
+        let tolerance = Uint128::one() + offer_amount.multiply_ratio(1u8, 1_000_000u128);
+        prop_assert!(return_amount(offer_amount - tolerance) < ask_amount);
+    }
+
+    #[test]
+    fn withdraw_imbalance_matches_sim(
+        balance0 in 1_000_000..1_000_000_000_000_000u128,
+        balance1 in 1_000_000..1_000_000_000_000_000u128,
+        total_share in 1_000_000..1_000_000_000_000_000u128,
+        ratio0 in 0..500u128,
+        ratio1 in 0..500u128,
+        amp in 1..150u64
+    ) {
+        prop_assume!(ratio0 + ratio1 > 0);
+        prop_assume!(balance0 / balance1 < 10 && balance1 / balance0 < 10);
+
+        let deps = mock_stable_pool([balance0, balance1], total_share, amp);
+        let amounts = [balance0 * ratio0 / 1000, balance1 * ratio1 / 1000];
+        let assets = vec![
+            native_asset("uusd".to_string(), Uint128::new(amounts[0])),
+            native_asset("uluna".to_string(), Uint128::new(amounts[1])),
+        ];
+        let result = query_simulate_withdraw(deps.as_ref(), mock_env(), assets).unwrap();
+
+        let mut model =
+            StableSwapModel::new_with_pool_tokens(amp.into(), vec![balance0, balance1], 2, total_share);
+        // 30 bps in the 1e10 fee precision of the model
+        model.fee = 30_000_000;
+        let sim_result = model.sim_remove_liquidity_imbalance(amounts.to_vec());
+
+        let diff = result.u128().abs_diff(sim_result);
+        let tolerance = 2 + sim_result / 1_000_000 + 4 * total_share / balance0.min(balance1);
+        assert!(
+            diff <= tolerance,
+            "result={}, sim_result={}, amp={}, amounts={:?}, balances={:?}, total_share={}, diff={}",
+            result,
+            sim_result,
+            amp,
+            amounts,
+            [balance0, balance1],
+            total_share,
+            diff
+        );
+    }
+
+    #[test]
+    fn withdraw_one_coin_matches_sim(
+        balance0 in 1_000_000..1_000_000_000_000_000u128,
+        balance1 in 1_000_000..1_000_000_000_000_000u128,
+        total_share in 1_000_000..1_000_000_000_000_000u128,
+        ratio in 1..500u128,
+        i in 0..2usize,
+        amp in 1..150u64
+    ) {
+        prop_assume!(balance0 / balance1 < 10 && balance1 / balance0 < 10);
+
+        let token_amount = total_share * ratio / 1000;
+        let xp = [balance0, balance1].map(|balance| Decimal256::with_precision(balance, 6u32).unwrap());
+        let share = Decimal256::from_ratio(token_amount, total_share);
+        let amp_with_precision = Uint64::new(amp * AMP_PRECISION);
+
+        let result =
+            compute_withdraw_one_coin(amp_with_precision, &xp, i, share, Decimal256::zero(), 6).unwrap();
+
+        let model =
+            StableSwapModel::new_with_pool_tokens(amp.into(), vec![balance0, balance1], 2, total_share);
+        let sim_result = model.sim_calc_withdraw_one_coin(token_amount, i as u128);
+
+        let diff = result.u128().abs_diff(sim_result);
+        assert!(
+            diff <= 2 + sim_result / 1_000_000,
+            "result={}, sim_result={}, amp={}, token_amount={}, balances={:?}, total_share={}, diff={}",
+            result,
+            sim_result,
+            amp,
+            token_amount,
+            [balance0, balance1],
+            total_share,
+            diff
+        );
+
+        // The imbalance fee only ever reduces the returned amount, by at most the fee rate
+        let fee = Decimal256::permille(3);
+        let result_with_fee =
+            compute_withdraw_one_coin(amp_with_precision, &xp, i, share, fee, 6).unwrap();
+        assert!(result_with_fee <= result);
+        assert!(result_with_fee >= result - result.multiply_ratio(3u8, 1000u16));
+    }
+}
+
+#[test]
+fn swap_exact_out() {
+    // Pool balances already include the 100_000_000 uusd sent as the maximum offer
+    let mut deps = mock_stable_pool([1_100_000_000, 1_000_000_000], 2_000_000_000, 100);
+    let swap_msg = |ask_amount: u128| ExecuteMsg::SwapExactOut {
+        ask_asset: native_asset("uluna".to_string(), Uint128::new(ask_amount)),
+        max_offer: native_asset("uusd".to_string(), Uint128::new(100_000_000)),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(100_000_000, "uusd")]);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        swap_msg(100_000_000),
+    )
+    .unwrap_err();
+    match err {
+        ContractError::MaxOfferExceeded {
+            required,
+            max_offer,
+        } => {
+            assert!(required > Uint128::new(100_000_000));
+            assert_eq!(max_offer, Uint128::new(100_000_000));
+        }
+        _ => panic!("unexpected error {err}"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(50_000_000)).unwrap();
+    let attribute = |key: &str| {
+        let value = &res.attributes.iter().find(|a| a.key == key).unwrap().value;
+        Uint128::from_str(value).unwrap()
+    };
+    let offer_amount = attribute("offer_amount");
+    assert_eq!(attribute("return_amount"), Uint128::new(50_000_000));
+    // A balanced pool with amp 100 trades close to 1:1 plus the 0.3% fee
+    assert!(offer_amount > Uint128::new(50_150_000));
+    assert!(offer_amount < Uint128::new(50_200_000));
+
+    assert_eq!(
+        res.messages[0].msg,
+        BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![coin(50_000_000, "uluna")],
+        }
+        .into()
+    );
+    assert_eq!(
+        res.messages.last().unwrap().msg,
+        BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![coin(100_000_000 - offer_amount.u128(), "uusd")],
+        }
+        .into()
+    );
+}
+
+#[test]
+fn provide_liquidity_with_min_lp_and_deadline() {
+    // Pool balances already include the 100_000_000 of each coin being deposited
+    let mut deps = mock_stable_pool([1_100_000_000, 1_100_000_000], 2_000_000_000, 100);
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: vec![
+                native_asset("uusd".to_string(), Uint128::new(100_000_000)),
+                native_asset("uluna".to_string(), Uint128::new(100_000_000)),
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+    let info = mock_info(
+        "addr0000",
+        &[coin(100_000_000, "uusd"), coin(100_000_000, "uluna")],
+    );
+    let env = mock_env();
+    let now = env.block.time.seconds();
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        provide_msg(None, Some(now - 1)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DeadlineExpired(DeadlineExpired {
+            deadline: now - 1,
+            block_time: now,
+        })
+    );
+
+    // A balanced deposit of 10% of the pool mints 10% of the LP supply
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        provide_msg(Some(Uint128::new(200_000_001)), None),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ProvideSlippageViolation(
+            Uint128::new(200_000_000),
+            Uint128::new(200_000_001)
+        )
+    );
+
+    execute(
+        deps.as_mut(),
+        env,
+        info,
+        provide_msg(Some(Uint128::new(200_000_000)), Some(now)),
+    )
+    .unwrap();
+}
+
+#[test]
+fn resize_observations() {
+    let mut deps = mock_stable_pool([1_000_000_000, 1_000_000_000], 2_000_000_000, 100);
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(100_000);
+
+    let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    let array = (1..=30)
+        .map(|i| Observation {
+            ts: env.block.time.seconds() + i * 1000,
+            price: Decimal::from_ratio(i, 1u8),
+            price_sma: Decimal::from_ratio(i, 1u8),
+            ..Default::default()
+        })
+        .collect_vec();
+    buffer.push_many(&array);
+    buffer.commit(&mut deps.storage).unwrap();
+    env.block.time = env.block.time.plus_seconds(30_000);
+
+    let resize_msg = |capacity: u32| ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&StablePoolUpdateParams::ResizeObservations { capacity }).unwrap(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        resize_msg(10),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory", &[]),
+        resize_msg(0),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CircularBuffer(BufferError::ZeroCapacity {})
+    );
+
+    // Shrinking keeps the latest 10 observations and rebases the moving average on them
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("factory", &[]),
+        resize_msg(10),
+    )
+    .unwrap();
+    let buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    assert_eq!(buffer.capacity(), 10);
+    let kept = buffer.read_all(&deps.storage).unwrap();
+    assert_eq!(
+        kept.iter().map(|obs| obs.ts).collect_vec(),
+        (121_000..=130_000).step_by(1000).collect_vec()
+    );
+    assert_eq!(kept[9].price_sma, f64_to_dec::<Decimal>(25.5));
+    assert_eq!(
+        query_observation(deps.as_ref(), env.clone(), OBSERVATIONS, 5500).unwrap(),
+        OracleObservation {
+            timestamp: 124_500,
+            price: f64_to_dec::<Decimal>(24.5),
+        }
+    );
+    let err = query_observation(deps.as_ref(), env.clone(), OBSERVATIONS, 10_000).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Requested observation is too old. Last known observation is at 121000"
+    );
+
+    // Growing keeps every observation
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("factory", &[]),
+        resize_msg(20),
+    )
+    .unwrap();
+    let buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    assert_eq!(buffer.capacity(), 20);
+    assert_eq!(buffer.head(), 10);
+    assert_eq!(buffer.read_all(&deps.storage).unwrap(), kept);
+}
+
+#[test]
+fn migrate_checks_contract_version() {
+    let mut deps = mock_stable_pool([1_000_000_000, 1_000_000_000], 2_000_000_000, 100);
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:palomadex-pair-stable",
+        "0.1.0",
+    )
+    .unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    // Migrating the current version again is a no-op
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    cw2::set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:palomadex-pair-stable",
+        "0.0.1",
+    )
+    .unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:another-contract", "0.1.0")
+        .unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+}
@@ -1,23 +1,21 @@
 use std::cmp::Ordering;
 
 use cosmwasm_std::{
-    wasm_execute, Addr, Api, CosmosMsg, Decimal, Decimal256, Env, StdResult, Storage, Uint128,
-    Uint64,
+    wasm_execute, Addr, Api, CosmosMsg, Decimal, Decimal256, Env, QuerierWrapper, StdError,
+    StdResult, Storage, Uint128, Uint64,
 };
 use cw20::Cw20ExecuteMsg;
 use itertools::Itertools;
 
-use palomadex::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset};
-use palomadex::observation::{
-    safe_sma_buffer_not_full, safe_sma_calculation, Observation, PrecommitObservation,
-};
+use palomadex::asset::{check_swap_parameters, Asset, AssetInfo, Decimal256Ext, DecimalAsset};
+use palomadex::cosmwasm_ext::IntegerToDecimal;
 use palomadex::pair::TWAP_PRECISION;
-use palomadex_circular_buffer::error::BufferResult;
-use palomadex_circular_buffer::BufferManager;
+use palomadex::rate_provider::{ExchangeRateResponse, QueryMsg as RateProviderQueryMsg};
+use palomadex::DecimalCheckedOps;
 
 use crate::error::ContractError;
-use crate::math::calc_y;
-use crate::state::{get_precision, Config, OBSERVATIONS};
+use crate::math::{calc_y, ITERATIONS};
+use crate::state::{get_precision, Config, RateProvider};
 
 /// Helper function to check if the given asset infos are valid.
 pub(crate) fn check_asset_infos(
@@ -208,6 +206,95 @@ pub(crate) fn get_share_in_assets(
         .collect()
 }
 
+/// Exchange rates of the pool assets for internal use. Pool balances are multiplied by the rate
+/// of their asset before the invariant is computed, so yield-bearing assets are valued in their
+/// underlying asset. Assets without a rate provider have a rate of one.
+pub(crate) struct ExchangeRates(Vec<(AssetInfo, Decimal)>);
+
+impl ExchangeRates {
+    /// Returns the exchange rate of the asset.
+    pub fn get(&self, asset_info: &AssetInfo) -> Decimal {
+        self.0
+            .iter()
+            .find(|(info, _)| info.eq(asset_info))
+            .map(|(_, rate)| *rate)
+            .unwrap_or_else(Decimal::one)
+    }
+
+    /// Returns the value of an asset amount in the underlying asset.
+    pub fn scale(&self, asset_info: &AssetInfo, amount: Decimal256) -> StdResult<Decimal256> {
+        Ok(amount.checked_mul(self.get(asset_info).into())?)
+    }
+
+    /// Returns the values of the pool balances in their underlying assets.
+    pub fn scale_pools(&self, pools: &[DecimalAsset]) -> StdResult<Vec<Decimal256>> {
+        pools
+            .iter()
+            .map(|pool| self.scale(&pool.info, pool.amount))
+            .collect()
+    }
+
+    /// Returns the asset amount worth `value` of the underlying asset, rounded down.
+    pub fn unscale_floor(&self, asset_info: &AssetInfo, value: Uint128) -> StdResult<Uint128> {
+        value
+            .checked_div_floor(self.get(asset_info))
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
+
+    /// Returns the asset amount worth `value` of the underlying asset, rounded up.
+    pub fn unscale_ceil(&self, asset_info: &AssetInfo, value: Uint128) -> StdResult<Uint128> {
+        value
+            .checked_div_ceil(self.get(asset_info))
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    }
+}
+
+/// Queries the exchange rates of the pool assets from their rate providers.
+/// Fails if a rate is zero or older than the staleness bound of its provider.
+pub(crate) fn query_exchange_rates(
+    querier: &QuerierWrapper,
+    env: &Env,
+    config: &Config,
+) -> Result<ExchangeRates, ContractError> {
+    config
+        .rate_providers
+        .iter()
+        .map(|provider| {
+            query_exchange_rate(querier, env, provider)
+                .map(|rate| (provider.asset_info.clone(), rate))
+        })
+        .collect::<Result<_, _>>()
+        .map(ExchangeRates)
+}
+
+/// Queries the exchange rate of a single rate provider.
+/// Fails if the rate is zero or older than the staleness bound of the provider.
+pub(crate) fn query_exchange_rate(
+    querier: &QuerierWrapper,
+    env: &Env,
+    provider: &RateProvider,
+) -> Result<Decimal, ContractError> {
+    let block_time = env.block.time.seconds();
+    let ExchangeRateResponse { rate, updated_at } = querier.query_wasm_smart(
+        &provider.contract_addr,
+        &RateProviderQueryMsg::ExchangeRate {},
+    )?;
+    if rate.is_zero() {
+        return Err(ContractError::ZeroExchangeRate(
+            provider.asset_info.to_string(),
+        ));
+    }
+    if block_time.saturating_sub(updated_at) > provider.max_staleness {
+        return Err(ContractError::StaleExchangeRate {
+            asset: provider.asset_info.to_string(),
+            updated_at,
+            block_time,
+        });
+    }
+
+    Ok(rate)
+}
+
 /// Structure for internal use which represents swap result.
 pub(crate) struct SwapResult {
     pub return_amount: Uint128,
@@ -223,6 +310,9 @@ pub(crate) struct SwapResult {
 /// * **ask_pool** asked asset.
 ///
 /// * **pools** array with assets available in the pool.
+///
+/// * **rates** exchange rates of the pool assets.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn compute_swap(
     storage: &dyn Storage,
     env: &Env,
@@ -231,23 +321,30 @@ pub(crate) fn compute_swap(
     offer_pool: &DecimalAsset,
     ask_pool: &DecimalAsset,
     pools: &[DecimalAsset],
+    rates: &ExchangeRates,
 ) -> Result<SwapResult, ContractError> {
     let token_precision = get_precision(storage, &ask_pool.info)?;
-    let xp = pools.iter().map(|p| p.amount).collect_vec();
+    let xp = rates.scale_pools(pools)?;
 
     let new_ask_pool = calc_y(
         compute_current_amp(config, env)?,
-        offer_pool.amount + offer_asset.amount,
+        rates.scale(&offer_pool.info, offer_pool.amount + offer_asset.amount)?,
         &xp,
         token_precision,
     )?;
+    // Round the new ask pool up in favour of the pool
+    let new_ask_pool = rates.unscale_ceil(&ask_pool.info, new_ask_pool)?;
 
     let return_amount = ask_pool.amount.to_uint128_with_precision(token_precision)? - new_ask_pool;
-    let offer_asset_amount = offer_asset
-        .amount
-        .to_uint128_with_precision(token_precision)?;
+    let offer_asset_amount = rates.unscale_floor(
+        &ask_pool.info,
+        rates
+            .scale(&offer_asset.info, offer_asset.amount)?
+            .to_uint128_with_precision(token_precision)?,
+    )?;
 
-    // We consider swap rate 1:1 in stable swap thus any difference is considered as spread.
+    // We consider swap rate 1:1 between the underlying assets in stable swap
+    // thus any difference is considered as spread.
     let spread_amount = offer_asset_amount.saturating_sub(return_amount);
 
     Ok(SwapResult {
@@ -256,14 +353,118 @@ pub(crate) fn compute_swap(
     })
 }
 
+/// Returns the smallest offer amount for which [`compute_swap`] returns at least `ask_amount`
+/// after the commission is deducted.
+///
+/// * **offer_pool** pool of offered asset.
+///
+/// * **ask_pool** asked asset.
+///
+/// * **pools** array with assets available in the pool.
+///
+/// * **ask_amount** amount of ask assets to receive after the commission.
+///
+/// * **commission_rate** total amount of fees charged for the swap.
+///
+/// * **rates** exchange rates of the pool assets.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compute_exact_out_offer_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    offer_pool: &DecimalAsset,
+    ask_pool: &DecimalAsset,
+    pools: &[DecimalAsset],
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+    rates: &ExchangeRates,
+) -> Result<Uint128, ContractError> {
+    let offer_precision = get_precision(storage, &offer_pool.info)?;
+    let ask_precision = get_precision(storage, &ask_pool.info)?;
+
+    check_swap_parameters(
+        pools
+            .iter()
+            .map(|pool| {
+                pool.amount
+                    .to_uint128_with_precision(get_precision(storage, &pool.info)?)
+            })
+            .collect::<StdResult<Vec<Uint128>>>()?,
+        ask_amount,
+    )?;
+
+    // The commission is rounded down, so the amount received is
+    // ceil(return_amount * (1 - commission_rate)). It reaches ask_amount as soon as
+    // return_amount > (ask_amount - 1) / (1 - commission_rate)
+    let before_commission = ((ask_amount - Uint128::one())
+        .checked_div_floor(Decimal::one() - commission_rate)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        + Uint128::one())
+    .to_decimal256(ask_precision)?;
+    if before_commission >= ask_pool.amount {
+        return Err(StdError::generic_err("Ask amount exceeds the available liquidity").into());
+    }
+
+    // Estimate the offer amount by solving the invariant for the new offer pool
+    let xp = rates.scale_pools(pools)?;
+    let new_offer_pool = calc_y(
+        compute_current_amp(config, env)?,
+        rates.scale(&ask_pool.info, ask_pool.amount - before_commission)?,
+        &xp,
+        offer_precision,
+    )?;
+    let mut offer_amount = rates
+        .unscale_ceil(&offer_pool.info, new_offer_pool)?
+        .checked_sub(
+            offer_pool
+                .amount
+                .to_uint128_with_precision(offer_precision)?,
+        )?
+        .max(Uint128::one());
+
+    // The invariant is only solved up to a tolerance and the estimate is rounded down,
+    // so top it up until the swap returns enough
+    for _ in 0..ITERATIONS {
+        let SwapResult { return_amount, .. } = compute_swap(
+            storage,
+            env,
+            config,
+            &DecimalAsset {
+                info: offer_pool.info.clone(),
+                amount: offer_amount.to_decimal256(offer_precision)?,
+            },
+            offer_pool,
+            ask_pool,
+            pools,
+            rates,
+        )?;
+        let return_amount =
+            return_amount.checked_sub(commission_rate.checked_mul_uint128(return_amount)?)?;
+        if return_amount >= ask_amount {
+            return Ok(offer_amount);
+        }
+
+        // Scale the shortfall by the average price of the swap, which never exceeds the
+        // marginal price, so the top up doesn't overshoot
+        offer_amount += (ask_amount - return_amount)
+            .multiply_ratio(offer_amount, return_amount.max(Uint128::one()))
+            .max(Uint128::one());
+    }
+
+    Err(StdError::generic_err("Offer amount is not converging").into())
+}
+
 /// Accumulate token prices for the assets in the pool.
 ///
 /// * **pools** array with assets available in the pool.
-pub fn accumulate_prices(
+///
+/// * **rates** exchange rates of the pool assets.
+pub(crate) fn accumulate_prices(
     storage: &dyn Storage,
     env: &Env,
     config: &mut Config,
     pools: &[DecimalAsset],
+    rates: &ExchangeRates,
 ) -> Result<bool, ContractError> {
     let block_time = env.block.time.seconds();
     if block_time <= config.block_time_last {
@@ -289,6 +490,7 @@ pub fn accumulate_prices(
                 &offer_pool,
                 &ask_pool,
                 pools,
+                rates,
             )?;
 
             *value = value.wrapping_add(time_elapsed.checked_mul(adjust_precision(
@@ -304,66 +506,6 @@ pub fn accumulate_prices(
     Ok(true)
 }
 
-/// Calculate and save price moving average
-pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResult<()> {
-    if let Some(PrecommitObservation {
-        base_amount,
-        quote_amount,
-        precommit_ts,
-    }) = PrecommitObservation::may_load(storage)?
-    {
-        let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
-        let observed_price = Decimal::from_ratio(base_amount, quote_amount);
-
-        let new_observation;
-        if let Some(last_obs) = buffer.read_last(storage)? {
-            // Skip saving observation if it has been already saved
-            if last_obs.ts < precommit_ts {
-                // Since this is circular buffer the next index contains the oldest value
-                let count = buffer.capacity();
-                if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
-                    let price_sma = safe_sma_calculation(
-                        last_obs.price_sma,
-                        oldest_obs.price,
-                        count,
-                        observed_price,
-                    )?;
-                    new_observation = Observation {
-                        ts: precommit_ts,
-                        price: observed_price,
-                        price_sma,
-                    };
-                } else {
-                    // Buffer is not full yet
-                    let count = buffer.head();
-                    let price_sma =
-                        safe_sma_buffer_not_full(last_obs.price_sma, count, observed_price)?;
-                    new_observation = Observation {
-                        ts: precommit_ts,
-                        price: observed_price,
-                        price_sma,
-                    };
-                }
-
-                buffer.instant_push(storage, &new_observation)?
-            }
-        } else {
-            // Buffer is empty
-            if env.block.time.seconds() > precommit_ts {
-                new_observation = Observation {
-                    ts: precommit_ts,
-                    price: observed_price,
-                    price_sma: observed_price,
-                };
-
-                buffer.instant_push(storage, &new_observation)?
-            }
-        }
-    }
-
-    Ok(())
-}
-
 /// Internal function to determine which asset is base one, which is quote one
 pub(crate) fn determine_base_quote_amount(
     pools: &[DecimalAsset],
@@ -5,23 +5,32 @@ use std::error::Error;
 use std::str::FromStr;
 
 use anyhow::Result as AnyResult;
-use cosmwasm_std::{coin, to_json_binary, Addr, Coin, Decimal, Empty, StdResult, Uint128};
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, Coin, Decimal, Empty, StdResult, Uint128,
+};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
 use derivative::Derivative;
 use itertools::Itertools;
-use palomadex_mocks::cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use palomadex_mocks::cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, Contract, ContractWrapper, Executor,
+    MockAddressGenerator, MockApiBech32, WasmKeeper,
+};
 
 use palomadex::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, PairInfo};
 use palomadex::factory::{PairConfig, PairType};
 use palomadex::observation::OracleObservation;
 use palomadex::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse, StablePoolParams,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg,
+    RateProviderParams, ReverseSimulationResponse, SimulationResponse, StablePoolConfig,
+    StablePoolParams, StablePoolUpdateParams,
 };
+use palomadex_mocks::rate_provider;
 pub const NATIVE_TOKEN_PRECISION: u8 = 6;
 use palomadex_pair_stable::contract::{execute, instantiate, query, reply};
 
 const INIT_BALANCE: u128 = 1_000_000_000000;
+/// The maximum age of exchange rates accepted by the pairs created with rate providers
+pub const RATE_MAX_STALENESS: u64 = 3600;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TestCoin {
@@ -84,6 +93,14 @@ fn pair_contract() -> Box<dyn Contract<Empty>> {
     Box::new(ContractWrapper::new_with_empty(execute, instantiate, query).with_reply_empty(reply))
 }
 
+fn rate_provider_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new_with_empty(
+        rate_provider::execute,
+        rate_provider::instantiate,
+        rate_provider::query,
+    ))
+}
+
 fn factory_contract() -> Box<dyn Contract<Empty>> {
     Box::new(
         ContractWrapper::new_with_empty(
@@ -103,17 +120,21 @@ fn store_coin_registry_code() -> Box<dyn Contract<Empty>> {
     ))
 }
 
+/// Pairs are created with instantiate2, which needs an api that can humanize the predicted addresses
+pub type TestApp = App<BankKeeper, MockApiBech32>;
+
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct Helper {
     #[derivative(Debug = "ignore")]
-    pub app: App,
+    pub app: TestApp,
     pub owner: Addr,
     pub assets: HashMap<TestCoin, AssetInfo>,
     pub factory: Addr,
     pub pair_addr: Addr,
     pub lp_token: Addr,
     pub amp: u64,
+    pub rate_providers: HashMap<TestCoin, Addr>,
 }
 
 impl Helper {
@@ -123,12 +144,26 @@ impl Helper {
         amp: u64,
         swap_fee: Option<u16>,
     ) -> AnyResult<Self> {
-        let mut app = App::new(|router, _, storage| {
-            router
-                .bank
-                .init_balance(storage, &owner, init_native_coins(&test_coins))
-                .unwrap()
-        });
+        Self::new_with_rate_providers(owner, test_coins, amp, swap_fee, vec![])
+    }
+
+    /// Creates a pair where the assets of `rates` are priced by mock rate providers reporting the given rates
+    pub fn new_with_rate_providers(
+        owner: &Addr,
+        test_coins: Vec<TestCoin>,
+        amp: u64,
+        swap_fee: Option<u16>,
+        rates: Vec<(TestCoin, Decimal)>,
+    ) -> AnyResult<Self> {
+        let mut app = AppBuilder::new()
+            .with_api(MockApiBech32::new("cosmwasm"))
+            .with_wasm(WasmKeeper::default().with_address_generator(MockAddressGenerator))
+            .build(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, owner, init_native_coins(&test_coins))
+                    .unwrap()
+            });
 
         let mut asset_infos_vec: Vec<_> = test_coins
             .clone()
@@ -149,7 +184,7 @@ impl Helper {
         let coin_registry_address = app
             .instantiate_contract(
                 coin_registry_id,
-                Addr::unchecked(owner.to_string()),
+                owner.clone(),
                 &palomadex::native_coin_registry::InstantiateMsg {
                     owner: owner.to_string(),
                 },
@@ -160,7 +195,7 @@ impl Helper {
             .unwrap();
 
         app.execute_contract(
-            Addr::unchecked(owner.to_string()),
+            owner.clone(),
             coin_registry_address.clone(),
             &palomadex::native_coin_registry::ExecuteMsg::Add {
                 native_coins: vec![
@@ -195,6 +230,8 @@ impl Helper {
             owner: owner.to_string(),
             whitelist_code_id: 234u64,
             coin_registry_address: coin_registry_address.to_string(),
+            timelock_delay: 86_400,
+            canceller: None,
         };
 
         let factory = app.instantiate_contract(
@@ -211,17 +248,52 @@ impl Helper {
             .into_iter()
             .map(|(_, asset_info)| asset_info)
             .collect_vec();
+
+        let rate_provider_code_id = app.store_code(rate_provider_contract());
+        let mut rate_providers = HashMap::new();
+        for (coin, rate) in &rates {
+            let rate_provider = app.instantiate_contract(
+                rate_provider_code_id,
+                owner.clone(),
+                &rate_provider::InstantiateMsg { rate: *rate },
+                &[],
+                "Rate provider",
+                None,
+            )?;
+            rate_providers.insert(coin.clone(), rate_provider);
+        }
+        let rate_provider_params = asset_infos_vec
+            .iter()
+            .filter_map(|(coin, asset_info)| {
+                Some(RateProviderParams {
+                    asset_info: asset_info.clone(),
+                    contract_addr: rate_providers.get(coin)?.to_string(),
+                    max_staleness: RATE_MAX_STALENESS,
+                })
+            })
+            .collect_vec();
+
         let init_pair_msg = palomadex::factory::ExecuteMsg::CreatePair {
             pair_type: PairType::Stable {},
             asset_infos: asset_infos.clone(),
-            init_params: Some(to_json_binary(&StablePoolParams { amp, owner: None }).unwrap()),
+            init_params: Some(
+                to_json_binary(&StablePoolParams {
+                    amp,
+                    owner: None,
+                    rate_providers: Some(rate_provider_params),
+                })
+                .unwrap(),
+            ),
         };
 
         app.execute_contract(owner.clone(), factory.clone(), &init_pair_msg, &[])?;
 
         let resp: PairInfo = app.wrap().query_wasm_smart(
             &factory,
-            &palomadex::factory::QueryMsg::Pair { asset_infos },
+            &palomadex::factory::QueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+            },
         )?;
 
         Ok(Self {
@@ -232,9 +304,57 @@ impl Helper {
             pair_addr: resp.contract_addr,
             lp_token: resp.liquidity_token,
             amp,
+            rate_providers,
         })
     }
 
+    /// Sets the exchange rate reported for the coin, the rate is updated at the current block time
+    pub fn set_rate(&mut self, coin: &TestCoin, rate: Decimal) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.owner.clone(),
+            self.rate_providers[coin].clone(),
+            &rate_provider::ExecuteMsg::SetRate { rate },
+            &[],
+        )
+    }
+
+    /// Instantiates a mock rate provider reporting the given rate
+    pub fn instantiate_rate_provider(&mut self, rate: Decimal) -> AnyResult<Addr> {
+        let code_id = self.app.store_code(rate_provider_contract());
+        self.app.instantiate_contract(
+            code_id,
+            self.owner.clone(),
+            &rate_provider::InstantiateMsg { rate },
+            &[],
+            "Rate provider",
+            None,
+        )
+    }
+
+    pub fn update_config(
+        &mut self,
+        sender: &Addr,
+        params: &StablePoolUpdateParams,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_json_binary(params).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    pub fn query_config(&self) -> StdResult<StablePoolConfig> {
+        let resp: ConfigResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::Config {})?;
+
+        from_json(resp.params.unwrap())
+    }
+
     pub fn provide_liquidity(&mut self, sender: &Addr, assets: &[Asset]) -> AnyResult<AppResponse> {
         let funds =
             assets.mock_coins_sent(&mut self.app, sender, &self.pair_addr, SendType::Allowance);
@@ -243,6 +363,8 @@ impl Helper {
             assets: assets.to_vec(),
             slippage_tolerance: None,
             receiver: None,
+            min_lp_to_receive: None,
+            deadline: None,
         };
 
         self.app
@@ -281,6 +403,7 @@ impl Helper {
                         belief_price: None,
                         max_spread: None,
                         to: None,
+                        deadline: None,
                     })
                     .unwrap(),
                 };
@@ -302,6 +425,7 @@ impl Helper {
                     belief_price: None,
                     max_spread: None,
                     to: None,
+                    deadline: None,
                 };
 
                 self.app
@@ -345,13 +469,34 @@ impl Helper {
     }
 
     pub fn query_observe(&self, seconds_ago: u64) -> StdResult<OracleObservation> {
-        self.app
-            .wrap()
-            .query_wasm_smart(&self.pair_addr, &QueryMsg::Observe { seconds_ago })
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::Observe {
+                seconds_ago,
+                base_asset: None,
+                quote_asset: None,
+            },
+        )
+    }
+
+    pub fn query_pair_observe(
+        &self,
+        seconds_ago: u64,
+        base_asset: &AssetInfo,
+        quote_asset: &AssetInfo,
+    ) -> StdResult<OracleObservation> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::Observe {
+                seconds_ago,
+                base_asset: Some(base_asset.clone()),
+                quote_asset: Some(quote_asset.clone()),
+            },
+        )
     }
 
     fn init_token(
-        app: &mut App,
+        app: &mut TestApp,
         token_code: u64,
         name: String,
         decimals: u8,
@@ -423,7 +568,11 @@ impl Helper {
             .wrap()
             .query_wasm_smart::<OracleObservation>(
                 &self.pair_addr,
-                &QueryMsg::Observe { seconds_ago },
+                &QueryMsg::Observe {
+                    seconds_ago,
+                    base_asset: None,
+                    quote_asset: None,
+                },
             )
             .map(|val| val.price)
     }
@@ -439,7 +588,7 @@ pub enum SendType {
 pub trait AssetExt {
     fn mock_coin_sent(
         &self,
-        app: &mut App,
+        app: &mut TestApp,
         user: &Addr,
         spender: &Addr,
         typ: SendType,
@@ -449,7 +598,7 @@ pub trait AssetExt {
 impl AssetExt for Asset {
     fn mock_coin_sent(
         &self,
-        app: &mut App,
+        app: &mut TestApp,
         user: &Addr,
         spender: &Addr,
         typ: SendType,
@@ -485,7 +634,7 @@ impl AssetExt for Asset {
 pub trait AssetsExt {
     fn mock_coins_sent(
         &self,
-        app: &mut App,
+        app: &mut TestApp,
         user: &Addr,
         spender: &Addr,
         typ: SendType,
@@ -495,7 +644,7 @@ pub trait AssetsExt {
 impl AssetsExt for &[Asset] {
     fn mock_coins_sent(
         &self,
-        app: &mut App,
+        app: &mut TestApp,
         user: &Addr,
         spender: &Addr,
         typ: SendType,
@@ -512,7 +661,7 @@ pub trait AppExtension {
     fn next_block(&mut self, time: u64);
 }
 
-impl AppExtension for App {
+impl AppExtension for TestApp {
     fn next_block(&mut self, time: u64) {
         self.update_block(|block| {
             block.time = block.time.plus_seconds(time);
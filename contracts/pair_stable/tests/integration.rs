@@ -2,15 +2,18 @@
 
 use std::str::FromStr;
 
+use anyhow::Result as AnyResult;
 use cosmwasm_std::{
-    attr, from_json, to_json_binary, Addr, Coin, Decimal, QueryRequest, Uint128, WasmQuery,
+    attr, from_json, to_json_binary, Addr, Binary, Coin, Decimal, QueryRequest, Uint128, WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 
 use palomadex::asset::{Asset, AssetInfo, PairInfo};
+use palomadex::common::Role;
 use palomadex::factory::{
-    ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
-    QueryMsg as FactoryQueryMsg,
+    ConfigResponse as FactoryConfigResponse, ExecuteMsg as FactoryExecuteMsg,
+    InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType, QueryMsg as FactoryQueryMsg,
+    TimelockedChange,
 };
 use palomadex::observation::OracleObservation;
 use palomadex::pair::{
@@ -19,20 +22,62 @@ use palomadex::pair::{
     MAX_FEE_SHARE_BPS, TWAP_PRECISION,
 };
 use palomadex::token::InstantiateMsg as TokenInstantiateMsg;
-use palomadex_mocks::cw_multi_test::{App, ContractWrapper, Executor};
+use palomadex_mocks::cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, Executor, IntoAddr,
+    MockAddressGenerator, MockApiBech32, WasmKeeper,
+};
 use palomadex_pair_stable::error::ContractError;
 use palomadex_pair_stable::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
 
 const OWNER: &str = "owner";
 
-fn mock_app(owner: Addr, coins: Vec<Coin>) -> App {
-    App::new(|router, _, storage| {
-        // initialization moved to App construction
-        router.bank.init_balance(storage, &owner, coins).unwrap()
-    })
+type TestApp = App<BankKeeper, MockApiBech32>;
+
+/// Forwards pair `params` through the factory timelock queue. The change is executed once
+/// the timelock delay has passed.
+fn forward_pair_update(
+    router: &mut TestApp,
+    owner: &Addr,
+    factory: &Addr,
+    pair: &Addr,
+    params: Binary,
+) -> AnyResult<AppResponse> {
+    let config: FactoryConfigResponse = router
+        .wrap()
+        .query_wasm_smart(factory, &FactoryQueryMsg::Config {})?;
+    let msg = FactoryExecuteMsg::ProposeChange {
+        change: TimelockedChange::UpdatePair {
+            pair_addr: pair.to_string(),
+            params,
+        },
+        eta: router.block_info().time.seconds() + config.timelock_delay,
+    };
+    let res = router.execute_contract(owner.clone(), factory.clone(), &msg, &[])?;
+    let id = res
+        .custom_attrs(1)
+        .iter()
+        .find(|attr| attr.key == "id")
+        .unwrap()
+        .value
+        .parse()
+        .unwrap();
+
+    router.update_block(|block| block.time = block.time.plus_seconds(config.timelock_delay));
+    let msg = FactoryExecuteMsg::ExecuteChange { id };
+    router.execute_contract(owner.clone(), factory.clone(), &msg, &[])
+}
+
+fn mock_app(owner: Addr, coins: Vec<Coin>) -> TestApp {
+    AppBuilder::new()
+        .with_api(MockApiBech32::new("cosmwasm"))
+        .with_wasm(WasmKeeper::default().with_address_generator(MockAddressGenerator))
+        .build(|router, _, storage| {
+            // initialization moved to App construction
+            router.bank.init_balance(storage, &owner, coins).unwrap()
+        })
 }
 
-fn store_token_code(app: &mut App) -> u64 {
+fn store_token_code(app: &mut TestApp) -> u64 {
     let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
         cw20_base::contract::execute,
         cw20_base::contract::instantiate,
@@ -42,7 +87,7 @@ fn store_token_code(app: &mut App) -> u64 {
     app.store_code(astro_token_contract)
 }
 
-fn store_pair_code(app: &mut App) -> u64 {
+fn store_pair_code(app: &mut TestApp) -> u64 {
     let pair_contract = Box::new(
         ContractWrapper::new_with_empty(
             palomadex_pair_stable::contract::execute,
@@ -55,7 +100,7 @@ fn store_pair_code(app: &mut App) -> u64 {
     app.store_code(pair_contract)
 }
 
-fn store_factory_code(app: &mut App) -> u64 {
+fn store_factory_code(app: &mut TestApp) -> u64 {
     let factory_contract = Box::new(
         ContractWrapper::new_with_empty(
             palomadex_factory::contract::execute,
@@ -68,7 +113,7 @@ fn store_factory_code(app: &mut App) -> u64 {
     app.store_code(factory_contract)
 }
 
-fn store_coin_registry_code(app: &mut App) -> u64 {
+fn store_coin_registry_code(app: &mut TestApp) -> u64 {
     let coin_registry_contract = Box::new(ContractWrapper::new_with_empty(
         palomadex_native_coin_registry::contract::execute,
         palomadex_native_coin_registry::contract::instantiate,
@@ -78,14 +123,14 @@ fn store_coin_registry_code(app: &mut App) -> u64 {
     app.store_code(coin_registry_contract)
 }
 
-fn instantiate_coin_registry(mut app: &mut App, coins: Option<Vec<(String, u8)>>) -> Addr {
+fn instantiate_coin_registry(mut app: &mut TestApp, coins: Option<Vec<(String, u8)>>) -> Addr {
     let coin_registry_id = store_coin_registry_code(&mut app);
     let coin_registry_address = app
         .instantiate_contract(
             coin_registry_id,
-            Addr::unchecked(OWNER),
+            OWNER.into_addr(),
             &palomadex::native_coin_registry::InstantiateMsg {
-                owner: OWNER.to_string(),
+                owner: OWNER.into_addr().to_string(),
             },
             &[],
             "Coin registry",
@@ -95,7 +140,7 @@ fn instantiate_coin_registry(mut app: &mut App, coins: Option<Vec<(String, u8)>>
 
     if let Some(coins) = coins {
         app.execute_contract(
-            Addr::unchecked(OWNER),
+            OWNER.into_addr(),
             coin_registry_address.clone(),
             &palomadex::native_coin_registry::ExecuteMsg::Add {
                 native_coins: coins,
@@ -108,7 +153,7 @@ fn instantiate_coin_registry(mut app: &mut App, coins: Option<Vec<(String, u8)>>
     coin_registry_address
 }
 
-fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
+fn instantiate_pair(mut router: &mut TestApp, owner: &Addr) -> Addr {
     let coin_registry_address = instantiate_coin_registry(
         &mut router,
         Some(vec![("uusd".to_string(), 6), ("uluna".to_string(), 6)]),
@@ -134,6 +179,8 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: coin_registry_address.to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_addr = router
@@ -191,6 +238,7 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -211,16 +259,21 @@ fn instantiate_pair(mut router: &mut App, owner: &Addr) -> Addr {
         .wrap()
         .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
         .unwrap();
-    assert_eq!("contract2", res.contract_addr);
-    assert_eq!("contract3", res.liquidity_token);
+    assert_eq!(pair, res.contract_addr);
+
+    let minter: MinterResponse = router
+        .wrap()
+        .query_wasm_smart(&res.liquidity_token, &Cw20QueryMsg::Minter {})
+        .unwrap();
+    assert_eq!(pair, minter.minter);
 
     pair
 }
 
 #[test]
 fn test_provide_and_withdraw_liquidity() {
-    let owner = Addr::unchecked("owner");
-    let alice_address = Addr::unchecked("alice");
+    let owner = "owner".into_addr();
+    let alice_address = "alice".into_addr();
 
     let mut router = mock_app(
         owner.clone(),
@@ -305,7 +358,10 @@ fn test_provide_and_withdraw_liquidity() {
         res.events[1].attributes[1],
         attr("action", "provide_liquidity")
     );
-    assert_eq!(res.events[1].attributes[3], attr("receiver", "alice"),);
+    assert_eq!(
+        res.events[1].attributes[3],
+        attr("receiver", alice_address.as_str()),
+    );
     assert_eq!(
         res.events[1].attributes[4],
         attr("assets", "100000uusd, 100000uluna")
@@ -316,14 +372,20 @@ fn test_provide_and_withdraw_liquidity() {
     );
 
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
-    assert_eq!(res.events[3].attributes[2], attr("to", "contract2"));
+    assert_eq!(
+        res.events[3].attributes[2],
+        attr("to", pair_instance.as_str())
+    );
     assert_eq!(
         res.events[3].attributes[3],
         attr("amount", 1000.to_string())
     );
 
     assert_eq!(res.events[5].attributes[1], attr("action", "mint"));
-    assert_eq!(res.events[5].attributes[2], attr("to", "alice"));
+    assert_eq!(
+        res.events[5].attributes[2],
+        attr("to", alice_address.as_str())
+    );
     assert_eq!(
         res.events[5].attributes[3],
         attr("amount", 199000u128.to_string())
@@ -333,7 +395,7 @@ fn test_provide_and_withdraw_liquidity() {
     let (msg, coins) = provide_liquidity_msg(
         Uint128::new(100000),
         Uint128::new(100000),
-        Some("bob".to_string()),
+        Some("bob".into_addr().to_string()),
     );
     let res = router
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
@@ -343,7 +405,10 @@ fn test_provide_and_withdraw_liquidity() {
         res.events[1].attributes[1],
         attr("action", "provide_liquidity")
     );
-    assert_eq!(res.events[1].attributes[3], attr("receiver", "bob"),);
+    assert_eq!(
+        res.events[1].attributes[3],
+        attr("receiver", "bob".into_addr().as_str()),
+    );
     assert_eq!(
         res.events[1].attributes[4],
         attr("assets", "100000uusd, 100000uluna")
@@ -353,7 +418,10 @@ fn test_provide_and_withdraw_liquidity() {
         attr("share", 200000u128.to_string())
     );
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
-    assert_eq!(res.events[3].attributes[2], attr("to", "bob"));
+    assert_eq!(
+        res.events[3].attributes[2],
+        attr("to", "bob".into_addr().as_str())
+    );
     assert_eq!(
         res.events[3].attributes[3],
         attr("amount", 200000.to_string())
@@ -382,6 +450,8 @@ fn provide_liquidity_msg(
         ],
         slippage_tolerance: None,
         receiver,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let coins = [
@@ -400,7 +470,7 @@ fn provide_liquidity_msg(
 
 #[test]
 fn provide_lp_for_single_token() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut app = mock_app(
         owner.clone(),
         vec![
@@ -429,11 +499,11 @@ fn provide_lp_for_single_token() {
         symbol: token_name.to_string(),
         decimals: 6,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: x_amount,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -457,11 +527,11 @@ fn provide_lp_for_single_token() {
         symbol: token_name.to_string(),
         decimals: 6,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: y_amount,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -493,10 +563,12 @@ fn provide_lp_for_single_token() {
             permissioned: false,
         }],
         token_code_id,
-        generator_address: Some(String::from("generator")),
-        owner: String::from("owner0000"),
+        generator_address: Some("generator".into_addr().to_string()),
+        owner: "owner0000".into_addr().to_string(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: "coin_registry".into_addr().to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = app
@@ -524,6 +596,7 @@ fn provide_lp_for_single_token() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -541,6 +614,7 @@ fn provide_lp_for_single_token() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
     };
 
     let res: PairInfo = app
@@ -575,6 +649,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -605,6 +680,8 @@ fn provide_lp_for_single_token() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let err = app
@@ -632,6 +709,8 @@ fn provide_lp_for_single_token() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -655,6 +734,8 @@ fn provide_lp_for_single_token() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -686,6 +767,8 @@ fn provide_lp_for_single_token() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -699,6 +782,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -715,6 +799,7 @@ fn provide_lp_for_single_token() {
             belief_price: None,
             max_spread: None,
             to: None,
+            deadline: None,
         })
         .unwrap(),
         amount: swap_amount,
@@ -731,7 +816,7 @@ fn provide_lp_for_single_token() {
 
 #[test]
 fn test_compatibility_of_tokens_with_different_precision() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut app = mock_app(
         owner.clone(),
         vec![
@@ -760,11 +845,11 @@ fn test_compatibility_of_tokens_with_different_precision() {
         symbol: token_name.to_string(),
         decimals: 5,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: x_amount + x_offer,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -788,11 +873,11 @@ fn test_compatibility_of_tokens_with_different_precision() {
         symbol: token_name.to_string(),
         decimals: 7,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: y_amount,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -824,10 +909,12 @@ fn test_compatibility_of_tokens_with_different_precision() {
             permissioned: false,
         }],
         token_code_id,
-        generator_address: Some(String::from("generator")),
-        owner: String::from("owner0000"),
+        generator_address: Some("generator".into_addr().to_string()),
+        owner: "owner0000".into_addr().to_string(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: "coin_registry".into_addr().to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = app
@@ -855,6 +942,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -872,6 +960,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
     };
 
     let res: PairInfo = app
@@ -916,6 +1005,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -927,7 +1018,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         .unwrap();
     assert_eq!(d, 20000000000000);
 
-    let user = Addr::unchecked("user");
+    let user = "user".into_addr();
 
     let msg = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
@@ -936,6 +1027,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -964,7 +1056,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
 
 #[test]
 fn test_if_twap_is_calculated_correctly_when_pool_idles() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut app = mock_app(
         owner.clone(),
         vec![
@@ -979,7 +1071,7 @@ fn test_if_twap_is_calculated_correctly_when_pool_idles() {
         ],
     );
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
 
     // Set User1's balances
     app.send_tokens(
@@ -1055,7 +1147,7 @@ fn test_if_twap_is_calculated_correctly_when_pool_idles() {
 
 #[test]
 fn create_pair_with_same_assets() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut router = mock_app(
         owner.clone(),
         vec![
@@ -1083,7 +1175,7 @@ fn create_pair_with_same_assets() {
             },
         ],
         token_code_id: token_contract_code_id,
-        factory_addr: String::from("factory"),
+        factory_addr: "factory".into_addr().to_string(),
         init_params: None,
     };
 
@@ -1106,7 +1198,7 @@ fn create_pair_with_same_assets() {
 
 #[test]
 fn update_pair_config() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut router = mock_app(
         owner.clone(),
         vec![
@@ -1135,10 +1227,12 @@ fn update_pair_config() {
         fee_address: None,
         pair_configs: vec![],
         token_code_id: token_contract_code_id,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some("generator".into_addr().to_string()),
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: coin_registry_address.to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = router
@@ -1167,6 +1261,7 @@ fn update_pair_config() {
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -1192,18 +1287,36 @@ fn update_pair_config() {
 
     assert_eq!(params.amp, Decimal::from_ratio(100u32, 1u32));
 
-    // Start changing amp with incorrect next amp
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
-            next_amp: MAX_AMP + 1,
-            next_amp_time: router.block_info().time.seconds(),
-        })
-        .unwrap(),
+    // Amp managers change the amplification without going through the timelock queue
+    let amp_manager = "amp_manager".into_addr();
+    router
+        .execute_contract(
+            owner.clone(),
+            factory_instance.clone(),
+            &FactoryExecuteMsg::GrantRole {
+                role: Role::AmpManager,
+                account: amp_manager.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    let update_amp = |router: &mut TestApp, params: Binary| {
+        router.execute_contract(
+            amp_manager.clone(),
+            pair.clone(),
+            &ExecuteMsg::UpdateConfig { params },
+            &[],
+        )
     };
 
-    let resp = router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap_err();
+    // Start changing amp with incorrect next amp
+    let params = to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+        next_amp: MAX_AMP + 1,
+        next_amp_time: router.block_info().time.seconds(),
+    })
+    .unwrap();
+
+    let resp = update_amp(&mut router, params).unwrap_err();
 
     assert_eq!(
         resp.root_cause().to_string(),
@@ -1214,17 +1327,13 @@ fn update_pair_config() {
     );
 
     // Start changing amp with big difference between the old and new amp value
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
-            next_amp: 100 * MAX_AMP_CHANGE + 1,
-            next_amp_time: router.block_info().time.seconds(),
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+        next_amp: 100 * MAX_AMP_CHANGE + 1,
+        next_amp_time: router.block_info().time.seconds(),
+    })
+    .unwrap();
 
-    let resp = router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap_err();
+    let resp = update_amp(&mut router, params).unwrap_err();
 
     assert_eq!(
         resp.root_cause().to_string(),
@@ -1235,17 +1344,13 @@ fn update_pair_config() {
     );
 
     // Start changing amp before the MIN_AMP_CHANGING_TIME has elapsed
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
-            next_amp: 250,
-            next_amp_time: router.block_info().time.seconds(),
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+        next_amp: 250,
+        next_amp_time: router.block_info().time.seconds(),
+    })
+    .unwrap();
 
-    let resp = router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap_err();
+    let resp = update_amp(&mut router, params).unwrap_err();
 
     assert_eq!(
         resp.root_cause().to_string(),
@@ -1260,17 +1365,13 @@ fn update_pair_config() {
         b.time = b.time.plus_seconds(MIN_AMP_CHANGING_TIME);
     });
 
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
-            next_amp: 250,
-            next_amp_time: router.block_info().time.seconds() + MIN_AMP_CHANGING_TIME,
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+        next_amp: 250,
+        next_amp_time: router.block_info().time.seconds() + MIN_AMP_CHANGING_TIME,
+    })
+    .unwrap();
 
-    router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap();
+    update_amp(&mut router, params).unwrap();
 
     router.update_block(|b| {
         b.time = b.time.plus_seconds(MIN_AMP_CHANGING_TIME / 2);
@@ -1303,17 +1404,13 @@ fn update_pair_config() {
         b.time = b.time.plus_seconds(MIN_AMP_CHANGING_TIME);
     });
 
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
-            next_amp: 50,
-            next_amp_time: router.block_info().time.seconds() + MIN_AMP_CHANGING_TIME,
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+        next_amp: 50,
+        next_amp_time: router.block_info().time.seconds() + MIN_AMP_CHANGING_TIME,
+    })
+    .unwrap();
 
-    router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap();
+    update_amp(&mut router, params).unwrap();
 
     router.update_block(|b| {
         b.time = b.time.plus_seconds(MIN_AMP_CHANGING_TIME / 2);
@@ -1329,13 +1426,9 @@ fn update_pair_config() {
     assert_eq!(params.amp, Decimal::from_ratio(150u32, 1u32));
 
     // Stop changing amp
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::StopChangingAmp {}).unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::StopChangingAmp {}).unwrap();
 
-    router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap();
+    update_amp(&mut router, params).unwrap();
 
     router.update_block(|b| {
         b.time = b.time.plus_seconds(MIN_AMP_CHANGING_TIME / 2);
@@ -1353,7 +1446,7 @@ fn update_pair_config() {
 
 #[test]
 fn enable_disable_fee_sharing() {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut router = mock_app(
         owner.clone(),
         vec![
@@ -1380,12 +1473,22 @@ fn enable_disable_fee_sharing() {
 
     let init_msg = FactoryInstantiateMsg {
         fee_address: None,
-        pair_configs: vec![],
+        pair_configs: vec![PairConfig {
+            code_id: pair_contract_code_id,
+            maker_fee_bps: 0,
+            total_fee_bps: 0,
+            pair_type: PairType::Stable {},
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
         token_code_id: token_contract_code_id,
-        generator_address: Some(String::from("generator")),
+        generator_address: Some("generator".into_addr().to_string()),
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: coin_registry_address.to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = router
@@ -1399,37 +1502,46 @@ fn enable_disable_fee_sharing() {
         )
         .unwrap();
 
-    let msg = InstantiateMsg {
-        asset_infos: vec![
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-        ],
-        token_code_id: token_contract_code_id,
-        factory_addr: factory_instance.to_string(),
+    let asset_infos = vec![
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+
+    // Fee sharing is updated through the factory, so the pair has to be registered there
+    let msg = FactoryExecuteMsg::CreatePair {
+        pair_type: PairType::Stable {},
+        asset_infos: asset_infos.clone(),
         init_params: Some(
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: None,
+                rate_providers: None,
             })
             .unwrap(),
         ),
     };
 
-    let pair = router
-        .instantiate_contract(
-            pair_contract_code_id,
-            owner.clone(),
-            &msg,
-            &[],
-            String::from("PAIR"),
-            None,
+    router
+        .execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: PairInfo = router
+        .wrap()
+        .query_wasm_smart(
+            &factory_instance,
+            &FactoryQueryMsg::Pair {
+                asset_infos,
+                pair_type: None,
+            },
         )
         .unwrap();
 
+    let pair = res.contract_addr;
+
     let res: ConfigResponse = router
         .wrap()
         .query_wasm_smart(pair.clone(), &QueryMsg::Config {})
@@ -1441,17 +1553,14 @@ fn enable_disable_fee_sharing() {
     assert_eq!(params.fee_share, None);
 
     // Attemt to set fee sharing higher than maximum
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
-            fee_share_bps: MAX_FEE_SHARE_BPS + 1,
-            fee_share_address: "contract".to_string(),
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
+        fee_share_bps: MAX_FEE_SHARE_BPS + 1,
+        fee_share_address: "contract".into_addr().to_string(),
+    })
+    .unwrap();
 
     assert_eq!(
-        router
-            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+        forward_pair_update(&mut router, &owner, &factory_instance, &pair, params)
             .unwrap_err()
             .downcast_ref::<ContractError>()
             .unwrap(),
@@ -1459,17 +1568,14 @@ fn enable_disable_fee_sharing() {
     );
 
     // Attemt to set fee sharing to 0
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
-            fee_share_bps: 0,
-            fee_share_address: "contract".to_string(),
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
+        fee_share_bps: 0,
+        fee_share_address: "contract".into_addr().to_string(),
+    })
+    .unwrap();
 
     assert_eq!(
-        router
-            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+        forward_pair_update(&mut router, &owner, &factory_instance, &pair, params)
             .unwrap_err()
             .downcast_ref::<ContractError>()
             .unwrap(),
@@ -1477,20 +1583,16 @@ fn enable_disable_fee_sharing() {
     );
 
     let fee_share_bps = 500; // 5%
-    let fee_share_address = "contract".to_string();
+    let fee_share_address = "contract".into_addr().to_string();
 
     // Set valid fee share
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
-            fee_share_bps,
-            fee_share_address: fee_share_address.clone(),
-        })
-        .unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
+        fee_share_bps,
+        fee_share_address: fee_share_address.clone(),
+    })
+    .unwrap();
 
-    router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap();
+    forward_pair_update(&mut router, &owner, &factory_instance, &pair, params).unwrap();
 
     let res: ConfigResponse = router
         .wrap()
@@ -1504,13 +1606,9 @@ fn enable_disable_fee_sharing() {
     assert_eq!(set_fee_share.recipient, fee_share_address);
 
     // Disable fee share
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&StablePoolUpdateParams::DisableFeeShare {}).unwrap(),
-    };
+    let params = to_json_binary(&StablePoolUpdateParams::DisableFeeShare {}).unwrap();
 
-    router
-        .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-        .unwrap();
+    forward_pair_update(&mut router, &owner, &factory_instance, &pair, params).unwrap();
 
     let res: ConfigResponse = router
         .wrap()
@@ -1523,8 +1621,8 @@ fn enable_disable_fee_sharing() {
 
 #[test]
 fn check_observe_queries() {
-    let owner = Addr::unchecked("owner");
-    let user1 = Addr::unchecked("user1");
+    let owner = "owner".into_addr();
+    let user1 = "user1".into_addr();
 
     let mut app = mock_app(
         owner.clone(),
@@ -1581,6 +1679,7 @@ fn check_observe_queries() {
         belief_price: None,
         max_spread: None,
         to: None,
+        deadline: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1598,7 +1697,11 @@ fn check_observe_queries() {
         .wrap()
         .query_wasm_smart(
             pair_instance.to_string(),
-            &QueryMsg::Observe { seconds_ago: 0 },
+            &QueryMsg::Observe {
+                seconds_ago: 0,
+                base_asset: None,
+                quote_asset: None,
+            },
         )
         .unwrap();
 
@@ -1612,9 +1715,9 @@ fn check_observe_queries() {
 }
 
 #[test]
-fn test_imbalance_withdraw_is_disabled() {
-    let owner = Addr::unchecked("owner");
-    let alice_address = Addr::unchecked("alice");
+fn test_imbalance_withdraw() {
+    let owner = "owner".into_addr();
+    let alice_address = "alice".into_addr();
 
     let mut router = mock_app(
         owner.clone(),
@@ -1691,40 +1794,100 @@ fn test_imbalance_withdraw_is_disabled() {
     let (msg, coins) = provide_liquidity_msg(
         Uint128::new(100000),
         Uint128::new(100000),
-        Some("bob".to_string()),
+        Some("bob".into_addr().to_string()),
     );
     router
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
         .unwrap();
 
-    // Check that imbalanced withdraw is currently disabled
-    let msg_imbalance = Cw20ExecuteMsg::Send {
+    let assets = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(10_000u16),
+    }];
+    let burn_amount: Uint128 = router
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::SimulateWithdraw {
+                assets: assets.clone(),
+            },
+        )
+        .unwrap();
+    // Withdrawing 5% of the uusd pool costs slightly more than a balanced 2.5% withdrawal
+    assert!(burn_amount > Uint128::new(10_000));
+    assert!(burn_amount < Uint128::new(10_100));
+
+    let withdraw_msg = |amount: Uint128| Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
-        amount: Uint128::from(50u8),
+        amount,
         msg: to_json_binary(&Cw20HookMsg::WithdrawLiquidity {
-            assets: vec![Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(100u8),
-            }],
+            assets: assets.clone(),
         })
         .unwrap(),
     };
 
     let err = router
-        .execute_contract(alice_address.clone(), lp_token.clone(), &msg_imbalance, &[])
+        .execute_contract(
+            alice_address.clone(),
+            lp_token.clone(),
+            &withdraw_msg(burn_amount - Uint128::one()),
+            &[],
+        )
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Imbalanced withdraw is currently disabled"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotEnoughLpTokens {
+            required: burn_amount,
+            provided: burn_amount - Uint128::one(),
+        }
+    );
+
+    let lp_balance = |router: &TestApp| {
+        router
+            .wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &lp_token,
+                &Cw20QueryMsg::Balance {
+                    address: alice_address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    let lp_before = lp_balance(&router);
+    let uusd_before = router
+        .wrap()
+        .query_balance(&alice_address, "uusd")
+        .unwrap()
+        .amount;
+
+    // Extra LP tokens are returned
+    router
+        .execute_contract(
+            alice_address.clone(),
+            lp_token.clone(),
+            &withdraw_msg(Uint128::new(20_000)),
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(lp_balance(&router), lp_before - burn_amount);
+    assert_eq!(
+        router
+            .wrap()
+            .query_balance(&alice_address, "uusd")
+            .unwrap()
+            .amount,
+        uusd_before + Uint128::new(10_000)
     );
 }
 
 #[test]
 fn test_provide_liquidity_without_funds() {
-    let owner = Addr::unchecked("owner");
-    let alice_address = Addr::unchecked("alice");
+    let owner = "owner".into_addr();
+    let alice_address = "alice".into_addr();
     let mut router = mock_app(
         owner.clone(),
         vec![
@@ -1852,7 +2015,7 @@ fn test_fee_share(
     expected_fee_share: Uint128,
     expected_maker_fee: Uint128,
 ) {
-    let owner = Addr::unchecked(OWNER);
+    let owner = OWNER.into_addr();
     let mut app = mock_app(
         owner.clone(),
         vec![
@@ -1880,11 +2043,11 @@ fn test_fee_share(
         symbol: token_name.to_string(),
         decimals: 6,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: x_amount + x_offer,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -1908,11 +2071,11 @@ fn test_fee_share(
         symbol: token_name.to_string(),
         decimals: 6,
         initial_balances: vec![Cw20Coin {
-            address: OWNER.to_string(),
+            address: OWNER.into_addr().to_string(),
             amount: y_amount,
         }],
         mint: Some(MinterResponse {
-            minter: String::from(OWNER),
+            minter: OWNER.into_addr().to_string(),
             cap: None,
         }),
         marketing: None,
@@ -1932,7 +2095,7 @@ fn test_fee_share(
     let pair_code_id = store_pair_code(&mut app);
     let factory_code_id = store_factory_code(&mut app);
 
-    let maker_address = "maker".to_string();
+    let maker_address = "maker".into_addr().to_string();
 
     let init_msg = FactoryInstantiateMsg {
         fee_address: Some(maker_address.clone()),
@@ -1946,10 +2109,12 @@ fn test_fee_share(
             permissioned: false,
         }],
         token_code_id,
-        generator_address: Some(String::from("generator")),
-        owner: String::from("owner0000"),
+        generator_address: Some("generator".into_addr().to_string()),
+        owner: "owner0000".into_addr().to_string(),
         whitelist_code_id: 234u64,
-        coin_registry_address: "coin_registry".to_string(),
+        coin_registry_address: "coin_registry".into_addr().to_string(),
+        timelock_delay: 86_400,
+        canceller: None,
     };
 
     let factory_instance = app
@@ -1977,6 +2142,7 @@ fn test_fee_share(
             to_json_binary(&StablePoolParams {
                 amp: 100,
                 owner: Some(owner.to_string()),
+                rate_providers: None,
             })
             .unwrap(),
         ),
@@ -1994,6 +2160,7 @@ fn test_fee_share(
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: None,
     };
 
     let res: PairInfo = app
@@ -2038,6 +2205,8 @@ fn test_fee_share(
         ],
         slippage_tolerance: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -2050,7 +2219,7 @@ fn test_fee_share(
     assert_eq!(d, 2000000000000);
 
     // Set up 10% fee sharing
-    let fee_share_address = "contract_receiver".to_string();
+    let fee_share_address = "contract_receiver".into_addr().to_string();
 
     let msg = ExecuteMsg::UpdateConfig {
         params: to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
@@ -2063,7 +2232,7 @@ fn test_fee_share(
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
         .unwrap();
 
-    let user = Addr::unchecked("user");
+    let user = "user".into_addr();
 
     let msg = Cw20ExecuteMsg::Send {
         contract: pair_instance.to_string(),
@@ -2072,6 +2241,7 @@ fn test_fee_share(
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
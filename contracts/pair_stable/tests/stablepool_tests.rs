@@ -1,23 +1,32 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{Addr, Decimal, StdError};
+use cosmwasm_std::{to_json_binary, Addr, Decimal, Fraction, StdError, Uint128};
 use itertools::Itertools;
 use std::str::FromStr;
 
 use helper::AppExtension;
-use palomadex::asset::AssetInfoExt;
+use palomadex::asset::{native_asset_info, AssetInfo, AssetInfoExt};
+use palomadex::common::Role;
 use palomadex::cosmwasm_ext::AbsDiff;
+use palomadex::factory::ExecuteMsg as FactoryExecuteMsg;
 use palomadex::observation::OracleObservation;
+use palomadex::pair::{
+    ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg, RateProviderParams,
+    SimulateProvideResponse, StablePoolUpdateParams,
+};
+use palomadex_mocks::cw_multi_test::{Executor, IntoAddr};
+use palomadex_mocks::rate_provider;
 use palomadex_pair_stable::error::ContractError;
+use palomadex_pair_stable::math::MIN_AMP_CHANGING_TIME;
 
-use crate::helper::{f64_to_dec, Helper, TestCoin};
+use crate::helper::{f64_to_dec, Helper, TestCoin, RATE_MAX_STALENESS};
 
 mod helper;
 
 #[ignore]
 #[test]
 fn provide_and_withdraw_no_fee() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![
         TestCoin::native("uluna"),
@@ -27,7 +36,7 @@ fn provide_and_withdraw_no_fee() {
 
     let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, Some(0u16)).unwrap();
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000000u128),
         helper.assets[&test_coins[1]].with_balance(100_000000u128),
@@ -43,7 +52,7 @@ fn provide_and_withdraw_no_fee() {
     assert_eq!(0, helper.coin_balance(&test_coins[2], &user1));
 
     // The user2 with the same assets should receive the same share
-    let user2 = Addr::unchecked("user2");
+    let user2 = "user2".into_addr();
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(100_000000u128),
         helper.assets[&test_coins[1]].with_balance(100_000000u128),
@@ -54,7 +63,7 @@ fn provide_and_withdraw_no_fee() {
     assert_eq!(300_000000, helper.token_balance(&helper.lp_token, &user2));
 
     // The user3 makes imbalanced provide thus he is charged with fees
-    let user3 = Addr::unchecked("user3");
+    let user3 = "user3".into_addr();
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(200_000000u128),
         helper.assets[&test_coins[1]].with_balance(100_000000u128),
@@ -64,7 +73,7 @@ fn provide_and_withdraw_no_fee() {
     assert_eq!(299_629321, helper.token_balance(&helper.lp_token, &user3));
 
     // Providing last asset with explicit zero amount should give nearly the same result
-    let user4 = Addr::unchecked("user4");
+    let user4 = "user4".into_addr();
     let assets = vec![
         helper.assets[&test_coins[0]].with_balance(200_000000u128),
         helper.assets[&test_coins[1]].with_balance(100_000000u128),
@@ -133,7 +142,7 @@ fn provide_and_withdraw_no_fee() {
 
 #[test]
 fn provide_with_different_precision() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![
         TestCoin::cw20precise("FOO", 4),
@@ -143,7 +152,7 @@ fn provide_with_different_precision() {
     let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
 
     for user_name in ["user1", "user2"] {
-        let user = Addr::unchecked(user_name);
+        let user = user_name.into_addr();
 
         let assets = vec![
             helper.assets[&test_coins[0]].with_balance(100_0000u128),
@@ -154,7 +163,7 @@ fn provide_with_different_precision() {
         helper.provide_liquidity(&user, &assets).unwrap();
     }
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
 
     assert_eq!(19999000, helper.token_balance(&helper.lp_token, &user1));
     assert_eq!(0, helper.coin_balance(&test_coins[0], &user1));
@@ -166,7 +175,7 @@ fn provide_with_different_precision() {
     assert_eq!(999950, helper.coin_balance(&test_coins[0], &user1));
     assert_eq!(9999500, helper.coin_balance(&test_coins[1], &user1));
 
-    let user2 = Addr::unchecked("user2");
+    let user2 = "user2".into_addr();
     assert_eq!(20000000, helper.token_balance(&helper.lp_token, &user2));
     assert_eq!(0, helper.coin_balance(&test_coins[0], &user2));
     assert_eq!(0, helper.coin_balance(&test_coins[1], &user2));
@@ -180,7 +189,7 @@ fn provide_with_different_precision() {
 
 #[test]
 fn swap_different_precisions() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![
         TestCoin::cw20precise("FOO", 4),
@@ -195,7 +204,7 @@ fn swap_different_precisions() {
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
 
-    let user = Addr::unchecked("user");
+    let user = "user".into_addr();
     // 100 x FOO tokens
     let offer_asset = helper.assets[&test_coins[0]].with_balance(100_0000u128);
     // Checking direct swap simulation
@@ -228,7 +237,7 @@ fn swap_different_precisions() {
 #[ignore]
 #[test]
 fn check_swaps() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![
         TestCoin::native("uluna"),
@@ -245,7 +254,7 @@ fn check_swaps() {
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
 
-    let user = Addr::unchecked("user");
+    let user = "user".into_addr();
     let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user);
 
@@ -277,7 +286,7 @@ fn check_swaps() {
 
 #[test]
 fn check_wrong_initializations() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let err = Helper::new(&owner, vec![TestCoin::native("uluna")], 100u64, None).unwrap_err();
 
@@ -332,7 +341,7 @@ fn check_wrong_initializations() {
 #[ignore]
 #[test]
 fn check_withdraw_charges_fees() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![
         TestCoin::native("uluna"),
@@ -349,7 +358,7 @@ fn check_withdraw_charges_fees() {
     ];
     helper.provide_liquidity(&owner, &assets).unwrap();
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
     let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
 
     // Usual swap for reference
@@ -365,7 +374,7 @@ fn check_withdraw_charges_fees() {
     assert_eq!(99_950000, usual_swap_amount);
 
     // Trying to swap LUNA -> USDC via provide/withdraw
-    let user2 = Addr::unchecked("user2");
+    let user2 = "user2".into_addr();
     helper.give_me_money(&[offer_asset.clone()], &user2);
 
     // Provide 100 x LUNA
@@ -403,7 +412,7 @@ fn check_withdraw_charges_fees() {
 
 #[test]
 fn check_twap_based_prices() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
 
@@ -437,7 +446,7 @@ fn check_twap_based_prices() {
     helper.app.next_block(1000);
     check_prices(&helper);
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
     let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user1);
 
@@ -476,9 +485,94 @@ fn check_twap_based_prices() {
     check_prices(&helper);
 }
 
+#[test]
+fn check_pool_prices_by_asset_pair() {
+    let owner = "owner".into_addr();
+
+    let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+    let [usd, usdx] = [0, 1].map(|i| helper.assets[&test_coins[i]].clone());
+
+    let assets = vec![
+        usd.with_balance(100_000_000_000_000u128),
+        usdx.with_balance(100_000_000_000_000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    helper.app.next_block(1000);
+
+    let user1 = "user1".into_addr();
+    let offer_asset = usdx.with_balance(1_000_000_000u128);
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &user1);
+    let res = helper
+        .swap(&user1, &offer_asset, Some(usd.clone()))
+        .unwrap();
+    let return_amount = res
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "return_amount")
+        .unwrap()
+        .value
+        .parse::<u128>()
+        .unwrap();
+
+    helper.app.next_block(86400);
+    // The asset which goes first in the pool is the base one by default
+    let observation = helper.query_observe(0).unwrap();
+    assert_eq!(
+        observation,
+        OracleObservation {
+            timestamp: helper.app.block_info().time.seconds(),
+            price: Decimal::from_ratio(return_amount, 1_000_000_000u128),
+        }
+    );
+    assert_eq!(
+        helper.query_pair_observe(0, &usd, &usdx).unwrap(),
+        observation
+    );
+    assert_eq!(
+        helper.query_pair_observe(0, &usdx, &usd).unwrap().price,
+        observation.price.inv().unwrap()
+    );
+
+    let err = helper
+        .app
+        .wrap()
+        .query_wasm_smart::<OracleObservation>(
+            &helper.pair_addr,
+            &PairQueryMsg::Observe {
+                seconds_ago: 0,
+                base_asset: Some(usd.clone()),
+                quote_asset: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            "Querier contract error: Generic error: base_asset and quote_asset must be specified together"
+        )
+    );
+    assert_eq!(
+        helper.query_pair_observe(0, &usd, &usd).unwrap_err(),
+        StdError::generic_err(
+            "Querier contract error: Generic error: Base and quote assets must be different"
+        )
+    );
+    let foreign_asset = native_asset_info("uatom".to_string());
+    assert_eq!(
+        helper
+            .query_pair_observe(0, &usd, &foreign_asset)
+            .unwrap_err(),
+        StdError::generic_err(
+            "Querier contract error: Generic error: The asset uatom does not belong to the pair"
+        )
+    );
+}
+
 #[test]
 fn check_pool_prices() {
-    let owner = Addr::unchecked("owner");
+    let owner = "owner".into_addr();
 
     let test_coins = vec![TestCoin::native("uusd"), TestCoin::cw20("USDX")];
 
@@ -497,7 +591,7 @@ fn check_pool_prices() {
         StdError::generic_err("Querier contract error: Generic error: Buffer is empty")
     );
 
-    let user1 = Addr::unchecked("user1");
+    let user1 = "user1".into_addr();
     let offer_asset = helper.assets[&test_coins[0]].with_balance(1000_000000u128);
     helper.give_me_money(&[offer_asset.clone()], &user1);
 
@@ -589,3 +683,514 @@ fn check_pool_prices() {
     );
     helper.app.next_block(10);
 }
+
+#[test]
+fn check_paused_pair() {
+    let owner = "owner".into_addr();
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let user = "user".into_addr();
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets).unwrap();
+
+    let pause_msg = FactoryExecuteMsg::PausePairs {
+        pairs: Some(vec![helper.pair_addr.to_string()]),
+    };
+    helper
+        .app
+        .execute_contract(owner.clone(), helper.factory.clone(), &pause_msg, &[])
+        .unwrap();
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000000u128);
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &user);
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+
+    helper.give_me_money(&assets, &user);
+    let err = helper.provide_liquidity(&user, &assets).unwrap_err();
+    assert_eq!(ContractError::Paused {}, err.downcast().unwrap());
+
+    // Liquidity can still be withdrawn from a paused pair
+    helper.withdraw_liquidity(&user, 1_000000, vec![]).unwrap();
+
+    let unpause_msg = FactoryExecuteMsg::UnpausePairs {
+        pairs: Some(vec![helper.pair_addr.to_string()]),
+    };
+    helper
+        .app
+        .execute_contract(owner.clone(), helper.factory.clone(), &unpause_msg, &[])
+        .unwrap();
+    helper.swap(&user, &offer_asset, None).unwrap();
+}
+
+#[test]
+fn check_amp_manager_role() {
+    let owner = "owner".into_addr();
+    let amp_manager = "amp_manager".into_addr();
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins, 100u64, None).unwrap();
+    helper.app.next_block(MIN_AMP_CHANGING_TIME);
+
+    let start_changing_amp = PairExecuteMsg::UpdateConfig {
+        params: to_json_binary(&StablePoolUpdateParams::StartChangingAmp {
+            next_amp: 150,
+            next_amp_time: helper.app.block_info().time.seconds() + MIN_AMP_CHANGING_TIME,
+        })
+        .unwrap(),
+    };
+    let err = helper
+        .app
+        .execute_contract(
+            amp_manager.clone(),
+            helper.pair_addr.clone(),
+            &start_changing_amp,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &FactoryExecuteMsg::GrantRole {
+                role: Role::AmpManager,
+                account: amp_manager.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    helper
+        .app
+        .execute_contract(
+            amp_manager.clone(),
+            helper.pair_addr.clone(),
+            &start_changing_amp,
+            &[],
+        )
+        .unwrap();
+    helper
+        .app
+        .execute_contract(
+            amp_manager.clone(),
+            helper.pair_addr.clone(),
+            &PairExecuteMsg::UpdateConfig {
+                params: to_json_binary(&StablePoolUpdateParams::StopChangingAmp {}).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Fee sharing is still up to the owner
+    let err = helper
+        .app
+        .execute_contract(
+            amp_manager.clone(),
+            helper.pair_addr.clone(),
+            &PairExecuteMsg::UpdateConfig {
+                params: to_json_binary(&StablePoolUpdateParams::EnableFeeShare {
+                    fee_share_bps: 1000,
+                    fee_share_address: amp_manager.to_string(),
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+}
+
+#[test]
+fn check_one_sided_provide_charges_fees() {
+    let owner = "owner".into_addr();
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000_000_000_u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000_000_000_u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // A balanced deposit is not charged
+    let sim: SimulateProvideResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &PairQueryMsg::SimulateProvide {
+                assets: vec![
+                    helper.assets[&test_coins[0]].with_balance(100_000000u128),
+                    helper.assets[&test_coins[1]].with_balance(100_000000u128),
+                ],
+            },
+        )
+        .unwrap();
+    assert!(sim.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // Trying to swap LUNA -> USDC via provide/withdraw
+    let user = "user".into_addr();
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &user);
+
+    let sim: SimulateProvideResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &PairQueryMsg::SimulateProvide {
+                assets: vec![offer_asset.clone()],
+            },
+        )
+        .unwrap();
+    assert!(sim.fees.iter().all(|fee| !fee.amount.is_zero()));
+
+    let res = helper
+        .provide_liquidity(
+            &user,
+            &[
+                offer_asset,
+                helper.assets[&test_coins[1]].with_balance(0u128),
+            ],
+        )
+        .unwrap();
+    let lp_amount = helper.token_balance(&helper.lp_token, &user);
+    assert_eq!(sim.share.u128(), lp_amount);
+    assert_eq!(
+        res.custom_attrs(1)
+            .iter()
+            .find(|attr| attr.key == "imbalance_fee")
+            .unwrap()
+            .value,
+        sim.fees.iter().join(", ")
+    );
+
+    helper.withdraw_liquidity(&user, lp_amount, vec![]).unwrap();
+
+    // The imbalance fee (half of the 5 bps swap fee on the 50% that is effectively swapped) stays in the pool
+    let received =
+        helper.coin_balance(&test_coins[0], &user) + helper.coin_balance(&test_coins[1], &user);
+    assert!(received < 100_000000 - 20000, "received {received}");
+}
+
+#[test]
+fn rate_providers_value_assets_at_exchange_rate() {
+    let owner = "owner".into_addr();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("STLUNA")];
+    let mut helper = Helper::new_with_rate_providers(
+        &owner,
+        test_coins.clone(),
+        100u64,
+        None,
+        vec![(test_coins[1].clone(), Decimal::from_ratio(2u8, 1u8))],
+    )
+    .unwrap();
+
+    // The pool is balanced as one STLUNA is worth two uluna
+    let user = "user".into_addr();
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(200_000_000_000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000_000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets).unwrap();
+
+    let d: Uint128 = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &PairQueryMsg::QueryComputeD {})
+        .unwrap();
+    assert_eq!(d.u128(), 400_000_000_000);
+
+    // Swapping one STLUNA returns two uluna minus the 0.05% fee
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000_000u128);
+    let sim_resp = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert!(sim_resp.return_amount.u128().abs_diff(1_999_000) <= 10);
+    assert!(sim_resp.spread_amount.u128() <= 10);
+
+    let trader = "trader".into_addr();
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &trader);
+    helper.swap(&trader, &offer_asset, None).unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[0], &trader),
+        sim_resp.return_amount.u128()
+    );
+
+    // Getting two uluna back requires about one STLUNA
+    let ask_asset = helper.assets[&test_coins[0]].with_balance(2_000_000u128);
+    let reverse_sim_resp = helper.simulate_reverse_swap(&ask_asset, None).unwrap();
+    assert!(reverse_sim_resp.offer_amount.u128().abs_diff(1_000_500) <= 10);
+    assert!(reverse_sim_resp.spread_amount.u128() <= 10);
+
+    // STLUNA accrues yield, so it is worth more uluna
+    helper
+        .set_rate(&test_coins[1], Decimal::from_ratio(22u8, 10u8))
+        .unwrap();
+    let new_sim_resp = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert!(new_sim_resp.return_amount > sim_resp.return_amount);
+    assert!(new_sim_resp.return_amount.u128() < 2_200_000);
+}
+
+#[test]
+fn stale_exchange_rate_blocks_rate_dependent_operations() {
+    let owner = "owner".into_addr();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("STLUNA")];
+    let mut helper = Helper::new_with_rate_providers(
+        &owner,
+        test_coins.clone(),
+        100u64,
+        None,
+        vec![(test_coins[1].clone(), Decimal::from_ratio(2u8, 1u8))],
+    )
+    .unwrap();
+
+    let user = "user".into_addr();
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(200_000_000_000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000_000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets).unwrap();
+
+    helper.app.next_block(RATE_MAX_STALENESS + 1);
+    let block_time = helper.app.block_info().time.seconds();
+    let stale_err = ContractError::StaleExchangeRate {
+        asset: helper.assets[&test_coins[1]].to_string(),
+        updated_at: block_time - RATE_MAX_STALENESS - 1,
+        block_time,
+    };
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000u128);
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &user);
+    let err = helper.swap(&user, &offer_asset, None).unwrap_err();
+    assert_eq!(stale_err, err.downcast().unwrap());
+
+    let err = helper.simulate_swap(&offer_asset, None).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Querier contract error: Generic error: {stale_err}"
+        ))
+    );
+
+    let deposit = [
+        offer_asset.clone(),
+        helper.assets[&test_coins[1]].with_balance(0u128),
+    ];
+    let err = helper.provide_liquidity(&user, &deposit).unwrap_err();
+    assert_eq!(stale_err, err.downcast().unwrap());
+
+    let err = helper
+        .withdraw_liquidity(&user, 1_000_000, vec![offer_asset.clone()])
+        .unwrap_err();
+    assert_eq!(stale_err, err.downcast().unwrap());
+
+    // Balanced withdrawals don't depend on the exchange rates
+    helper.withdraw_liquidity(&user, 1_000_000, vec![]).unwrap();
+
+    // The pool is usable again once the rate is refreshed
+    helper
+        .set_rate(&test_coins[1], Decimal::from_ratio(2u8, 1u8))
+        .unwrap();
+    helper.swap(&user, &offer_asset, None).unwrap();
+}
+
+#[test]
+fn factory_replaces_and_removes_rate_providers() {
+    let owner = "owner".into_addr();
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("STLUNA")];
+    let mut helper = Helper::new_with_rate_providers(
+        &owner,
+        test_coins.clone(),
+        100u64,
+        None,
+        vec![(test_coins[1].clone(), Decimal::from_ratio(2u8, 1u8))],
+    )
+    .unwrap();
+    let stluna = helper.assets[&test_coins[1]].clone();
+
+    let user = "user".into_addr();
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(200_000_000_000u128),
+        stluna.with_balance(100_000_000_000u128),
+    ];
+    helper.give_me_money(&assets, &user);
+    helper.provide_liquidity(&user, &assets).unwrap();
+
+    // The rate provider stops reporting, so swaps are blocked until it is replaced
+    helper.app.next_block(RATE_MAX_STALENESS + 1);
+    let offer_asset = stluna.with_balance(1_000_000u128);
+    helper.give_me_money(std::slice::from_ref(&offer_asset), &user);
+    helper.swap(&user, &offer_asset, None).unwrap_err();
+
+    let new_provider = helper
+        .instantiate_rate_provider(Decimal::from_ratio(22u8, 10u8))
+        .unwrap();
+    let rate_provider_params = |asset_info: &AssetInfo, contract_addr: &Addr| RateProviderParams {
+        asset_info: asset_info.clone(),
+        contract_addr: contract_addr.to_string(),
+        max_staleness: RATE_MAX_STALENESS,
+    };
+    let set_rate_provider = |asset_info: &AssetInfo| StablePoolUpdateParams::SetRateProvider {
+        provider: rate_provider_params(asset_info, &new_provider),
+    };
+
+    // Rate providers are changed through the factory timelock only, amp managers can't change them
+    let amp_manager = "amp_manager".into_addr();
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.factory.clone(),
+            &FactoryExecuteMsg::GrantRole {
+                role: Role::AmpManager,
+                account: amp_manager.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    for sender in [&user, &amp_manager] {
+        let err = helper
+            .update_config(sender, &set_rate_provider(&stluna))
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+        let err = helper
+            .update_config(
+                sender,
+                &StablePoolUpdateParams::RemoveRateProvider {
+                    asset_info: stluna.clone(),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    }
+
+    // Owner updates are forwarded by the factory
+    let factory = helper.factory.clone();
+    let foreign_asset = native_asset_info("uatom".to_string());
+    let err = helper
+        .update_config(&factory, &set_rate_provider(&foreign_asset))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset(foreign_asset.to_string()),
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .update_config(
+            &factory,
+            &StablePoolUpdateParams::SetRateProvider {
+                provider: RateProviderParams {
+                    max_staleness: 0,
+                    ..rate_provider_params(&stluna, &new_provider)
+                },
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMaxStaleness(stluna.to_string()),
+        err.downcast().unwrap()
+    );
+
+    // The new provider must report a usable rate
+    let zero_provider = helper.instantiate_rate_provider(Decimal::zero()).unwrap();
+    let err = helper
+        .update_config(
+            &factory,
+            &StablePoolUpdateParams::SetRateProvider {
+                provider: rate_provider_params(&stluna, &zero_provider),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ZeroExchangeRate(stluna.to_string()),
+        err.downcast().unwrap()
+    );
+
+    let stale_provider = helper
+        .instantiate_rate_provider(Decimal::from_ratio(22u8, 10u8))
+        .unwrap();
+    let block_time = helper.app.block_info().time.seconds();
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            stale_provider.clone(),
+            &rate_provider::ExecuteMsg::SetRateUpdatedAt {
+                rate: Decimal::from_ratio(22u8, 10u8),
+                updated_at: block_time - RATE_MAX_STALENESS - 1,
+            },
+            &[],
+        )
+        .unwrap();
+    let err = helper
+        .update_config(
+            &factory,
+            &StablePoolUpdateParams::SetRateProvider {
+                provider: rate_provider_params(&stluna, &stale_provider),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::StaleExchangeRate {
+            asset: stluna.to_string(),
+            updated_at: block_time - RATE_MAX_STALENESS - 1,
+            block_time,
+        },
+        err.downcast().unwrap()
+    );
+
+    helper
+        .update_config(&factory, &set_rate_provider(&stluna))
+        .unwrap();
+    assert_eq!(
+        helper.query_config().unwrap().rate_providers,
+        vec![RateProviderParams {
+            asset_info: stluna.clone(),
+            contract_addr: new_provider.to_string(),
+            max_staleness: RATE_MAX_STALENESS,
+        }]
+    );
+    let sim_resp = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert!(sim_resp.return_amount.u128() > 2_000_000);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    helper
+        .update_config(
+            &factory,
+            &StablePoolUpdateParams::RemoveRateProvider {
+                asset_info: stluna.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(helper.query_config().unwrap().rate_providers, vec![]);
+    // Without the rate STLUNA is valued one to one, so the pool is now heavy in uluna
+    let sim_resp = helper.simulate_swap(&offer_asset, None).unwrap();
+    assert!(sim_resp.return_amount.u128() < 2_000_000);
+
+    let err = helper
+        .update_config(
+            &factory,
+            &StablePoolUpdateParams::RemoveRateProvider {
+                asset_info: stluna.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::RateProviderNotFound(stluna.to_string()),
+        err.downcast().unwrap()
+    );
+}
@@ -6,7 +6,7 @@ use cw2::set_contract_version;
 use cw20::Cw20ReceiveMsg;
 
 use palomadex::asset::{addr_opt_validate, Asset, AssetInfo};
-use palomadex::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use palomadex::pair::{check_deadline, QueryMsg as PairQueryMsg, SimulationResponse};
 use palomadex::querier::query_pair_info;
 use palomadex::router::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
@@ -50,7 +50,7 @@ pub fn instantiate(
 /// * **ExecuteMsg::ExecuteSwapOperations {
 ///             operations,
 ///             minimum_receive,
-///             to
+///             to, max_spread, deadline
 ///         }** Performs swap operations with the specified parameters.
 ///
 /// * **ExecuteMsg::ExecuteSwapOperation { operation, to }** Execute a single swap operation.
@@ -75,6 +75,7 @@ pub fn execute(
             minimum_receive,
             to,
             max_spread,
+            deadline,
         } => execute_swap_operations(
             deps,
             env,
@@ -83,6 +84,7 @@ pub fn execute(
             minimum_receive,
             to,
             max_spread,
+            deadline,
         ),
         ExecuteMsg::ExecuteSwapOperation {
             operation,
@@ -107,6 +109,7 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            deadline,
         } => execute_swap_operations(
             deps,
             env,
@@ -115,6 +118,7 @@ pub fn receive_cw20(
             minimum_receive,
             to,
             max_spread,
+            deadline,
         ),
     }
 }
@@ -128,6 +132,8 @@ pub fn receive_cw20(
 /// * **minimum_receive** used to guarantee that the ask amount is above a minimum amount.
 ///
 /// * **to** recipient of the ask tokens.
+///
+/// * **deadline** block time (in seconds) after which the swaps are rejected.
 #[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operations(
     deps: DepsMut,
@@ -137,7 +143,9 @@ pub fn execute_swap_operations(
     minimum_receive: Option<Uint128>,
     to: Option<String>,
     max_spread: Option<Decimal>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    check_deadline(&env, deadline)?;
     assert_operations(deps.api, &operations)?;
 
     let to = addr_opt_validate(deps.api, &to)?.unwrap_or(sender);
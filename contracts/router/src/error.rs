@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError, Uint128};
+use palomadex::pair::DeadlineExpired;
 use thiserror::Error;
 
 /// This enum describes oracle contract errors
@@ -13,6 +14,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("{0}")]
+    DeadlineExpired(#[from] DeadlineExpired),
+
     #[error(
         "The next offer asset must be the same as the previous ask asset; \
     {prev_ask_asset} --> {next_offer_asset} --> {next_ask_asset}"
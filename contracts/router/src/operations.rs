@@ -111,6 +111,7 @@ pub fn asset_into_swap_msg(
                 belief_price,
                 max_spread,
                 to,
+                deadline: None,
             })?,
         })),
         AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -124,6 +125,7 @@ pub fn asset_into_swap_msg(
                     belief_price,
                     max_spread,
                     to,
+                    deadline: None,
                 })?,
             })?,
         })),
@@ -15,6 +15,7 @@ use palomadex::pair::SimulationResponse;
 pub enum QueryMsg {
     Pair {
         asset_infos: Vec<AssetInfo>,
+        pair_type: Option<PairType>,
     },
     Simulation {
         offer_asset: Asset,
@@ -133,7 +134,7 @@ impl WasmMockQuerier {
 
     fn handle_default(&self, msg: &Binary) -> QuerierResult {
         match from_json(&msg).unwrap() {
-            QueryMsg::Pair { asset_infos } => {
+            QueryMsg::Pair { asset_infos, .. } => {
                 let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                 match self.palomadex_factory_querier.pairs.get(&key) {
                     Some(v) => SystemResult::Ok(ContractResult::from(to_json_binary(&PairInfo {
@@ -50,6 +50,7 @@ fn execute_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -87,6 +88,7 @@ fn execute_swap_operations() {
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
         max_spread: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -203,6 +205,7 @@ fn execute_swap_operations() {
             minimum_receive: None,
             to: Some(String::from("addr0002")),
             max_spread: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -351,6 +354,7 @@ fn execute_swap_operation() {
                         belief_price: None,
                         max_spread: None,
                         to: Some(String::from("addr0000")),
+                        deadline: None,
                     })
                     .unwrap()
                 })
@@ -455,6 +459,7 @@ fn assert_maximum_receive_swap_operations() {
         minimum_receive: None,
         to: None,
         max_spread: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -3,19 +3,33 @@
 use anyhow::Result as AnyResult;
 use cosmwasm_std::{coins, Addr, Binary};
 use cw20::MinterResponse;
-use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use cw_multi_test::{
+    no_init, App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, Executor,
+    MockAddressGenerator, MockApiBech32, WasmKeeper,
+};
 
 use palomadex::asset::{AssetInfo, PairInfo};
 use palomadex::factory::{PairConfig, PairType, QueryMsg};
 
+/// Pairs are created with instantiate2, which needs an api that can humanize the predicted addresses
+pub type TestApp = App<BankKeeper, MockApiBech32>;
+
+pub fn mock_app() -> TestApp {
+    AppBuilder::new()
+        .with_api(MockApiBech32::new("cosmwasm"))
+        .with_wasm(WasmKeeper::default().with_address_generator(MockAddressGenerator))
+        .build(no_init)
+}
+
 pub struct FactoryHelper {
     // pub owner: Addr,
     pub factory: Addr,
+    pub coin_registry: Addr,
     pub cw20_token_code_id: u64,
 }
 
 impl FactoryHelper {
-    pub fn init(router: &mut App, owner: &Addr) -> Self {
+    pub fn init(router: &mut TestApp, owner: &Addr) -> Self {
         let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
             cw20_base::contract::execute,
             cw20_base::contract::instantiate,
@@ -46,6 +60,27 @@ impl FactoryHelper {
 
         let factory_code_id = router.store_code(factory_contract);
 
+        let coin_registry_contract = Box::new(ContractWrapper::new_with_empty(
+            palomadex_native_coin_registry::contract::execute,
+            palomadex_native_coin_registry::contract::instantiate,
+            palomadex_native_coin_registry::contract::query,
+        ));
+
+        let coin_registry_code_id = router.store_code(coin_registry_contract);
+
+        let coin_registry = router
+            .instantiate_contract(
+                coin_registry_code_id,
+                owner.clone(),
+                &palomadex::native_coin_registry::InstantiateMsg {
+                    owner: owner.to_string(),
+                },
+                &[],
+                String::from("Coin registry"),
+                None,
+            )
+            .unwrap();
+
         let msg = palomadex::factory::InstantiateMsg {
             pair_configs: vec![
                 PairConfig {
@@ -72,7 +107,9 @@ impl FactoryHelper {
             generator_address: None,
             owner: owner.to_string(),
             whitelist_code_id: 0,
-            coin_registry_address: "coin_registry".to_string(),
+            coin_registry_address: coin_registry.to_string(),
+            timelock_delay: 86_400,
+            canceller: None,
         };
 
         let factory = router
@@ -89,13 +126,34 @@ impl FactoryHelper {
         Self {
             // owner: owner.clone(),
             factory,
+            coin_registry,
             cw20_token_code_id,
         }
     }
 
+    /// Registers the precisions of native coins, which pairs need to observe swaps of them.
+    pub fn add_native_coins(
+        &self,
+        router: &mut TestApp,
+        owner: &Addr,
+        native_coins: &[(&str, u8)],
+    ) -> AnyResult<AppResponse> {
+        router.execute_contract(
+            owner.clone(),
+            self.coin_registry.clone(),
+            &palomadex::native_coin_registry::ExecuteMsg::Add {
+                native_coins: native_coins
+                    .iter()
+                    .map(|(denom, precision)| (denom.to_string(), *precision))
+                    .collect(),
+            },
+            &[],
+        )
+    }
+
     pub fn create_pair(
         &mut self,
-        router: &mut App,
+        router: &mut TestApp,
         sender: &Addr,
         pair_type: PairType,
         asset_infos: [AssetInfo; 2],
@@ -113,6 +171,7 @@ impl FactoryHelper {
             self.factory.clone(),
             &QueryMsg::Pair {
                 asset_infos: asset_infos.to_vec(),
+                pair_type: None,
             },
         )?;
 
@@ -121,7 +180,7 @@ impl FactoryHelper {
 }
 
 pub fn instantiate_token(
-    app: &mut App,
+    app: &mut TestApp,
     token_code_id: u64,
     owner: &Addr,
     token_name: &str,
@@ -151,7 +210,7 @@ pub fn instantiate_token(
 }
 
 pub fn mint(
-    app: &mut App,
+    app: &mut TestApp,
     owner: &Addr,
     token: &Addr,
     amount: u128,
@@ -169,13 +228,13 @@ pub fn mint(
 }
 
 pub fn mint_native(
-    app: &mut App,
+    app: &mut TestApp,
     denom: &str,
     amount: u128,
     receiver: &Addr,
 ) -> AnyResult<AppResponse> {
     // .init_balance() erases previous balance thus we use such hack and create intermediate "denom admin"
-    let denom_admin = Addr::unchecked(format!("{denom}_admin"));
+    let denom_admin = app.api().addr_make(&format!("{denom}_admin"));
     let coins_vec = coins(amount, denom);
     app.init_modules(|router, _, storage| {
         router
@@ -1,15 +1,16 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{coins, from_json, to_json_binary, Addr, Empty, StdError};
+use cosmwasm_std::{coins, from_json, to_json_binary, Empty, StdError};
 use cw20::Cw20ExecuteMsg;
-use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_multi_test::{Contract, ContractWrapper, Executor};
 
 use palomadex::asset::{native_asset_info, token_asset_info};
 use palomadex::factory::PairType;
+use palomadex::pair::DeadlineExpired;
 use palomadex::router::{ExecuteMsg, InstantiateMsg, SwapOperation, SwapResponseData};
 use palomadex_router::error::ContractError;
 
-use crate::factory_helper::{instantiate_token, mint, mint_native, FactoryHelper};
+use crate::factory_helper::{instantiate_token, mint, mint_native, mock_app, FactoryHelper};
 
 mod factory_helper;
 
@@ -26,9 +27,9 @@ fn router_contract() -> Box<dyn Contract<Empty>> {
 
 #[test]
 fn router_does_not_enforce_spread_assertion() {
-    let mut app = App::default();
+    let mut app = mock_app();
 
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
 
     let token_x = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "TOX", None);
@@ -89,6 +90,7 @@ fn router_does_not_enforce_spread_assertion() {
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -119,6 +121,7 @@ fn router_does_not_enforce_spread_assertion() {
                     minimum_receive: None,
                     to: None,
                     max_spread: None,
+                    deadline: None,
                 })
                 .unwrap(),
             },
@@ -133,14 +136,21 @@ fn router_does_not_enforce_spread_assertion() {
 
 #[test]
 fn route_through_pairs_with_natives() {
-    let mut app = App::default();
+    let mut app = mock_app();
 
-    let owner = Addr::unchecked("owner");
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
 
     let denom_x = "denom_x";
     let denom_y = "denom_y";
     let denom_z = "denom_z";
+    helper
+        .add_native_coins(
+            &mut app,
+            &owner,
+            &[(denom_x, 6), (denom_y, 6), (denom_z, 6)],
+        )
+        .unwrap();
 
     for (a, b, typ, liq) in [
         (&denom_x, &denom_y, PairType::Xyk {}, 100_000_000000),
@@ -210,6 +220,7 @@ fn route_through_pairs_with_natives() {
                 to: None,
                 max_spread: None,
                 minimum_receive: None,
+                deadline: None,
             },
             &[],
         )
@@ -231,6 +242,7 @@ fn route_through_pairs_with_natives() {
                 to: None,
                 max_spread: None,
                 minimum_receive: None,
+                deadline: None,
             },
             &[],
         )
@@ -264,6 +276,7 @@ fn route_through_pairs_with_natives() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
             },
             &coins(50_000_000000, denom_x),
         )
@@ -292,6 +305,7 @@ fn route_through_pairs_with_natives() {
                 minimum_receive: Some(50_000_000000u128.into()), // <--- enforcing minimum receive with 1:1 rate (which practically impossible)
                 to: None,
                 max_spread: None,
+                deadline: None,
             },
             &coins(50_000_000000, denom_x),
         )
@@ -306,18 +320,116 @@ fn route_through_pairs_with_natives() {
     );
 }
 
+#[test]
+fn swap_operations_respect_deadline() {
+    let mut app = mock_app();
+
+    let owner = app.api().addr_make("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let denom_x = "denom_x";
+    let denom_y = "denom_y";
+    helper
+        .add_native_coins(&mut app, &owner, &[(denom_x, 6), (denom_y, 6)])
+        .unwrap();
+
+    let pair = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [
+                native_asset_info(denom_x.to_string()),
+                native_asset_info(denom_y.to_string()),
+            ],
+            None,
+        )
+        .unwrap();
+    mint_native(&mut app, denom_x, 100_000_000_000, &pair).unwrap();
+    mint_native(&mut app, denom_y, 100_000_000_000, &pair).unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                palomadex_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let now = app.block_info().time.seconds();
+    let swap_msg = |deadline| ExecuteMsg::ExecuteSwapOperations {
+        operations: vec![SwapOperation::AstroSwap {
+            offer_asset_info: native_asset_info(denom_x.to_string()),
+            ask_asset_info: native_asset_info(denom_y.to_string()),
+        }],
+        minimum_receive: None,
+        to: None,
+        max_spread: None,
+        deadline: Some(deadline),
+    };
+
+    mint_native(&mut app, denom_x, 2_000000, &owner).unwrap();
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router.clone(),
+            &swap_msg(now - 1),
+            &coins(1_000000, denom_x),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DeadlineExpired(DeadlineExpired {
+            deadline: now - 1,
+            block_time: now
+        })
+    );
+
+    // The deadline is inclusive
+    app.execute_contract(
+        owner.clone(),
+        router.clone(),
+        &swap_msg(now),
+        &coins(1_000000, denom_x),
+    )
+    .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(10));
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            router,
+            &swap_msg(now + 5),
+            &coins(1_000000, denom_x),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::DeadlineExpired(DeadlineExpired {
+            deadline: now + 5,
+            block_time: now + 10
+        })
+    );
+}
+
 #[test]
 fn test_swap_route() {
     use crate::factory_helper::{instantiate_token, mint, FactoryHelper};
-    use cosmwasm_std::{to_json_binary, Addr, Uint128};
+    use cosmwasm_std::{to_json_binary, Uint128};
     use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
     use palomadex::asset::AssetInfo;
     use palomadex::factory::PairType;
     use palomadex::router::{
         ExecuteMsg, InstantiateMsg, QueryMsg, SimulateSwapOperationsResponse, SwapOperation,
     };
-    let mut app = App::default();
-    let owner = Addr::unchecked("owner");
+    let mut app = mock_app();
+    let owner = app.api().addr_make("owner");
     let mut helper = FactoryHelper::init(&mut app, &owner);
     let astro = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "astro", None);
     let inj = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "inj", None);
@@ -390,10 +502,10 @@ fn test_swap_route() {
         .unwrap_err();
     assert_eq!(
         err,
-        StdError::generic_err(
+        StdError::generic_err(format!(
             "Querier contract error: The next offer asset must be \
-    the same as the previous ask asset; contract2 --> contract3 --> contract4"
-        )
+    the same as the previous ask asset; {inj} --> {atom} --> {osmo}"
+        ))
     );
 
     // swap astro for osmo
@@ -451,7 +563,7 @@ fn test_swap_route() {
         simulate_res.amount
     );
 
-    let user = Addr::unchecked("user");
+    let user = app.api().addr_make("user");
     mint(&mut app, &owner, &astro, swap_amount.u128(), &user).unwrap();
 
     // query balance
@@ -478,6 +590,7 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
             })
             .unwrap(),
         },
@@ -485,7 +598,7 @@ fn test_swap_route() {
     )
     .unwrap();
 
-    let attacker = Addr::unchecked("attacker");
+    let attacker = app.api().addr_make("attacker");
     let donated_atom: u128 = 1;
 
     mint(&mut app, &owner, &atom, donated_atom, &attacker).unwrap();
@@ -565,6 +678,7 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
             })
             .unwrap(),
         },
@@ -657,6 +771,7 @@ fn test_swap_route() {
                 minimum_receive: Some(Uint128::new(9_997_000)),
                 to: None,
                 max_spread: None,
+                deadline: None,
             },
             &[],
         )
@@ -761,7 +876,7 @@ fn test_swap_route() {
     assert_eq!(balance_res.balance, swap_amount);
 
     // attacker2 front-run tx
-    let attacker2 = Addr::unchecked("attacker2");
+    let attacker2 = app.api().addr_make("attacker2");
 
     // assume the market is bad and user wants to get as much as they can
     let donated_atom = Uint128::new(9_000_000);
@@ -792,6 +907,7 @@ fn test_swap_route() {
                 minimum_receive: Some(donated_atom),
                 to: None,
                 max_spread: None,
+                deadline: None,
             })
             .unwrap(),
         },
@@ -850,6 +966,7 @@ fn test_swap_route() {
                 minimum_receive: None,
                 to: None,
                 max_spread: None,
+                deadline: None,
             },
             &[],
         )
@@ -18,6 +18,7 @@
 //!
 //! let values = buffer.read(&store, 0u32..=9, true).unwrap();
 //! let all_values = buffer.read_all(&store).unwrap();
+//! let newest_first = buffer.iter(&store).rev().collect::<Result<Vec<_>, _>>().unwrap();
 //! ```
 
 use std::collections::HashMap;
@@ -27,13 +28,19 @@ use std::marker::PhantomData;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_schema::serde::Serialize;
-use cosmwasm_std::{StdError, Storage};
+use cosmwasm_std::{Order, StdError, Storage};
 use cw_storage_plus::{Item, Map};
 
 use crate::error::{BufferError, BufferResult};
 
 pub mod error;
 
+/// Values which are pushed to the buffer in non-decreasing order of their key, e.g. a timestamp.
+/// Enables key-based search and range iteration over the buffer.
+pub trait TimeSeries {
+    fn key(&self) -> u64;
+}
+
 #[cw_serde]
 pub struct BufferState {
     capacity: u32,
@@ -137,6 +144,19 @@ where
         self.commit(store)
     }
 
+    /// Overwrite the last value saved in storage. Useful for aggregates which are updated until the next value is pushed.
+    /// In case buffer is empty it throws [`BufferError::IndexNotFound`] error.
+    pub fn instant_update_last(&self, store: &mut dyn Storage, value: &V) -> BufferResult<()> {
+        let last_ind = (self.state.capacity + self.state.head - 1) % self.state.capacity;
+        let array_key = self.store_iface.array();
+        if !array_key.has(store, last_ind) {
+            return Err(BufferError::IndexNotFound(last_ind));
+        }
+        array_key.save(store, last_ind, value)?;
+
+        Ok(())
+    }
+
     /// Commit in storage current state and precommit buffer. Buffer is erased after commit.
     pub fn commit(&mut self, store: &mut dyn Storage) -> BufferResult<()> {
         let array_key = self.store_iface.array();
@@ -152,6 +172,39 @@ where
         Ok(())
     }
 
+    /// Change buffer capacity. Precommitted values are committed first.
+    /// Saved values are moved to the beginning of the buffer keeping chronological order.
+    /// When shrinking, the oldest values which don't fit into the new capacity are removed.
+    pub fn resize(&mut self, store: &mut dyn Storage, new_capacity: u32) -> BufferResult<()> {
+        if new_capacity == 0 {
+            return Err(BufferError::ZeroCapacity {});
+        }
+        self.commit(store)?;
+
+        let (oldest_ind, len) = self.chronological_bounds(store);
+        let indexes = (oldest_ind..oldest_ind + len)
+            .map(|ind| ind % self.state.capacity)
+            .collect::<Vec<_>>();
+        let array_key = self.store_iface.array();
+        let skip = indexes.len().saturating_sub(new_capacity as usize);
+        let values = self.read(store, indexes[skip..].iter().copied(), false)?;
+
+        for index in indexes {
+            array_key.remove(store, index);
+        }
+        for (index, value) in (0u32..).zip(&values) {
+            array_key.save(store, index, value)?;
+        }
+
+        self.state = BufferState {
+            capacity: new_capacity,
+            head: values.len() as u32 % new_capacity,
+        };
+        self.store_iface.state().save(store, &self.state)?;
+
+        Ok(())
+    }
+
     /// Read values from storage by indexes. If `stop_if_empty` is true,
     /// reading will stop when first empty value is encountered.
     /// Otherwise, [`BufferError::IndexNotFound`] error will be thrown.
@@ -243,8 +296,197 @@ where
             .array()
             .has(store, index % self.state.capacity)
     }
+
+    /// Iterate over saved values from the oldest to the newest one.
+    /// Values are loaded lazily, call `.rev()` to iterate from the newest one.
+    pub fn iter<'s>(&'s self, store: &'s dyn Storage) -> BufferIter<'s, V> {
+        let (oldest_ind, len) = self.chronological_bounds(store);
+        self.iter_positions(store, oldest_ind, 0, len)
+    }
+
+    /// Returns the index of the oldest saved value and the number of saved values.
+    fn chronological_bounds(&self, store: &dyn Storage) -> (u32, u32) {
+        // If the slot at head is taken the buffer has wrapped around and head points to the oldest value
+        if self.exists(store, self.state.head) {
+            (self.state.head, self.state.capacity)
+        } else {
+            (0, self.state.head)
+        }
+    }
+
+    fn iter_positions<'s>(
+        &'s self,
+        store: &'s dyn Storage,
+        oldest_ind: u32,
+        front: u32,
+        back: u32,
+    ) -> BufferIter<'s, V> {
+        BufferIter {
+            store,
+            array: Map::new(self.store_iface.array_namespace),
+            oldest_ind,
+            capacity: self.state.capacity,
+            front,
+            back,
+        }
+    }
+}
+
+impl<'a, V> BufferManager<'a, V>
+where
+    V: TimeSeries + Serialize + DeserializeOwned + 'a,
+{
+    /// Returns the latest value with a key less than or equal to `key`
+    /// and the earliest value with a key greater than `key`.
+    /// Precommitted values are not considered.
+    pub fn find_bounds(
+        &self,
+        store: &dyn Storage,
+        key: u64,
+    ) -> BufferResult<(Option<V>, Option<V>)> {
+        let (oldest_ind, len) = self.chronological_bounds(store);
+        let pos = self.partition_point(store, oldest_ind, len, |k| k <= key)?;
+        let mut iter = self.iter_positions(store, oldest_ind, pos.saturating_sub(1), len);
+        let left = if pos > 0 {
+            iter.next().transpose()?
+        } else {
+            None
+        };
+
+        Ok((left, iter.next().transpose()?))
+    }
+
+    /// Iterate over saved values with a key in the inclusive range from `from` to `to`.
+    /// Unset bound means the range is not limited from that side.
+    pub fn range_by_key<'s>(
+        &'s self,
+        store: &'s dyn Storage,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> BufferResult<BufferIter<'s, V>> {
+        let (oldest_ind, len) = self.chronological_bounds(store);
+        let front = match from {
+            Some(from) => self.partition_point(store, oldest_ind, len, |k| k < from)?,
+            None => 0,
+        };
+        let back = match to {
+            Some(to) => self.partition_point(store, oldest_ind, len, |k| k <= to)?,
+            None => len,
+        };
+
+        Ok(self.iter_positions(store, oldest_ind, front, back.max(front)))
+    }
+
+    /// Read up to `limit` values in the given key order, starting after the value with key `start_after`.
+    /// Keys are expected to be unique, otherwise values sharing the `start_after` key are skipped.
+    pub fn paginate(
+        &self,
+        store: &dyn Storage,
+        start_after: Option<u64>,
+        limit: usize,
+        order: Order,
+    ) -> BufferResult<Vec<V>> {
+        match (order, start_after) {
+            (Order::Ascending, Some(u64::MAX)) | (Order::Descending, Some(0)) => Ok(vec![]),
+            (Order::Ascending, _) => self
+                .range_by_key(store, start_after.map(|key| key + 1), None)?
+                .take(limit)
+                .collect(),
+            (Order::Descending, _) => self
+                .range_by_key(store, None, start_after.map(|key| key - 1))?
+                .rev()
+                .take(limit)
+                .collect(),
+        }
+    }
+
+    /// Returns the number of the oldest values whose keys satisfy `pred` using binary search.
+    fn partition_point(
+        &self,
+        store: &dyn Storage,
+        oldest_ind: u32,
+        len: u32,
+        pred: impl Fn(u64) -> bool,
+    ) -> BufferResult<u32> {
+        let (mut lo, mut hi) = (0, len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let value = self
+                .iter_positions(store, oldest_ind, mid, mid + 1)
+                .next()
+                .transpose()?;
+            if value.is_some_and(|value| pred(value.key())) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        Ok(lo)
+    }
+}
+
+/// Lazy iterator over saved buffer values in chronological order.
+pub struct BufferIter<'s, V> {
+    store: &'s dyn Storage,
+    array: Map<'s, u32, V>,
+    oldest_ind: u32,
+    capacity: u32,
+    front: u32,
+    back: u32,
+}
+
+impl<V> BufferIter<'_, V>
+where
+    V: Serialize + DeserializeOwned,
+{
+    fn load(&self, pos: u32) -> BufferResult<V> {
+        let ind = (self.oldest_ind + pos) % self.capacity;
+        self.array.load(self.store, ind).map_err(|err| {
+            if let StdError::NotFound { .. } = err {
+                BufferError::IndexNotFound(ind)
+            } else {
+                err.into()
+            }
+        })
+    }
+}
+
+impl<V> Iterator for BufferIter<'_, V>
+where
+    V: Serialize + DeserializeOwned,
+{
+    type Item = BufferResult<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.load(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.back - self.front) as usize;
+        (len, Some(len))
+    }
 }
 
+impl<V> DoubleEndedIterator for BufferIter<'_, V>
+where
+    V: Serialize + DeserializeOwned,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.load(self.back))
+    }
+}
+
+impl<V> ExactSizeIterator for BufferIter<'_, V> where V: Serialize + DeserializeOwned {}
+
 impl<V: Debug> Debug for BufferManager<'_, V> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BufferManager")
@@ -257,7 +499,8 @@ impl<V: Debug> Debug for BufferManager<'_, V> {
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::MockStorage;
-    use cosmwasm_std::Uint128;
+    use cosmwasm_std::{Order, StdResult, Uint128};
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -314,6 +557,35 @@ mod tests {
         assert_eq!(err, BufferError::ReadAheadError(10));
     }
 
+    #[test]
+    fn test_update_last() {
+        let mut store = MockStorage::new();
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 3).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+        // Nothing to update in empty buffer
+        let err = buffer
+            .instant_update_last(&mut store, &DataType::from(1u8))
+            .unwrap_err();
+        assert_eq!(err, BufferError::IndexNotFound(2));
+
+        let data = (1..=4u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        buffer
+            .instant_update_last(&mut store, &DataType::from(40u8))
+            .unwrap();
+        assert_eq!(buffer.read_last(&store).unwrap().unwrap().u128(), 40);
+        let saved = buffer
+            .read_all(&store)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.u128())
+            .collect::<Vec<_>>();
+        assert_eq!(saved, vec![40, 2, 3]);
+    }
+
     #[test]
     fn test_push_many() {
         let mut store = MockStorage::new();
@@ -350,4 +622,204 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(partial_read, vec![11, 13, 15, 7, 9]);
     }
+
+    /// Reads saved values starting from the oldest one.
+    fn read_chronological(buffer: &BufferManager<DataType>, store: &MockStorage) -> Vec<u128> {
+        buffer.iter(store).map(|i| i.unwrap().u128()).collect()
+    }
+
+    #[test]
+    fn test_resize_zero_capacity() {
+        let mut store = MockStorage::new();
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 10).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+        let err = buffer.resize(&mut store, 0).unwrap_err();
+        assert_eq!(err, BufferError::ZeroCapacity {});
+    }
+
+    #[cw_serde]
+    struct Point {
+        ts: u64,
+    }
+
+    impl TimeSeries for Point {
+        fn key(&self) -> u64 {
+            self.ts
+        }
+    }
+
+    const POINTS: CircularBuffer<Point> = CircularBuffer::new("points_state", "points");
+
+    #[test]
+    fn test_time_series() {
+        let mut store = MockStorage::new();
+        BufferManager::init(&mut store, POINTS, 5).unwrap();
+        let mut buffer = BufferManager::new(&store, POINTS).unwrap();
+
+        assert_eq!(buffer.find_bounds(&store, 10).unwrap(), (None, None));
+        assert_eq!(buffer.iter(&store).len(), 0);
+
+        // Buffer wraps around and keeps points 30..=70
+        let data = (1..=7).map(|i| Point { ts: i * 10 }).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        let ts = |values: Vec<Point>| values.into_iter().map(|p| p.ts).collect::<Vec<_>>();
+        let collect = |iter: BufferIter<Point>| ts(iter.collect::<BufferResult<_>>().unwrap());
+
+        assert_eq!(collect(buffer.iter(&store)), vec![30, 40, 50, 60, 70]);
+        assert_eq!(
+            ts(buffer.iter(&store).rev().map(Result::unwrap).collect()),
+            vec![70, 60, 50, 40, 30]
+        );
+
+        assert_eq!(
+            buffer.find_bounds(&store, 20).unwrap(),
+            (None, Some(Point { ts: 30 }))
+        );
+        assert_eq!(
+            buffer.find_bounds(&store, 50).unwrap(),
+            (Some(Point { ts: 50 }), Some(Point { ts: 60 }))
+        );
+        assert_eq!(
+            buffer.find_bounds(&store, 55).unwrap(),
+            (Some(Point { ts: 50 }), Some(Point { ts: 60 }))
+        );
+        assert_eq!(
+            buffer.find_bounds(&store, 100).unwrap(),
+            (Some(Point { ts: 70 }), None)
+        );
+
+        let range = buffer.range_by_key(&store, Some(35), Some(60)).unwrap();
+        assert_eq!(range.len(), 3);
+        assert_eq!(collect(range), vec![40, 50, 60]);
+        let mut range = buffer.range_by_key(&store, Some(40), None).unwrap();
+        assert_eq!(range.next_back().unwrap().unwrap().ts, 70);
+        assert_eq!(range.next().unwrap().unwrap().ts, 40);
+        assert_eq!(ts(range.rev().map(Result::unwrap).collect()), vec![60, 50]);
+        assert_eq!(
+            collect(buffer.range_by_key(&store, None, Some(30)).unwrap()),
+            vec![30]
+        );
+        assert_eq!(
+            collect(buffer.range_by_key(&store, Some(61), Some(69)).unwrap()),
+            Vec::<u64>::new()
+        );
+        assert_eq!(
+            collect(buffer.range_by_key(&store, Some(60), Some(40)).unwrap()),
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_paginate() {
+        let mut store = MockStorage::new();
+        BufferManager::init(&mut store, POINTS, 5).unwrap();
+        let mut buffer = BufferManager::new(&store, POINTS).unwrap();
+        let data = (1..=7).map(|i| Point { ts: i * 10 }).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        let page = |start_after, limit, order| {
+            buffer
+                .paginate(&store, start_after, limit, order)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.ts)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(page(None, 2, Order::Ascending), vec![30, 40]);
+        assert_eq!(page(Some(40), 2, Order::Ascending), vec![50, 60]);
+        assert_eq!(page(Some(60), 2, Order::Ascending), vec![70]);
+        assert_eq!(page(Some(u64::MAX), 2, Order::Ascending), Vec::<u64>::new());
+        assert_eq!(page(None, 2, Order::Descending), vec![70, 60]);
+        assert_eq!(page(Some(60), 10, Order::Descending), vec![50, 40, 30]);
+        assert_eq!(page(Some(35), 10, Order::Descending), vec![30]);
+        assert_eq!(page(Some(0), 10, Order::Descending), Vec::<u64>::new());
+    }
+
+    proptest! {
+        #[test]
+        fn range_by_key_matches_naive_filter(
+            capacity in 1u32..20,
+            steps in prop::collection::vec(0u64..3, 0..40),
+            from in prop::option::of(0u64..60),
+            to in prop::option::of(0u64..60),
+        ) {
+            let mut store = MockStorage::new();
+            BufferManager::init(&mut store, POINTS, capacity).unwrap();
+            let mut buffer = BufferManager::new(&store, POINTS).unwrap();
+
+            // Keys are non-decreasing and may repeat
+            let data = steps
+                .iter()
+                .scan(0, |ts, step| {
+                    *ts += step;
+                    Some(Point { ts: *ts })
+                })
+                .collect::<Vec<_>>();
+            buffer.push_many(&data);
+            buffer.commit(&mut store).unwrap();
+
+            let saved = data[data.len().saturating_sub(capacity as usize)..].to_vec();
+            let range = buffer
+                .range_by_key(&store, from, to)
+                .unwrap()
+                .collect::<BufferResult<Vec<_>>>()
+                .unwrap();
+            let expected = saved
+                .iter()
+                .filter(|p| from.is_none_or(|from| p.ts >= from) && to.is_none_or(|to| p.ts <= to))
+                .cloned()
+                .collect::<Vec<_>>();
+            prop_assert_eq!(range, expected);
+
+            let key = from.unwrap_or_default();
+            let (left, right) = buffer.find_bounds(&store, key).unwrap();
+            prop_assert_eq!(left, saved.iter().rev().find(|p| p.ts <= key).cloned());
+            prop_assert_eq!(right, saved.iter().find(|p| p.ts > key).cloned());
+        }
+
+        #[test]
+        fn resize_keeps_latest_values_in_order(
+            capacity in 1u32..40,
+            pushed in 0u32..100,
+            new_capacity in 1u32..40,
+        ) {
+            let mut store = MockStorage::new();
+            BufferManager::init(&mut store, CIRCULAR_BUFFER, capacity).unwrap();
+            let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+            let data = (1..=pushed as u128).map(DataType::from).collect::<Vec<_>>();
+            buffer.push_many(&data);
+            buffer.commit(&mut store).unwrap();
+
+            let saved = read_chronological(&buffer, &store);
+            let kept = saved[saved.len().saturating_sub(new_capacity as usize)..].to_vec();
+
+            buffer.resize(&mut store, new_capacity).unwrap();
+            prop_assert_eq!(buffer.capacity(), new_capacity);
+            prop_assert_eq!(read_chronological(&buffer, &store), kept.clone());
+
+            // Orphaned slots are removed
+            let keys = CIRCULAR_BUFFER
+                .array()
+                .keys(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            prop_assert_eq!(keys, (0..kept.len() as u32).collect::<Vec<_>>());
+
+            // The resized buffer keeps working as usual
+            let reloaded = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+            prop_assert_eq!(reloaded.capacity(), new_capacity);
+            prop_assert_eq!(reloaded.head(), buffer.head());
+            let next = DataType::from(pushed as u128 + 1);
+            buffer.instant_push(&mut store, &next).unwrap();
+            let mut expected = kept;
+            expected.push(next.u128());
+            let expected = expected[expected.len().saturating_sub(new_capacity as usize)..].to_vec();
+            prop_assert_eq!(read_chronological(&buffer, &store), expected);
+        }
+    }
 }
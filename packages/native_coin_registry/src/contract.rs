@@ -8,8 +8,11 @@ use cw_storage_plus::Bound;
 use std::collections::HashSet;
 
 use crate::error::ContractError;
-use crate::state::{CONFIG, OWNERSHIP_PROPOSAL};
-use palomadex::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use crate::state::{CONFIG, OWNERSHIP_PROPOSAL, ROLES};
+use palomadex::common::{
+    claim_ownership, drop_ownership_proposal, grant_role, has_role, propose_new_owner,
+    query_role_members, renounce_role, revoke_role, Role,
+};
 use palomadex::native_coin_registry::{
     CoinResponse, Config, ExecuteMsg, InstantiateMsg, QueryMsg, COINS_INFO,
 };
@@ -45,6 +48,19 @@ pub fn execute(
     match msg {
         ExecuteMsg::Add { native_coins } => update(deps, info, native_coins),
         ExecuteMsg::Remove { native_coins } => remove(deps, info, native_coins),
+        ExecuteMsg::GrantRole { role, account } => {
+            let config = CONFIG.load(deps.storage)?;
+
+            grant_role(deps, info, config.owner, role, account, ROLES).map_err(Into::into)
+        }
+        ExecuteMsg::RevokeRole { role, account } => {
+            let config = CONFIG.load(deps.storage)?;
+
+            revoke_role(deps, info, config.owner, role, account, ROLES).map_err(Into::into)
+        }
+        ExecuteMsg::RenounceRole { role } => {
+            renounce_role(deps, info, role, ROLES).map_err(Into::into)
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -84,7 +100,7 @@ pub fn execute(
 /// * **native_coins** is a vector with the assets we are adding to the registry.
 ///
 /// ## Executor
-/// Only the owner can execute this.
+/// Only the owner or a lister can execute this. A lister can only add coins which aren't registered yet.
 pub fn update(
     deps: DepsMut,
     info: MessageInfo,
@@ -92,7 +108,8 @@ pub fn update(
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    if info.sender != config.owner {
+    let is_owner = info.sender == config.owner;
+    if !is_owner && !has_role(deps.storage, &Role::Lister, &info.sender, ROLES) {
         return Err(ContractError::Unauthorized {});
     }
 
@@ -106,6 +123,10 @@ pub fn update(
         if decimals == 0 {
             return Err(ContractError::CoinWithZeroPrecision(coin));
         }
+        // Changing the precision of a listed coin affects existing pairs, so it's up to the owner
+        if !is_owner && COINS_INFO.has(deps.storage, coin.clone()) {
+            return Err(ContractError::CoinAlreadyExists(coin));
+        }
 
         COINS_INFO.save(deps.storage, coin, &decimals)?;
     }
@@ -157,6 +178,13 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::NativeTokens { start_after, limit } => {
             to_json_binary(&query_native_tokens(deps, start_after, limit)?)
         }
+        QueryMsg::RoleMembers { role } => {
+            to_json_binary(&query_role_members(deps.storage, &role, ROLES)?)
+        }
+        QueryMsg::HasRole { role, account } => {
+            let account = deps.api.addr_validate(&account)?;
+            to_json_binary(&has_role(deps.storage, &role, &account, ROLES))
+        }
     }
 }
 
@@ -20,4 +20,7 @@ pub enum ContractError {
 
     #[error("The coin does not exist: {0}")]
     CoinDoesNotExist(String),
+
+    #[error("The coin already exists: {0}")]
+    CoinAlreadyExists(String),
 }
@@ -1,5 +1,5 @@
-use cw_storage_plus::Item;
-use palomadex::common::OwnershipProposal;
+use cw_storage_plus::{Item, Map};
+use palomadex::common::{OwnershipProposal, Roles};
 use palomadex::native_coin_registry::Config;
 
 /// Stores the contract config at the given key
@@ -7,3 +7,6 @@ pub const CONFIG: Item<Config> = Item::new("config");
 
 /// Contains a proposal to change contract ownership.
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
+
+/// Contains the accounts that were granted roles by the owner.
+pub const ROLES: Roles = Map::new("roles");
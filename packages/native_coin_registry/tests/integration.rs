@@ -3,6 +3,7 @@
 use cosmwasm_std::Addr;
 
 use cw_multi_test::{App, ContractWrapper, Executor};
+use palomadex::common::Role;
 use palomadex::native_coin_registry::{CoinResponse, Config, ExecuteMsg, InstantiateMsg, QueryMsg};
 
 fn mock_app() -> App {
@@ -381,3 +382,193 @@ fn try_add_and_remove_native_tokens() {
         config_res
     );
 }
+
+#[test]
+fn check_lister_role() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let lister = Addr::unchecked("lister");
+
+    let native_registry_code_id = store_native_registry_code(&mut app);
+    let native_registry_instance = app
+        .instantiate_contract(
+            native_registry_code_id,
+            owner.clone(),
+            &InstantiateMsg {
+                owner: owner.to_string(),
+            },
+            &[],
+            "Precision registry contract",
+            None,
+        )
+        .unwrap();
+
+    let add_msg = ExecuteMsg::Add {
+        native_coins: vec![("uluna".to_string(), 6)],
+    };
+    let err = app
+        .execute_contract(
+            lister.clone(),
+            native_registry_instance.clone(),
+            &add_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    // Only the owner can grant roles
+    let grant_msg = ExecuteMsg::GrantRole {
+        role: Role::Lister,
+        account: lister.to_string(),
+    };
+    let err = app
+        .execute_contract(
+            lister.clone(),
+            native_registry_instance.clone(),
+            &grant_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    app.execute_contract(
+        owner.clone(),
+        native_registry_instance.clone(),
+        &grant_msg,
+        &[],
+    )
+    .unwrap();
+
+    let members: Vec<Addr> = app
+        .wrap()
+        .query_wasm_smart(
+            &native_registry_instance,
+            &QueryMsg::RoleMembers { role: Role::Lister },
+        )
+        .unwrap();
+    assert_eq!(members, vec![lister.clone()]);
+
+    app.execute_contract(
+        lister.clone(),
+        native_registry_instance.clone(),
+        &add_msg,
+        &[],
+    )
+    .unwrap();
+
+    // Listers can't change the precision of registered coins
+    let err = app
+        .execute_contract(
+            lister.clone(),
+            native_registry_instance.clone(),
+            &ExecuteMsg::Add {
+                native_coins: vec![("uusd".to_string(), 6), ("uluna".to_string(), 8)],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "The coin already exists: uluna"
+    );
+
+    // Listers can't remove coins
+    let remove_msg = ExecuteMsg::Remove {
+        native_coins: vec!["uluna".to_string()],
+    };
+    let err = app
+        .execute_contract(
+            lister.clone(),
+            native_registry_instance.clone(),
+            &remove_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let res: u8 = app
+        .wrap()
+        .query_wasm_smart(
+            &native_registry_instance,
+            &QueryMsg::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, 6);
+
+    // The owner can still update and remove coins
+    app.execute_contract(
+        owner.clone(),
+        native_registry_instance.clone(),
+        &ExecuteMsg::Add {
+            native_coins: vec![("uluna".to_string(), 8)],
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        native_registry_instance.clone(),
+        &remove_msg,
+        &[],
+    )
+    .unwrap();
+
+    // Roles don't allow changing the ownership
+    let err = app
+        .execute_contract(
+            lister.clone(),
+            native_registry_instance.clone(),
+            &ExecuteMsg::ProposeNewOwner {
+                owner: lister.to_string(),
+                expires_in: 100,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+
+    app.execute_contract(
+        lister.clone(),
+        native_registry_instance.clone(),
+        &ExecuteMsg::RenounceRole { role: Role::Lister },
+        &[],
+    )
+    .unwrap();
+
+    let has_role: bool = app
+        .wrap()
+        .query_wasm_smart(
+            &native_registry_instance,
+            &QueryMsg::HasRole {
+                role: Role::Lister,
+                account: lister.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(!has_role);
+    app.execute_contract(
+        lister.clone(),
+        native_registry_instance.clone(),
+        &add_msg,
+        &[],
+    )
+    .unwrap_err();
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            native_registry_instance.clone(),
+            &ExecuteMsg::RevokeRole {
+                role: Role::Lister,
+                account: lister.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: lister doesn't have the lister role"
+    );
+}
@@ -1,8 +1,11 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    attr, Addr, Api, CustomQuery, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    attr, Addr, Api, CustomQuery, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+    Storage,
 };
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 const MAX_PROPOSAL_TTL: u64 = 1209600;
 
@@ -133,6 +136,159 @@ where
     ]))
 }
 
+/// This enum describes the roles the contract owner can grant to split its duties.
+/// The owner is the root role and is allowed to do everything any role allows.
+#[cw_serde]
+pub enum Role {
+    /// Manages the fees of pairs
+    FeeManager,
+    /// Changes the amplification of stableswap pools
+    AmpManager,
+    /// Creates permissioned pairs and lists new native coins
+    Lister,
+}
+
+impl Role {
+    /// Returns the name used for the role in storage keys and attributes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::FeeManager => "fee_manager",
+            Role::AmpManager => "amp_manager",
+            Role::Lister => "lister",
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        fmt.write_str(self.as_str())
+    }
+}
+
+/// Stores the accounts that were granted a [`Role`]. The key is the role name and the account address.
+pub type Roles<'a> = Map<'a, (&'a str, &'a Addr), ()>;
+
+/// Grants `role` to `account`. Granting a role the account already has is a no-op.
+///
+/// `owner` is the current contract owner.
+///
+/// ## Executor
+/// Only the current contract owner can execute this.
+pub fn grant_role<C, T>(
+    deps: DepsMut<C>,
+    info: MessageInfo,
+    owner: Addr,
+    role: Role,
+    account: String,
+    roles: Roles,
+) -> StdResult<Response<T>>
+where
+    C: CustomQuery,
+{
+    // Permission check
+    if info.sender != owner {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    let account = deps.api.addr_validate(&account)?;
+    roles.save(deps.storage, (role.as_str(), &account), &())?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "grant_role"),
+        attr("role", role.as_str()),
+        attr("account", account),
+    ]))
+}
+
+/// Revokes `role` from `account`.
+///
+/// `owner` is the current contract owner.
+///
+/// ## Executor
+/// Only the current contract owner can execute this.
+pub fn revoke_role<C, T>(
+    deps: DepsMut<C>,
+    info: MessageInfo,
+    owner: Addr,
+    role: Role,
+    account: String,
+    roles: Roles,
+) -> StdResult<Response<T>>
+where
+    C: CustomQuery,
+{
+    // Permission check
+    if info.sender != owner {
+        return Err(StdError::generic_err("Unauthorized"));
+    }
+
+    let account = deps.api.addr_validate(&account)?;
+    remove_role(deps.storage, &role, &account, roles)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_role"),
+        attr("role", role.as_str()),
+        attr("account", account),
+    ]))
+}
+
+/// Gives up `role` held by the sender.
+///
+/// ## Executor
+/// Only an account that has `role` can execute this.
+pub fn renounce_role<C, T>(
+    deps: DepsMut<C>,
+    info: MessageInfo,
+    role: Role,
+    roles: Roles,
+) -> StdResult<Response<T>>
+where
+    C: CustomQuery,
+{
+    remove_role(deps.storage, &role, &info.sender, roles)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "renounce_role"),
+        attr("role", role.as_str()),
+        attr("account", info.sender),
+    ]))
+}
+
+fn remove_role(
+    storage: &mut dyn Storage,
+    role: &Role,
+    account: &Addr,
+    roles: Roles,
+) -> StdResult<()> {
+    if !roles.has(storage, (role.as_str(), account)) {
+        return Err(StdError::generic_err(format!(
+            "{account} doesn't have the {role} role"
+        )));
+    }
+
+    roles.remove(storage, (role.as_str(), account));
+
+    Ok(())
+}
+
+/// Returns whether `account` was granted `role`. The owner isn't stored as a role member,
+/// so contracts have to check it separately.
+pub fn has_role(storage: &dyn Storage, role: &Role, account: &Addr, roles: Roles) -> bool {
+    roles.has(storage, (role.as_str(), account))
+}
+
+/// Returns all accounts that were granted `role`.
+pub fn query_role_members(
+    storage: &dyn Storage,
+    role: &Role,
+    roles: Roles,
+) -> StdResult<Vec<Addr>> {
+    roles
+        .prefix(role.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
 /// Bulk validation and conversion between [`String`] -> [`Addr`] for an array of addresses.
 /// If any address is invalid, the function returns [`StdError`].
 pub fn validate_addresses(api: &dyn Api, admins: &[String]) -> StdResult<Vec<Addr>> {
@@ -1,4 +1,5 @@
-use crate::asset::{AssetInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::common::Role;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, Binary};
@@ -22,10 +23,12 @@ pub struct Config {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins with their precision
     pub coin_registry_address: Addr,
-
-    pub bonding_curve_factory_address: Option<Addr>,
-
-    pub pusd_denom: Option<String>,
+    /// Address allowed to pause pairs in addition to the owner
+    pub guardian: Option<Addr>,
+    /// The minimum time (in seconds) between proposing a timelocked change and executing it
+    pub timelock_delay: u64,
+    /// Address allowed to cancel timelocked changes in addition to the owner
+    pub canceller: Option<Addr>,
 }
 
 /// This enum describes available pair types.
@@ -92,6 +95,65 @@ impl PairConfig {
     }
 }
 
+/// This structure stores fees for a specific pair which override the fees of its pair type.
+#[cw_serde]
+pub struct PairFeeOverride {
+    /// The total fees (in bps) charged by the pair
+    pub total_fee_bps: u16,
+    /// The amount of fees (in bps) collected by the Maker contract from the pair
+    pub maker_fee_bps: u16,
+}
+
+impl PairFeeOverride {
+    /// This method is used to check fee bps.
+    pub fn valid_fee_bps(&self) -> bool {
+        self.total_fee_bps <= MAX_TOTAL_FEE_BPS && self.maker_fee_bps <= MAX_MAKER_FEE_BPS
+    }
+}
+
+/// This enum describes which native denoms a [`ListingRule`] applies to.
+#[cw_serde]
+pub enum DenomMatcher {
+    /// Matches the specified denom only
+    Exact(String),
+    /// Matches every tokenfactory denom created by the specified address (`factory/{creator}/...`)
+    TokenFactoryCreator(String),
+}
+
+impl DenomMatcher {
+    /// Returns true if `denom` is matched.
+    pub fn matches(&self, denom: &str) -> bool {
+        match self {
+            DenomMatcher::Exact(exact) => denom == exact,
+            DenomMatcher::TokenFactoryCreator(creator) => denom
+                .strip_prefix("factory/")
+                .and_then(|rest| rest.strip_prefix(creator.as_str()))
+                .is_some_and(|rest| rest.starts_with('/')),
+        }
+    }
+}
+
+/// Returns the denom or the denom pattern matched by a [`DenomMatcher`]
+impl Display for DenomMatcher {
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        match self {
+            DenomMatcher::Exact(denom) => fmt.write_str(denom),
+            DenomMatcher::TokenFactoryCreator(creator) => write!(fmt, "factory/{}/*", creator),
+        }
+    }
+}
+
+/// This structure describes a rule of the factory listing policy.
+/// Assets matched by a rule are restricted for every sender that isn't allowed by the rule.
+/// A pair can contain at most one asset that is restricted for the pair creator.
+#[cw_serde]
+pub struct ListingRule {
+    /// The native denoms the rule applies to
+    pub denom: DenomMatcher,
+    /// Senders that may pair matching assets without restrictions
+    pub allowed_senders: Vec<Addr>,
+}
+
 /// This structure stores the basic settings for creating a new factory contract.
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -109,32 +171,33 @@ pub struct InstantiateMsg {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins and their accuracy
     pub coin_registry_address: String,
+    /// The minimum time (in seconds) between proposing a timelocked change and executing it
+    pub timelock_delay: u64,
+    /// Address allowed to cancel timelocked changes in addition to the owner
+    pub canceller: Option<String>,
 }
 
 /// This structure describes the execute messages of the contract.
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// UpdateConfig updates relevant code IDs
-    UpdateConfig {
-        /// CW20 token contract code identifier
-        token_code_id: Option<u64>,
-        /// Contract address to send governance fees to (the Maker)
-        fee_address: Option<String>,
-        /// Contract address where Lp tokens can be auto_staked after someone provides liquidity in an incentivized Palomadex pool
-        generator_address: Option<String>,
-        /// CW1 whitelist contract code id used to store 3rd party rewards for staking Palomadex LP tokens
-        whitelist_code_id: Option<u64>,
-        /// The address of the contract that contains the coins and their accuracy
-        coin_registry_address: Option<String>,
-
-        bonding_curve_factory_address: Option<String>,
-
-        pusd_denom: Option<String>,
+    /// ProposeChange queues a timelocked change which can be executed at `eta` or later.
+    /// `eta` must be at least `timelock_delay` seconds in the future.
+    /// Fee managers can queue and execute changes of pair fee overrides.
+    ProposeChange {
+        /// The change to queue
+        change: TimelockedChange,
+        /// The time (in seconds) after which the change can be executed
+        eta: u64,
     },
-    /// UpdatePairConfig updates the config for a pair type.
-    UpdatePairConfig {
-        /// New [`PairConfig`] settings for a pair type
-        config: PairConfig,
+    /// ExecuteChange applies a queued change after its `eta`.
+    ExecuteChange {
+        /// The identifier of the queued change
+        id: u64,
+    },
+    /// CancelChange removes a queued change without applying it.
+    CancelChange {
+        /// The identifier of the queued change
+        id: u64,
     },
     /// CreatePair instantiates a new pair contract.
     CreatePair {
@@ -145,10 +208,82 @@ pub enum ExecuteMsg {
         /// Optional binary serialised parameters for custom pool types
         init_params: Option<Binary>,
     },
+    /// CreatePairAndProvide instantiates a new pair contract and provides its initial liquidity in the same transaction.
+    CreatePairAndProvide {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The assets to create the pool for
+        asset_infos: Vec<AssetInfo>,
+        /// Optional binary serialised parameters for custom pool types
+        init_params: Option<Binary>,
+        /// The assets to provide. Native coins must be sent along, the factory must be allowed to transfer cw20 tokens
+        assets: Vec<Asset>,
+        /// The receiver of LP tokens. The sender by default
+        receiver: Option<String>,
+    },
     /// Deregister removes a previously created pair.
     Deregister {
         /// The assets for which we deregister a pool
         asset_infos: Vec<AssetInfo>,
+        /// The type of the pool to deregister. If not set, the first pool created for the assets is removed
+        pair_type: Option<PairType>,
+    },
+    /// SetListingRule adds a listing rule or replaces the rule with the same `denom` matcher.
+    SetListingRule {
+        /// The native denoms the rule applies to
+        denom: DenomMatcher,
+        /// Senders that may pair matching assets without restrictions
+        allowed_senders: Vec<String>,
+    },
+    /// RemoveListingRule removes the listing rule with the specified `denom` matcher.
+    RemoveListingRule {
+        /// The matcher of the rule to remove
+        denom: DenomMatcher,
+    },
+    /// MigratePairs migrates registered pairs of the specified type to a new code ID.
+    /// The migration must be approved by executing a [`TimelockedChange::MigratePairs`] with the same parameters first.
+    /// Pairs are migrated in batches of `limit`, the remaining ones are migrated by subsequent calls.
+    MigratePairs {
+        /// The type of pairs to migrate
+        pair_type: PairType,
+        /// The code ID to migrate pairs to
+        new_code_id: u64,
+        /// The binary serialised migration message sent to every pair
+        msg: Binary,
+        /// The maximum number of pairs to migrate in this call
+        limit: Option<u32>,
+    },
+    /// PausePairs pauses swaps and liquidity provision in the specified pairs, or in all pairs if `pairs` is not set.
+    /// Withdrawing liquidity from paused pairs is still possible.
+    PausePairs {
+        /// The pair contract addresses to pause
+        pairs: Option<Vec<String>>,
+    },
+    /// UnpausePairs unpauses the specified pairs, or lifts the pause of all pairs if `pairs` is not set.
+    /// Pairs paused individually stay paused until they are unpaused by address.
+    /// Only the owner can unpause pairs.
+    UnpausePairs {
+        /// The pair contract addresses to unpause
+        pairs: Option<Vec<String>>,
+    },
+    /// GrantRole grants a role to an account. Only the owner can grant roles.
+    GrantRole {
+        /// The granted role
+        role: Role,
+        /// The account that receives the role
+        account: String,
+    },
+    /// RevokeRole revokes a role from an account. Only the owner can revoke roles.
+    RevokeRole {
+        /// The revoked role
+        role: Role,
+        /// The account that loses the role
+        account: String,
+    },
+    /// RenounceRole gives up a role held by the sender.
+    RenounceRole {
+        /// The role to give up
+        role: Role,
     },
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
@@ -164,6 +299,88 @@ pub enum ExecuteMsg {
     ClaimOwnership {},
 }
 
+/// This enum describes the changes that take effect only after the timelock delay.
+#[cw_serde]
+pub enum TimelockedChange {
+    /// UpdateConfig updates relevant code IDs, addresses and the timelock settings
+    UpdateConfig {
+        /// CW20 token contract code identifier
+        token_code_id: Option<u64>,
+        /// Contract address to send governance fees to (the Maker)
+        fee_address: Option<String>,
+        /// Contract address where Lp tokens can be auto_staked after someone provides liquidity in an incentivized Palomadex pool
+        generator_address: Option<String>,
+        /// CW1 whitelist contract code id used to store 3rd party rewards for staking Palomadex LP tokens
+        whitelist_code_id: Option<u64>,
+        /// The address of the contract that contains the coins and their accuracy
+        coin_registry_address: Option<String>,
+        /// Address allowed to pause pairs in addition to the owner
+        guardian: Option<String>,
+        /// The minimum time (in seconds) between proposing a timelocked change and executing it
+        timelock_delay: Option<u64>,
+        /// Address allowed to cancel timelocked changes in addition to the owner
+        canceller: Option<String>,
+    },
+    /// UpdatePairConfig updates the config for a pair type.
+    UpdatePairConfig {
+        /// New [`PairConfig`] settings for a pair type
+        config: PairConfig,
+    },
+    /// UpdatePair forwards an `UpdateConfig` message with the specified params to a pair.
+    UpdatePair {
+        /// The pair contract address
+        pair_addr: String,
+        /// The binary serialised pair specific params
+        params: Binary,
+    },
+    /// SetPairFeeOverride sets fees for a specific pair which are used instead of its pair type fees.
+    SetPairFeeOverride {
+        /// The pair contract address
+        pair_addr: String,
+        /// The total fees (in bps) charged by the pair
+        total_fee_bps: u16,
+        /// The amount of fees (in bps) collected by the Maker contract from the pair
+        maker_fee_bps: u16,
+    },
+    /// RemovePairFeeOverride removes the fee override of a specific pair.
+    RemovePairFeeOverride {
+        /// The pair contract address
+        pair_addr: String,
+    },
+    /// MigratePairs approves the migration of the registered pairs of the specified type to a new code ID.
+    /// The pairs are migrated afterwards by `ExecuteMsg::MigratePairs` calls with the same parameters.
+    MigratePairs {
+        /// The type of pairs to migrate
+        pair_type: PairType,
+        /// The code ID to migrate pairs to
+        new_code_id: u64,
+        /// The binary serialised migration message sent to every pair
+        msg: Binary,
+    },
+}
+
+impl TimelockedChange {
+    /// Returns whether the change only manages the fees of a pair, which fee managers may queue and execute.
+    pub fn is_fee_change(&self) -> bool {
+        matches!(
+            self,
+            TimelockedChange::SetPairFeeOverride { .. }
+                | TimelockedChange::RemovePairFeeOverride { .. }
+        )
+    }
+}
+
+/// This structure describes a timelocked change waiting in the queue.
+#[cw_serde]
+pub struct QueuedChange {
+    /// The identifier of the change
+    pub id: u64,
+    /// The queued change
+    pub change: TimelockedChange,
+    /// The time (in seconds) after which the change can be executed
+    pub eta: u64,
+}
+
 /// This structure describes the available query messages for the factory contract.
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -176,6 +393,8 @@ pub enum QueryMsg {
     Pair {
         /// The assets for which we return a pair
         asset_infos: Vec<AssetInfo>,
+        /// The type of the pool to return. If not set, the first pool created for the assets is returned
+        pair_type: Option<PairType>,
     },
     /// Pairs returns an array of pairs and their information according to the specified parameters in `start_after` and `limit` variables.
     #[returns(PairsResponse)]
@@ -190,10 +409,66 @@ pub enum QueryMsg {
     FeeInfo {
         /// The pair type for which we return fee information. Pair type is a [`PairType`] struct
         pair_type: PairType,
+        /// The pair for which we return fee information. Its fee override is used if one is set
+        pair_addr: Option<String>,
     },
     /// Returns a vector that contains blacklisted pair types
     #[returns(Vec<PairType>)]
     BlacklistedPairTypes {},
+    /// PairsByAsset returns all pairs that contain the specified asset, ordered by pair address.
+    #[returns(PairsResponse)]
+    PairsByAsset {
+        /// The asset for which we return pairs
+        asset_info: AssetInfo,
+        /// The pair contract address to start reading from
+        start_after: Option<String>,
+        /// The number of pairs to read and return
+        limit: Option<u32>,
+    },
+    /// Returns the pairs that are still pending the approved migration
+    #[returns(PairsToMigrateResponse)]
+    PairsToMigrate {},
+    /// PredictPairAddress returns the address a pair of `pair_type` for the specified assets is created at.
+    /// The address depends on the code ID of the pair type, so it changes if the pair type's code ID is updated.
+    /// A pair recreated after `Deregister` is created at a new address.
+    #[returns(Addr)]
+    PredictPairAddress {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The assets of the pair
+        asset_infos: Vec<AssetInfo>,
+    },
+    /// Returns the rules of the listing policy
+    #[returns(Vec<ListingRule>)]
+    ListingRules {},
+    /// IsPaused returns whether swaps and liquidity provision are paused in the specified pair
+    #[returns(bool)]
+    IsPaused {
+        /// The pair contract address
+        pair_addr: String,
+    },
+    /// RoleMembers returns the accounts that were granted the specified role
+    #[returns(Vec<Addr>)]
+    RoleMembers {
+        /// The role to query members for
+        role: Role,
+    },
+    /// HasRole returns whether the specified account was granted the specified role
+    #[returns(bool)]
+    HasRole {
+        /// The role to check
+        role: Role,
+        /// The account to check
+        account: String,
+    },
+    /// QueuedChanges returns timelocked changes which were neither executed nor cancelled
+    #[returns(Vec<QueuedChange>)]
+    QueuedChanges {
+        /// The identifier of the change to start reading from
+        start_after: Option<u64>,
+        /// The number of changes to read
+        limit: Option<u32>,
+    },
 }
 
 /// A custom struct for each query response that returns general contract settings/configs.
@@ -213,10 +488,12 @@ pub struct ConfigResponse {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins and their accuracy
     pub coin_registry_address: Addr,
-
-    pub bonding_curve_factory_address: Option<Addr>,
-
-    pub pusd_denom: Option<String>,
+    /// Address allowed to pause pairs in addition to the owner
+    pub guardian: Option<Addr>,
+    /// The minimum time (in seconds) between proposing a timelocked change and executing it
+    pub timelock_delay: u64,
+    /// Address allowed to cancel timelocked changes in addition to the owner
+    pub canceller: Option<Addr>,
 }
 
 /// A custom struct for each query response that returns an array of objects of type [`PairInfo`].
@@ -226,6 +503,17 @@ pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
 
+/// A custom struct for each query response that returns pairs pending migration.
+#[cw_serde]
+pub struct PairsToMigrateResponse {
+    /// The type of pairs being migrated
+    pub pair_type: Option<PairType>,
+    /// The code ID the pairs are migrated to
+    pub new_code_id: Option<u64>,
+    /// Addresses of pairs that haven't been migrated yet
+    pub pairs: Vec<Addr>,
+}
+
 /// A custom struct for each query response that returns an object of type [`FeeInfoResponse`].
 #[cw_serde]
 pub struct FeeInfoResponse {
@@ -237,6 +525,15 @@ pub struct FeeInfoResponse {
     pub maker_fee_bps: u16,
 }
 
+/// This structure describes a migration message.
+#[cw_serde]
+pub struct MigrateMsg {
+    /// The timelock delay (in seconds) of factories migrated from version 0.1.0, which had no timelock
+    pub timelock_delay: Option<u64>,
+    /// Address allowed to cancel timelocked changes of factories migrated from version 0.1.0
+    pub canceller: Option<String>,
+}
+
 /// This is an enum used for setting and removing a contract address.
 #[cw_serde]
 pub enum UpdateAddr {
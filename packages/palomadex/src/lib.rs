@@ -6,6 +6,7 @@ pub mod native_coin_registry;
 pub mod observation;
 pub mod pair;
 pub mod querier;
+pub mod rate_provider;
 pub mod router;
 pub mod token;
 pub use decimal_checked_ops::DecimalCheckedOps;
@@ -2,6 +2,8 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Addr;
 use cw_storage_plus::Map;
 
+use crate::common::Role;
+
 /// This structure stores the main parameters for the native coin registry contract.
 #[cw_serde]
 pub struct Config {
@@ -21,12 +23,25 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Adds or updates native assets with specified precisions
     /// ## Executor
-    /// Only the current owner can execute this
+    /// Only the current owner or a [`Role::Lister`] can execute this.
+    /// A [`Role::Lister`] can only add assets which aren't registered yet
     Add { native_coins: Vec<(String, u8)> },
     /// Removes the native assets by specified parameters
     /// ## Executor
     /// Only the current owner can execute this
     Remove { native_coins: Vec<String> },
+    /// Grants a role to an account
+    /// ## Executor
+    /// Only the current owner can execute this
+    GrantRole { role: Role, account: String },
+    /// Revokes a role from an account
+    /// ## Executor
+    /// Only the current owner can execute this
+    RevokeRole { role: Role, account: String },
+    /// Gives up a role held by the sender
+    /// ## Executor
+    /// Only an account that has the role can execute this
+    RenounceRole { role: Role },
     /// Creates a request to change contract ownership
     /// ## Executor
     /// Only the current owner can execute this
@@ -62,6 +77,12 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Returns the accounts that were granted the specified role.
+    #[returns(Vec<Addr>)]
+    RoleMembers { role: Role },
+    /// Returns whether the specified account was granted the specified role.
+    #[returns(bool)]
+    HasRole { role: Role, account: String },
 }
 
 #[cw_serde]
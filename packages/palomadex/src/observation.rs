@@ -1,17 +1,41 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    CustomQuery, Decimal, Decimal256, Deps, Env, Fraction, StdError, StdResult, Storage, Uint128,
-    Uint256,
+    CustomQuery, Decimal, Decimal256, Deps, Env, Fraction, Order, StdError, StdResult, Storage,
+    Uint128, Uint256,
 };
 use cw_storage_plus::Item;
 
-use palomadex_circular_buffer::{BufferManager, CircularBuffer};
+use palomadex_circular_buffer::error::{BufferError, BufferResult};
+use palomadex_circular_buffer::{BufferManager, CircularBuffer, TimeSeries};
 
+use crate::asset::AssetInfo;
 use crate::cosmwasm_ext::AbsDiff;
 
 /// Circular buffer size which stores observations
 pub const OBSERVATIONS_SIZE: u32 = 3000;
 
+/// Default half-life of the price EMA in seconds
+pub const DEFAULT_EMA_HALF_LIFE: u64 = 3600;
+
+/// Half-life of the price EMA in seconds
+const EMA_HALF_LIFE: Item<u64> = Item::new("ema_half_life");
+
+/// Default number of candles returned by a query
+const CANDLES_DEFAULT_LIMIT: u32 = 10;
+/// Maximum number of candles returned by a query
+const CANDLES_MAX_LIMIT: u32 = 30;
+
+/// Default number of raw observations returned by a query
+const OBSERVATIONS_DEFAULT_LIMIT: u32 = 10;
+/// Maximum number of raw observations returned by a query
+const OBSERVATIONS_MAX_LIMIT: u32 = 100;
+
+/// Natural logarithm of 2 with 18 decimal places
+const LN_2: Decimal256 = Decimal256::raw(693_147_180_559_945_309);
+
+/// Number of Taylor series terms used to compute the EMA decay
+const EMA_DECAY_TERMS: u8 = 16;
+
 /// Stores trade size observations. We use it in orderbook integration
 /// and derive prices for external contracts/users.
 #[cw_serde]
@@ -23,6 +47,18 @@ pub struct Observation {
     pub price: Decimal,
     /// Price simple moving average (mean)
     pub price_sma: Decimal,
+    /// Price exponential moving average with the configured half-life
+    #[serde(default)]
+    pub price_ema: Decimal,
+    /// Cumulative sum of squared relative price changes between consecutive observations
+    #[serde(default)]
+    pub sq_returns_cumulative: Decimal256,
+}
+
+impl TimeSeries for Observation {
+    fn key(&self) -> u64 {
+        self.ts
+    }
 }
 
 #[cw_serde]
@@ -31,6 +67,157 @@ pub struct OracleObservation {
     pub price: Decimal,
 }
 
+impl OracleObservation {
+    /// Returns the observation with the base and quote assets swapped.
+    pub fn invert(self) -> StdResult<Self> {
+        Ok(Self {
+            price: self
+                .price
+                .inv()
+                .ok_or_else(|| StdError::generic_err("Observed price is zero"))?,
+            ..self
+        })
+    }
+}
+
+/// Time-weighted average price over a window of observations.
+#[cw_serde]
+pub struct TwapResponse {
+    /// Timestamp at which the window starts
+    pub start_timestamp: u64,
+    /// Timestamp at which the window ends
+    pub end_timestamp: u64,
+    /// Time-weighted average price over the window
+    pub price: Decimal,
+}
+
+/// Length of the time bucket aggregated into a single candle.
+#[cw_serde]
+#[derive(Copy)]
+pub enum CandleResolution {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl CandleResolution {
+    pub const ALL: [CandleResolution; 3] = [
+        CandleResolution::Minute,
+        CandleResolution::Hour,
+        CandleResolution::Day,
+    ];
+
+    /// Returns the bucket length in seconds.
+    pub const fn seconds(&self) -> u64 {
+        match self {
+            CandleResolution::Minute => 60,
+            CandleResolution::Hour => 3600,
+            CandleResolution::Day => 86400,
+        }
+    }
+
+    /// Returns the number of stored candles, which covers a day, a month and a year respectively.
+    pub const fn capacity(&self) -> u32 {
+        match self {
+            CandleResolution::Minute => 1440,
+            CandleResolution::Hour => 720,
+            CandleResolution::Day => 365,
+        }
+    }
+
+    /// Returns the circular buffer which stores candles of this resolution.
+    pub const fn buffer(&self) -> CircularBuffer<'static, Candle> {
+        match self {
+            CandleResolution::Minute => CircularBuffer::new("candles_1m_state", "candles_1m"),
+            CandleResolution::Hour => CircularBuffer::new("candles_1h_state", "candles_1h"),
+            CandleResolution::Day => CircularBuffer::new("candles_1d_state", "candles_1d"),
+        }
+    }
+}
+
+/// Open/high/low/close prices and traded volume over a time bucket.
+/// Prices are quoted the same way as in [`Observation`].
+#[cw_serde]
+#[derive(Copy)]
+pub struct Candle {
+    /// Timestamp at which the bucket starts
+    pub ts: u64,
+    /// Price of the first trade in the bucket
+    pub open: Decimal,
+    /// Highest trade price in the bucket
+    pub high: Decimal,
+    /// Lowest trade price in the bucket
+    pub low: Decimal,
+    /// Price of the last trade in the bucket
+    pub close: Decimal,
+    /// Traded amount of the base asset
+    pub volume: Uint128,
+}
+
+impl TimeSeries for Candle {
+    fn key(&self) -> u64 {
+        self.ts
+    }
+}
+
+#[cw_serde]
+pub struct CandlesResponse {
+    pub candles: Vec<Candle>,
+}
+
+/// Order in which paginated time series are returned.
+#[cw_serde]
+#[derive(Copy)]
+pub enum OrderBy {
+    Asc,
+    Desc,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Self {
+        match order {
+            OrderBy::Asc => Order::Ascending,
+            OrderBy::Desc => Order::Descending,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct ObservationsResponse {
+    pub observations: Vec<Observation>,
+}
+
+/// Price statistics over a window of observations.
+#[cw_serde]
+pub struct ObservationStatsResponse {
+    /// Timestamp of the observation the window starts from
+    pub start_timestamp: u64,
+    /// Timestamp of the latest observation
+    pub end_timestamp: u64,
+    /// Number of price changes observed within the window
+    pub returns_count: u32,
+    /// Latest price exponential moving average
+    pub price_ema: Decimal,
+    /// Half-life of the price EMA in seconds
+    pub ema_half_life: u64,
+    /// Mean squared relative price change within the window
+    pub variance: Decimal,
+    /// Realized volatility which is the square root of the variance
+    pub volatility: Decimal,
+}
+
+/// Returns the half-life of the price EMA in seconds.
+pub fn ema_half_life(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(EMA_HALF_LIFE
+        .may_load(storage)?
+        .unwrap_or(DEFAULT_EMA_HALF_LIFE))
+}
+
+/// Sets the half-life of the price EMA in seconds.
+pub fn set_ema_half_life(storage: &mut dyn Storage, half_life: u64) -> StdResult<()> {
+    EMA_HALF_LIFE.save(storage, &half_life)
+}
+
 /// Returns price observation at point that was 'seconds_ago' seconds ago.
 pub fn query_observation<C>(
     deps: Deps<C>,
@@ -44,14 +231,8 @@ where
     let buffer = BufferManager::new(deps.storage, observations)?;
     let target = env.block.time.seconds() - seconds_ago;
 
-    let mut oldest_ind = buffer.head();
-    let mut newest_ind = buffer.head() + buffer.capacity() - 1;
-
-    if !buffer.exists(deps.storage, oldest_ind) {
-        if buffer.head() > 0 {
-            oldest_ind = 0;
-            newest_ind %= buffer.capacity();
-        } else {
+    let (left, right) = match buffer.find_bounds(deps.storage, target)? {
+        (None, None) => {
             return match PrecommitObservation::may_load(deps.storage)? {
                 // First observation after pool initialization could be captured but not committed yet
                 Some(obs) if obs.precommit_ts <= target => Ok(OracleObservation {
@@ -65,38 +246,24 @@ where
                 None => Err(StdError::generic_err("Buffer is empty")),
             };
         }
-    }
-
-    let newest_obs = buffer.read_single(deps.storage, newest_ind)?.unwrap();
-    if target >= newest_obs.ts {
-        return Ok(OracleObservation {
-            timestamp: target,
-            price: newest_obs.price_sma,
-        });
-    }
-    let oldest_obs = buffer.read_single(deps.storage, oldest_ind)?.unwrap();
-    if target == oldest_obs.ts {
-        return Ok(OracleObservation {
-            timestamp: target,
-            price: oldest_obs.price_sma,
-        });
-    }
-    if target < oldest_obs.ts {
-        return Err(StdError::generic_err(format!(
-            "Requested observation is too old. Last known observation is at {}",
-            oldest_obs.ts
-        )));
-    }
-
-    let (left, right) = binary_search(deps.storage, &buffer, target, oldest_ind, newest_ind)?;
+        (None, Some(oldest_obs)) => {
+            return Err(StdError::generic_err(format!(
+                "Requested observation is too old. Last known observation is at {}",
+                oldest_obs.ts
+            )));
+        }
+        (Some(newest_obs), None) => {
+            return Ok(OracleObservation {
+                timestamp: target,
+                price: newest_obs.price_sma,
+            });
+        }
+        (Some(left), Some(right)) => (left, right),
+    };
 
     let price_left = left.price_sma;
     let price_right = right.price_sma;
-    let price = if left.ts == target {
-        price_left
-    } else if right.ts == target {
-        price_right
-    } else if price_left == price_right {
+    let price = if left.ts == target || price_left == price_right {
         price_left
     } else {
         // Interpolate.
@@ -116,39 +283,226 @@ where
     })
 }
 
-/// Performs binary search in circular buffer. Returns left and right bounds of target value.
-/// Either left or right bound may hit in target value.
-fn binary_search(
-    storage: &dyn Storage,
-    buffer: &BufferManager<Observation>,
-    target: u64,
-    mut start: u32,
-    mut end: u32,
-) -> StdResult<(Observation, Observation)> {
-    loop {
-        let mid = (start + end) / 2;
-
-        // We've checked bounds before calling this function thus these errors should be impossible.
-        let leftward_or_hit = buffer.read_single(storage, mid)?.ok_or_else(|| {
-            StdError::generic_err(format!(
-                "Unexpected error in binary_search: leftward_or_hit is None at index {mid}",
-            ))
-        })?;
-        let rightward_or_hit = buffer.read_single(storage, mid + 1)?.ok_or_else(|| {
-            StdError::generic_err(format!(
-                "Unexpected error in binary_search: rightward_or_hit is None at index {}",
-                mid + 1
-            ))
-        })?;
-
-        if leftward_or_hit.ts <= target && target <= rightward_or_hit.ts {
-            break Ok((leftward_or_hit, rightward_or_hit));
+/// Returns the time-weighted average price between 'start_seconds_ago' and 'end_seconds_ago'.
+/// Every observed price is considered to hold until the next observation, including the one
+/// which is precommitted but not saved to the buffer yet.
+pub fn query_twap<C>(
+    deps: Deps<C>,
+    env: Env,
+    observations: CircularBuffer<Observation>,
+    start_seconds_ago: u64,
+    end_seconds_ago: u64,
+) -> StdResult<TwapResponse>
+where
+    C: CustomQuery,
+{
+    if start_seconds_ago <= end_seconds_ago {
+        return Err(StdError::generic_err(
+            "start_seconds_ago must be greater than end_seconds_ago",
+        ));
+    }
+    let now = env.block.time.seconds();
+    let start = now.checked_sub(start_seconds_ago).ok_or_else(|| {
+        StdError::generic_err(format!(
+            "start_seconds_ago must not exceed the block time {now}"
+        ))
+    })?;
+    let end = now - end_seconds_ago;
+
+    let buffer = BufferManager::new(deps.storage, observations)?;
+    let oldest_obs = buffer.iter(deps.storage).next().transpose()?;
+
+    let precommit = PrecommitObservation::may_load(deps.storage)?;
+    let oldest_ts = match oldest_obs {
+        Some(obs) => Some(obs.ts),
+        None => precommit.as_ref().map(|obs| obs.precommit_ts),
+    };
+    match oldest_ts {
+        None => return Err(StdError::generic_err("Buffer is empty")),
+        Some(ts) if ts > start => {
+            return Err(StdError::generic_err(format!(
+                "Requested window is not covered by observations. Oldest observation is at {ts}"
+            )))
         }
-        if leftward_or_hit.ts > target {
-            end = mid - 1;
+        _ => {}
+    }
+
+    // The latest observation made no later than the window start holds at the start
+    let mut points = vec![];
+    if let (Some(obs), _) = buffer.find_bounds(deps.storage, start)? {
+        points.push((obs.ts, obs.price));
+    }
+    for obs in buffer.range_by_key(deps.storage, Some(start + 1), Some(end - 1))? {
+        let obs = obs?;
+        points.push((obs.ts, obs.price));
+    }
+    if let Some(obs) = precommit {
+        let last_ts = buffer.read_last(deps.storage)?.map(|last_obs| last_obs.ts);
+        if last_ts.is_none_or(|ts| obs.precommit_ts > ts) && obs.precommit_ts < end {
+            points.push((
+                obs.precommit_ts,
+                Decimal::from_ratio(obs.base_amount, obs.quote_amount),
+            ));
+        }
+    }
+
+    let next_timestamps = points.iter().skip(1).map(|(ts, _)| *ts).chain([end]);
+    let mut weighted_sum = Decimal256::zero();
+    for ((ts, price), next_ts) in points.iter().zip(next_timestamps) {
+        let from = (*ts).max(start);
+        let to = next_ts.min(end);
+        if to > from {
+            weighted_sum += Decimal256::from(*price) * Decimal256::from_ratio(to - from, 1u8);
+        }
+    }
+
+    Ok(TwapResponse {
+        start_timestamp: start,
+        end_timestamp: end,
+        price: try_dec256_into_dec(weighted_sum / Decimal256::from_ratio(end - start, 1u8))?,
+    })
+}
+
+/// Returns realized volatility and the latest price EMA over the last 'window_seconds' seconds.
+/// Only observations saved to the buffer are considered.
+pub fn query_observation_stats<C>(
+    deps: Deps<C>,
+    env: Env,
+    observations: CircularBuffer<Observation>,
+    window_seconds: u64,
+) -> StdResult<ObservationStatsResponse>
+where
+    C: CustomQuery,
+{
+    if window_seconds == 0 {
+        return Err(StdError::generic_err(
+            "window_seconds must be greater than zero",
+        ));
+    }
+    let now = env.block.time.seconds();
+    let start = now.checked_sub(window_seconds).ok_or_else(|| {
+        StdError::generic_err(format!(
+            "window_seconds must not exceed the block time {now}"
+        ))
+    })?;
+
+    let buffer = BufferManager::new(deps.storage, observations)?;
+    // The latest observation made no later than the window start is the base for accumulators
+    let base_obs = match buffer.find_bounds(deps.storage, start)? {
+        (Some(obs), _) => obs,
+        (None, Some(oldest_obs)) => {
+            return Err(StdError::generic_err(format!(
+                "Requested window is not covered by observations. Oldest observation is at {}",
+                oldest_obs.ts
+            )))
+        }
+        (None, None) => return Err(StdError::generic_err("Buffer is empty")),
+    };
+
+    let mut returns = buffer.range_by_key(deps.storage, Some(start + 1), None)?;
+    let returns_count = returns.len() as u32;
+    let last_obs = returns.next_back().transpose()?.unwrap_or(base_obs);
+    let variance = if returns_count == 0 {
+        Decimal256::zero()
+    } else {
+        (last_obs.sq_returns_cumulative - base_obs.sq_returns_cumulative)
+            / Decimal256::from_ratio(returns_count, 1u8)
+    };
+
+    Ok(ObservationStatsResponse {
+        start_timestamp: base_obs.ts,
+        end_timestamp: last_obs.ts,
+        returns_count,
+        // Observations saved before the EMA was introduced have it unset
+        price_ema: if last_obs.price_ema.is_zero() {
+            last_obs.price
         } else {
-            start = mid + 1;
+            last_obs.price_ema
+        },
+        ema_half_life: ema_half_life(deps.storage)?,
+        variance: try_dec256_into_dec(variance)?,
+        volatility: try_dec256_into_dec(variance.sqrt())?,
+    })
+}
+
+/// Returns candles of the given resolution in chronological order, starting after the bucket
+/// which starts at 'start_after'.
+pub fn query_candles<C>(
+    deps: Deps<C>,
+    resolution: CandleResolution,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CandlesResponse>
+where
+    C: CustomQuery,
+{
+    let limit = limit
+        .unwrap_or(CANDLES_DEFAULT_LIMIT)
+        .min(CANDLES_MAX_LIMIT);
+    let buffer = BufferManager::new(deps.storage, resolution.buffer())?;
+    let candles = buffer.paginate(deps.storage, start_after, limit as usize, Order::Ascending)?;
+
+    Ok(CandlesResponse { candles })
+}
+
+/// Returns raw observations saved to the buffer, starting after the one made at 'start_after_ts'.
+/// Observations are returned in chronological order unless 'order' is [`OrderBy::Desc`].
+pub fn query_observations<C>(
+    deps: Deps<C>,
+    observations: CircularBuffer<Observation>,
+    start_after_ts: Option<u64>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+) -> StdResult<ObservationsResponse>
+where
+    C: CustomQuery,
+{
+    let limit = limit
+        .unwrap_or(OBSERVATIONS_DEFAULT_LIMIT)
+        .min(OBSERVATIONS_MAX_LIMIT);
+    let buffer = BufferManager::new(deps.storage, observations)?;
+    let observations = buffer.paginate(
+        deps.storage,
+        start_after_ts,
+        limit as usize,
+        order.unwrap_or(OrderBy::Asc).into(),
+    )?;
+
+    Ok(ObservationsResponse { observations })
+}
+
+/// Returns pool indexes of the requested base and quote assets.
+/// If neither of them is specified, the first asset of the pool is the base one and the second one is the quote one.
+pub fn observation_asset_indexes(
+    asset_infos: &[AssetInfo],
+    base_asset: Option<AssetInfo>,
+    quote_asset: Option<AssetInfo>,
+) -> StdResult<(usize, usize)> {
+    let position = |asset_info: AssetInfo| {
+        asset_infos
+            .iter()
+            .position(|info| *info == asset_info)
+            .ok_or_else(|| {
+                StdError::generic_err(format!(
+                    "The asset {asset_info} does not belong to the pair"
+                ))
+            })
+    };
+
+    match (base_asset, quote_asset) {
+        (None, None) => Ok((0, 1)),
+        (Some(base_asset), Some(quote_asset)) => {
+            let indexes = (position(base_asset)?, position(quote_asset)?);
+            if indexes.0 == indexes.1 {
+                return Err(StdError::generic_err(
+                    "Base and quote assets must be different",
+                ));
+            }
+            Ok(indexes)
         }
+        _ => Err(StdError::generic_err(
+            "base_asset and quote_asset must be specified together",
+        )),
     }
 }
 
@@ -193,6 +547,158 @@ impl<'a> PrecommitObservation {
     }
 }
 
+/// Commits the pending [`PrecommitObservation`] into the `observations` buffer and updates the price moving average.
+pub fn accumulate_swap_sizes(
+    storage: &mut dyn Storage,
+    env: &Env,
+    observations: CircularBuffer<Observation>,
+) -> BufferResult<()> {
+    if let Some(PrecommitObservation {
+        base_amount,
+        quote_amount,
+        precommit_ts,
+    }) = PrecommitObservation::may_load(storage)?
+    {
+        let mut buffer = BufferManager::new(storage, observations)?;
+        let observed_price = Decimal::from_ratio(base_amount, quote_amount);
+
+        let new_observation;
+        if let Some(last_obs) = buffer.read_last(storage)? {
+            // Skip saving observation if it has been already saved
+            if last_obs.ts < precommit_ts {
+                // Since this is circular buffer the next index contains the oldest value
+                let count = buffer.capacity();
+                let price_sma =
+                    if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
+                        safe_sma_calculation(
+                            last_obs.price_sma,
+                            oldest_obs.price,
+                            count,
+                            observed_price,
+                        )?
+                    } else {
+                        // Buffer is not full yet
+                        let count = buffer.head();
+                        safe_sma_buffer_not_full(last_obs.price_sma, count, observed_price)?
+                    };
+                new_observation = Observation {
+                    ts: precommit_ts,
+                    price: observed_price,
+                    price_sma,
+                    price_ema: safe_ema_calculation(
+                        &last_obs,
+                        precommit_ts,
+                        observed_price,
+                        ema_half_life(storage)?,
+                    )?,
+                    sq_returns_cumulative: last_obs.sq_returns_cumulative
+                        + squared_return(last_obs.price, observed_price),
+                };
+
+                buffer.instant_push(storage, &new_observation)?
+            }
+        } else {
+            // Buffer is empty
+            if env.block.time.seconds() > precommit_ts {
+                new_observation = Observation {
+                    ts: precommit_ts,
+                    price: observed_price,
+                    price_sma: observed_price,
+                    price_ema: observed_price,
+                    sq_returns_cumulative: Decimal256::zero(),
+                };
+
+                buffer.instant_push(storage, &new_observation)?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes the capacity of the `observations` buffer keeping the latest observations.
+/// The moving average of the latest observation is recalculated over the kept ones,
+/// so the next observations continue the average over the new capacity.
+pub fn resize_observations(
+    storage: &mut dyn Storage,
+    observations: CircularBuffer<Observation>,
+    capacity: u32,
+) -> BufferResult<()> {
+    let mut buffer = BufferManager::new(storage, observations)?;
+    buffer.resize(storage, capacity)?;
+
+    // Observations are kept in chronological order starting from the first slot
+    let kept = buffer.read_all(storage)?;
+    if let Some(last_obs) = kept.last() {
+        let prices_sum = kept.iter().fold(Decimal256::zero(), |acc, obs| {
+            acc + Decimal256::from(obs.price)
+        });
+        let new_last_obs = Observation {
+            price_sma: try_dec256_into_dec(
+                prices_sum / Decimal256::from_ratio(kept.len() as u128, 1u8),
+            )?,
+            ..*last_obs
+        };
+        buffer.instant_update_last(storage, &new_last_obs)?;
+    }
+
+    Ok(())
+}
+
+/// Initializes candle buffers of every resolution which are not initialized yet.
+pub fn init_candles(storage: &mut dyn Storage) -> BufferResult<()> {
+    for resolution in CandleResolution::ALL {
+        match BufferManager::init(storage, resolution.buffer(), resolution.capacity()) {
+            Ok(()) | Err(BufferError::BufferAlreadyInitialized {}) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Records a trade of `base_amount` for `quote_amount` in the current candle of every resolution.
+pub fn update_candles(
+    storage: &mut dyn Storage,
+    env: &Env,
+    base_amount: Uint128,
+    quote_amount: Uint128,
+) -> BufferResult<()> {
+    let price = Decimal::from_ratio(base_amount, quote_amount);
+    let now = env.block.time.seconds();
+
+    for resolution in CandleResolution::ALL {
+        let mut buffer = BufferManager::new(storage, resolution.buffer())?;
+        let bucket_ts = now - now % resolution.seconds();
+
+        match buffer.read_last(storage)? {
+            Some(mut candle) if candle.ts == bucket_ts => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume = candle
+                    .volume
+                    .checked_add(base_amount)
+                    .map_err(StdError::from)?;
+                buffer.instant_update_last(storage, &candle)?;
+            }
+            _ => {
+                let candle = Candle {
+                    ts: bucket_ts,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: base_amount,
+                };
+                buffer.instant_push(storage, &candle)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn try_dec256_into_dec(val: Decimal256) -> StdResult<Decimal> {
     let numerator: Uint128 = val.numerator().try_into()?;
 
@@ -218,6 +724,61 @@ pub fn safe_sma_calculation(
     try_dec256_into_dec(res)
 }
 
+/// Internal function to calculate new exponential moving average using Decimal256.
+/// The previous average keeps the weight of 2^(-elapsed / half_life).
+/// Formula: ema * decay + new_price * (1 - decay)
+pub fn safe_ema_calculation(
+    last_obs: &Observation,
+    ts: u64,
+    new_price: Decimal,
+    half_life: u64,
+) -> StdResult<Decimal> {
+    // Observations saved before the EMA was introduced have it unset
+    if last_obs.price_ema.is_zero() {
+        return Ok(new_price);
+    }
+
+    let decay = ema_decay(ts - last_obs.ts, half_life);
+    try_dec256_into_dec(
+        Decimal256::from(last_obs.price_ema) * decay
+            + Decimal256::from(new_price) * (Decimal256::one() - decay),
+    )
+}
+
+/// Returns 2^(-elapsed / half_life). The fractional part of the exponent is computed as
+/// exp(-f * ln 2) using its Taylor series.
+fn ema_decay(elapsed: u64, half_life: u64) -> Decimal256 {
+    let halvings = elapsed / half_life;
+    // 2^(-64) is already below Decimal256 precision
+    if halvings >= 64 {
+        return Decimal256::zero();
+    }
+
+    let x = Decimal256::from_ratio(elapsed % half_life, half_life) * LN_2;
+    let mut term = Decimal256::one();
+    let mut decay = Decimal256::one();
+    for i in 1..=EMA_DECAY_TERMS {
+        term = term * x / Decimal256::from_ratio(i, 1u8);
+        if i % 2 == 1 {
+            decay -= term;
+        } else {
+            decay += term;
+        }
+    }
+
+    decay / Decimal256::from_ratio(1u64 << halvings, 1u8)
+}
+
+/// Returns the squared relative change between two consecutive prices.
+fn squared_return(prev_price: Decimal, price: Decimal) -> Decimal256 {
+    if prev_price.is_zero() {
+        return Decimal256::zero();
+    }
+
+    let relative_change = Decimal256::from(price.diff(prev_price)) / Decimal256::from(prev_price);
+    relative_change * relative_change
+}
+
 /// Same as [`safe_sma_calculation`] but is being used when buffer is not full yet.
 /// Formula: (sma * count + new_price) / (count + 1)
 pub fn safe_sma_buffer_not_full(
@@ -236,9 +797,18 @@ pub fn safe_sma_buffer_not_full(
 
 #[cfg(test)]
 mod test {
-    use cosmwasm_std::to_json_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{from_json, to_json_binary, Decimal, Decimal256, StdError, Timestamp};
+    use palomadex_circular_buffer::{BufferManager, CircularBuffer};
+
+    use crate::observation::{
+        accumulate_swap_sizes, ema_decay, init_candles, query_candles, query_observation_stats,
+        set_ema_half_life, update_candles, Candle, CandleResolution, Observation,
+        ObservationStatsResponse, PrecommitObservation,
+    };
 
-    use crate::observation::Observation;
+    const OBSERVATIONS: CircularBuffer<Observation> =
+        CircularBuffer::new("observations_state", "observations_buffer");
 
     #[test]
     fn check_observation_size() {
@@ -248,13 +818,176 @@ mod test {
             ts: 0,
             price: Default::default(),
             price_sma: Default::default(),
+            price_ema: Default::default(),
+            sq_returns_cumulative: Default::default(),
         };
 
         let storage_bytes = to_json_binary(&obs).unwrap().len();
-        assert_eq!(storage_bytes, 36); // in storage
+        assert_eq!(storage_bytes, 80); // in storage
 
         // https://github.com/cosmos/cosmos-sdk/blob/47f46643affd7ec7978329c42bac47275ac7e1cc/store/types/gas.go#L199
         println!("sdk gas cost per read {}", 1000 + storage_bytes * 3);
         println!("sdk gas cost per write {}", 2000 + storage_bytes * 30)
     }
+
+    #[test]
+    fn legacy_observation_deserializes() {
+        let obs: Observation =
+            from_json(br#"{"ts":1,"price":"2","price_sma":"3"}"#.as_slice()).unwrap();
+        assert_eq!(
+            obs,
+            Observation {
+                ts: 1,
+                price: Decimal::from_ratio(2u8, 1u8),
+                price_sma: Decimal::from_ratio(3u8, 1u8),
+                price_ema: Decimal::zero(),
+                sq_returns_cumulative: Decimal256::zero(),
+            }
+        );
+    }
+
+    #[test]
+    fn ema_decay_halves_every_half_life() {
+        assert_eq!(ema_decay(0, 3600), Decimal256::one());
+        assert_eq!(ema_decay(3600, 3600), Decimal256::percent(50));
+        assert_eq!(ema_decay(7200, 3600), Decimal256::percent(25));
+        assert_eq!(ema_decay(3600 * 64, 3600), Decimal256::zero());
+
+        // 2^(-0.5) = 0.707106781186547524...
+        let diff = ema_decay(1800, 3600).abs_diff(Decimal256::raw(707_106_781_186_547_524));
+        assert!(diff <= Decimal256::raw(10), "{diff}");
+    }
+
+    #[test]
+    fn observation_stats_track_ema_and_volatility() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 10).unwrap();
+        set_ema_half_life(&mut deps.storage, 1000).unwrap();
+
+        let err =
+            query_observation_stats(deps.as_ref(), env.clone(), OBSERVATIONS, 100).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Buffer is empty"));
+
+        // Prices of 1, 2 and 1 are observed with a 1000 seconds interval
+        for (ts, base_amount) in [(1000, 1u128), (2000, 2), (3000, 1)] {
+            env.block.time = Timestamp::from_seconds(ts);
+            accumulate_swap_sizes(&mut deps.storage, &env, OBSERVATIONS).unwrap();
+            PrecommitObservation::save(&mut deps.storage, &env, base_amount.into(), 1u8.into())
+                .unwrap();
+        }
+        env.block.time = Timestamp::from_seconds(4000);
+        accumulate_swap_sizes(&mut deps.storage, &env, OBSERVATIONS).unwrap();
+
+        assert_eq!(
+            query_observation_stats(deps.as_ref(), env.clone(), OBSERVATIONS, 3000).unwrap(),
+            ObservationStatsResponse {
+                start_timestamp: 1000,
+                end_timestamp: 3000,
+                returns_count: 2,
+                // 1 -> 1 * 0.5 + 2 * 0.5 -> 1.5 * 0.5 + 1 * 0.5
+                price_ema: Decimal::percent(125),
+                ema_half_life: 1000,
+                // (1^2 + 0.5^2) / 2
+                variance: Decimal::permille(625),
+                volatility: Decimal::raw(790_569_415_042_094_832),
+            }
+        );
+
+        let stats =
+            query_observation_stats(deps.as_ref(), env.clone(), OBSERVATIONS, 2000).unwrap();
+        assert_eq!(stats.start_timestamp, 2000);
+        assert_eq!(stats.returns_count, 1);
+        assert_eq!(stats.variance, Decimal::percent(25));
+        assert_eq!(stats.volatility, Decimal::percent(50));
+
+        let err = query_observation_stats(deps.as_ref(), env, OBSERVATIONS, 3001).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(
+                "Requested window is not covered by observations. Oldest observation is at 1000"
+            )
+        );
+    }
+
+    #[test]
+    fn candles_aggregate_trades_per_bucket() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        init_candles(&mut deps.storage).unwrap();
+        // Initialization is idempotent so it can be called during migrations
+        init_candles(&mut deps.storage).unwrap();
+
+        // Prices of 2, 4 and 1 in the third minute and 3 in the fourth one
+        for (ts, base_amount, quote_amount) in [
+            (120, 10u128, 5u128),
+            (150, 40, 10),
+            (170, 5, 5),
+            (200, 30, 10),
+        ] {
+            env.block.time = Timestamp::from_seconds(ts);
+            update_candles(
+                &mut deps.storage,
+                &env,
+                base_amount.into(),
+                quote_amount.into(),
+            )
+            .unwrap();
+        }
+
+        let price = |value: u64| Decimal::from_ratio(value, 1u8);
+        let minute_candles = vec![
+            Candle {
+                ts: 120,
+                open: price(2),
+                high: price(4),
+                low: price(1),
+                close: price(1),
+                volume: 55u128.into(),
+            },
+            Candle {
+                ts: 180,
+                open: price(3),
+                high: price(3),
+                low: price(3),
+                close: price(3),
+                volume: 30u128.into(),
+            },
+        ];
+        assert_eq!(
+            query_candles(deps.as_ref(), CandleResolution::Minute, None, None)
+                .unwrap()
+                .candles,
+            minute_candles
+        );
+        assert_eq!(
+            query_candles(deps.as_ref(), CandleResolution::Minute, None, Some(1))
+                .unwrap()
+                .candles,
+            minute_candles[..1]
+        );
+        assert_eq!(
+            query_candles(deps.as_ref(), CandleResolution::Minute, Some(120), None)
+                .unwrap()
+                .candles,
+            minute_candles[1..]
+        );
+
+        let hour_candle = Candle {
+            ts: 0,
+            open: price(2),
+            high: price(4),
+            low: price(1),
+            close: price(3),
+            volume: 85u128.into(),
+        };
+        for resolution in [CandleResolution::Hour, CandleResolution::Day] {
+            assert_eq!(
+                query_candles(deps.as_ref(), resolution, None, None)
+                    .unwrap()
+                    .candles,
+                vec![hour_candle]
+            );
+        }
+    }
 }
@@ -1,12 +1,16 @@
 #[allow(unused_imports)]
-use crate::observation::OracleObservation;
+use crate::observation::{
+    CandleResolution, CandlesResponse, ObservationStatsResponse, ObservationsResponse,
+    OracleObservation, OrderBy, TwapResponse,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
 #[allow(unused_imports)]
 use crate::asset::{Asset, AssetInfo, PairInfo};
 
-use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, Env, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
+use thiserror::Error;
 
 /// The default swap slippage
 pub const DEFAULT_SLIPPAGE: &str = "0.005";
@@ -48,6 +52,12 @@ pub enum ExecuteMsg {
         slippage_tolerance: Option<Decimal>,
         /// The receiver of LP tokens
         receiver: Option<String>,
+        /// The minimum amount of LP tokens the provider is willing to receive
+        #[serde(default)]
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the provision is rejected
+        #[serde(default)]
+        deadline: Option<u64>,
     },
     /// Swap performs a swap in the pool
     Swap {
@@ -56,6 +66,20 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The block time (in seconds) after which the swap is rejected
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// SwapExactOut swaps just enough of the offer asset to receive `ask_asset`
+    SwapExactOut {
+        /// The asset and amount to receive
+        ask_asset: Asset,
+        /// The native asset sent for the swap. The part that is not needed is refunded
+        max_offer: Asset,
+        to: Option<String>,
+        /// The block time (in seconds) after which the swap is rejected
+        #[serde(default)]
+        deadline: Option<u64>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
@@ -70,12 +94,30 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The block time (in seconds) after which the swap is rejected
+        #[serde(default)]
+        deadline: Option<u64>,
+    },
+    /// Swap just enough of the sent tokens to receive `ask_asset`. The tokens that were not
+    /// needed are returned to the sender
+    SwapExactOut {
+        ask_asset: Asset,
+        to: Option<String>,
+        /// The block time (in seconds) after which the swap is rejected
+        #[serde(default)]
+        deadline: Option<u64>,
     },
-    /// Withdraw liquidity from the pool
+    /// Withdraw liquidity from the pool. If `assets` is not empty, only the specified assets
+    /// are withdrawn and the LP tokens that were not needed are returned to the sender
     WithdrawLiquidity {
         #[serde(default)]
         assets: Vec<Asset>,
     },
+    /// Withdraw liquidity from the pool in a single asset
+    WithdrawLiquidityOneCoin {
+        ask_asset_info: AssetInfo,
+        min_return: Option<Uint128>,
+    },
 }
 
 /// This structure describes the query messages available in the contract.
@@ -118,11 +160,59 @@ pub enum QueryMsg {
         asset_info: AssetInfo,
         block_height: Uint64,
     },
-    /// Query price from observations
+    /// Returns the amount of LP tokens minted and the fee charged for a deposit in a [`SimulateProvideResponse`] object
+    #[returns(SimulateProvideResponse)]
+    SimulateProvide { assets: Vec<Asset> },
+    /// Returns the amount of LP tokens that must be burned to withdraw the specified assets
+    #[returns(Uint128)]
+    SimulateWithdraw { assets: Vec<Asset> },
+    /// Returns the amount of an asset received when burning LP tokens for that asset only
+    #[returns(Uint128)]
+    SimulateWithdrawOneCoin {
+        amount: Uint128,
+        ask_asset_info: AssetInfo,
+    },
+    /// Query price from observations.
+    /// Returns the price of `quote_asset` denominated in `base_asset`, i.e. the amount of `base_asset`
+    /// paid per one `quote_asset`. Defaults to the first pool asset as the base one and the second one as the quote one
     #[returns(OracleObservation)]
-    Observe { seconds_ago: u64 },
+    Observe {
+        seconds_ago: u64,
+        base_asset: Option<AssetInfo>,
+        quote_asset: Option<AssetInfo>,
+    },
+    /// Query the time-weighted average price between two points in time
+    #[returns(TwapResponse)]
+    Twap {
+        start_seconds_ago: u64,
+        end_seconds_ago: u64,
+    },
+    /// Query the price EMA and realized volatility over the last `window_seconds` seconds
+    #[returns(ObservationStatsResponse)]
+    ObservationStats { window_seconds: u64 },
+    /// Query OHLC candles of the given resolution in chronological order
+    #[returns(CandlesResponse)]
+    Candles {
+        resolution: CandleResolution,
+        /// The bucket start timestamp to start reading after
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Query raw observations saved by the pool, in chronological order by default
+    #[returns(ObservationsResponse)]
+    Observations {
+        /// The observation timestamp to start reading after
+        start_after_ts: Option<u64>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
 }
 
+/// This structure describes a migration message.
+/// We currently take no arguments for migrations.
+#[cw_serde]
+pub struct MigrateMsg {}
+
 /// This struct is used to return a query result with the total amount of LP tokens and assets in a specific pool.
 #[cw_serde]
 pub struct PoolResponse {
@@ -176,6 +266,15 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
+/// This structure holds the parameters that are returned from a provide simulation response
+#[cw_serde]
+pub struct SimulateProvideResponse {
+    /// The amount of LP tokens minted for the deposit
+    pub share: Uint128,
+    /// The imbalance fee charged on each asset of the deposit
+    pub fees: Vec<Asset>,
+}
+
 /// This structure is used to return a cumulative prices query response.
 #[cw_serde]
 pub struct CumulativePricesResponse {
@@ -218,6 +317,10 @@ pub enum XYKPoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Sets the half-life of the observed price EMA in seconds.
+    SetEmaHalfLife {
+        ema_half_life: u64,
+    },
 }
 
 /// This structure holds stableswap pool parameters.
@@ -227,6 +330,21 @@ pub struct StablePoolParams {
     pub amp: u64,
     /// The contract owner
     pub owner: Option<String>,
+    /// The rate providers of yield-bearing pool assets.
+    /// Balances of assets without a rate provider are taken at face value
+    pub rate_providers: Option<Vec<RateProviderParams>>,
+}
+
+/// This structure describes a contract which reports the exchange rate of a pool asset.
+/// Pool balances are scaled by the rate before the stableswap invariant is computed.
+#[cw_serde]
+pub struct RateProviderParams {
+    /// The pool asset whose exchange rate is reported
+    pub asset_info: AssetInfo,
+    /// The rate provider contract address
+    pub contract_addr: String,
+    /// The maximum age (in seconds) of a rate that the pool accepts, must be greater than zero
+    pub max_staleness: u64,
 }
 
 /// This structure stores a stableswap pool's configuration.
@@ -236,6 +354,8 @@ pub struct StablePoolConfig {
     pub amp: Decimal,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The rate providers of yield-bearing pool assets
+    pub rate_providers: Vec<RateProviderParams>,
 }
 
 /// This enum stores the options available to start and stop changing a stableswap pool's amplification.
@@ -254,13 +374,51 @@ pub enum StablePoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Sets the half-life of the observed price EMA in seconds.
+    SetEmaHalfLife {
+        ema_half_life: u64,
+    },
+    /// Changes the number of stored price observations.
+    ResizeObservations {
+        capacity: u32,
+    },
+    /// Sets the rate provider of a pool asset, replacing the current one if it exists.
+    /// Only accepted from the factory, the new provider must report a fresh nonzero rate.
+    SetRateProvider {
+        provider: RateProviderParams,
+    },
+    /// Removes the rate provider of a pool asset. Only accepted from the factory.
+    RemoveRateProvider {
+        asset_info: AssetInfo,
+    },
+}
+
+/// This error is returned when a swap or a liquidity provision lands after its deadline.
+#[derive(Error, Debug, PartialEq)]
+#[error("Deadline of {deadline} has expired, the block time is {block_time}")]
+pub struct DeadlineExpired {
+    pub deadline: u64,
+    pub block_time: u64,
+}
+
+/// Rejects a transaction once the block time has passed its optional `deadline` (in seconds).
+pub fn check_deadline(env: &Env, deadline: Option<u64>) -> Result<(), DeadlineExpired> {
+    let block_time = env.block.time.seconds();
+    match deadline {
+        Some(deadline) if block_time > deadline => Err(DeadlineExpired {
+            deadline,
+            block_time,
+        }),
+        _ => Ok(()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::asset::native_asset_info;
-    use cosmwasm_std::{from_json, to_json_binary};
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::{from_json, to_json_binary, Timestamp};
 
     #[cw_serde]
     pub struct LegacyInstantiateMsg {
@@ -303,6 +461,7 @@ mod tests {
                 to_json_binary(&StablePoolConfig {
                     amp: Decimal::one(),
                     fee_share: None,
+                    rate_providers: vec![],
                 })
                 .unwrap(),
             ),
@@ -314,6 +473,33 @@ mod tests {
         let _: ConfigResponse = from_json(&ser_msg).unwrap();
     }
 
+    #[test]
+    fn check_deadline_is_optional() {
+        let variant: Cw20HookMsg = from_json(br#"{"swap": {}}"#).unwrap();
+        assert_eq!(
+            variant,
+            Cw20HookMsg::Swap {
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: None,
+                to: None,
+                deadline: None,
+            }
+        );
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100);
+        check_deadline(&env, None).unwrap();
+        check_deadline(&env, Some(100)).unwrap();
+        assert_eq!(
+            check_deadline(&env, Some(99)).unwrap_err(),
+            DeadlineExpired {
+                deadline: 99,
+                block_time: 100,
+            }
+        );
+    }
+
     #[test]
     fn check_empty_vec_deserialization() {
         let variant: Cw20HookMsg = from_json(br#"{"withdraw_liquidity": {} }"#).unwrap();
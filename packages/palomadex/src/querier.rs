@@ -1,4 +1,5 @@
 use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::common::Role;
 use crate::factory::{
     Config as FactoryConfig, FeeInfoResponse, PairType, PairsResponse, QueryMsg as FactoryQueryMsg,
 };
@@ -161,19 +162,27 @@ pub struct FeeInfo {
     pub maker_fee_rate: Decimal,
 }
 
-/// Returns the fee information for a specific pair type.
+/// Returns the fee information for a specific pair.
 ///
 /// * **pair_type** pair type we query information for.
+///
+/// * **pair_addr** pair we query information for. Its fee override takes precedence over the pair type fees.
 pub fn query_fee_info<C>(
     querier: &QuerierWrapper<C>,
     factory_contract: impl Into<String>,
     pair_type: PairType,
+    pair_addr: impl Into<String>,
 ) -> StdResult<FeeInfo>
 where
     C: CustomQuery,
 {
-    let res: FeeInfoResponse =
-        querier.query_wasm_smart(factory_contract, &FactoryQueryMsg::FeeInfo { pair_type })?;
+    let res: FeeInfoResponse = querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::FeeInfo {
+            pair_type,
+            pair_addr: Some(pair_addr.into()),
+        },
+    )?;
 
     Ok(FeeInfo {
         fee_address: res.fee_address,
@@ -182,6 +191,46 @@ where
     })
 }
 
+/// Returns whether swaps and liquidity provision are paused in a specific pair.
+///
+/// * **pair_addr** pair we query the pause status for.
+pub fn query_pair_paused<C>(
+    querier: &QuerierWrapper<C>,
+    factory_contract: impl Into<String>,
+    pair_addr: impl Into<String>,
+) -> StdResult<bool>
+where
+    C: CustomQuery,
+{
+    querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::IsPaused {
+            pair_addr: pair_addr.into(),
+        },
+    )
+}
+
+/// Returns whether an account was granted a role in the factory.
+///
+/// * **account** account we check the role for.
+pub fn query_factory_has_role<C>(
+    querier: &QuerierWrapper<C>,
+    factory_contract: impl Into<String>,
+    role: Role,
+    account: impl Into<String>,
+) -> StdResult<bool>
+where
+    C: CustomQuery,
+{
+    querier.query_wasm_smart(
+        factory_contract,
+        &FactoryQueryMsg::HasRole {
+            role,
+            account: account.into(),
+        },
+    )
+}
+
 /// Accepts two tokens as input and returns a pair's information.
 pub fn query_pair_info(
     querier: &QuerierWrapper,
@@ -192,6 +241,7 @@ pub fn query_pair_info(
         factory_contract,
         &FactoryQueryMsg::Pair {
             asset_infos: asset_infos.to_vec(),
+            pair_type: None,
         },
     )
 }
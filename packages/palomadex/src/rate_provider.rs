@@ -0,0 +1,22 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Decimal;
+
+/// This structure describes the query messages a rate provider contract must answer.
+/// A rate provider reports the exchange rate of a yield-bearing asset, such as a liquid staking token,
+/// to its underlying asset.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Returns the current exchange rate of the asset in an [`ExchangeRateResponse`] object.
+    #[returns(ExchangeRateResponse)]
+    ExchangeRate {},
+}
+
+/// This structure holds the parameters that are returned from an exchange rate query.
+#[cw_serde]
+pub struct ExchangeRateResponse {
+    /// The amount of the underlying asset one unit of the asset is worth
+    pub rate: Decimal,
+    /// The timestamp (in seconds) when the rate was last updated
+    pub updated_at: u64,
+}
@@ -55,6 +55,9 @@ pub enum ExecuteMsg {
         minimum_receive: Option<Uint128>,
         to: Option<String>,
         max_spread: Option<Decimal>,
+        /// The block time (in seconds) after which the swaps are rejected
+        #[serde(default)]
+        deadline: Option<u64>,
     },
 
     /// Internal use
@@ -83,6 +86,9 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         /// Max spread
         max_spread: Option<Decimal>,
+        /// The block time (in seconds) after which the swaps are rejected
+        #[serde(default)]
+        deadline: Option<u64>,
     },
 }
 
@@ -112,6 +112,8 @@ where
                     generator_address: None,
                     whitelist_code_id,
                     coin_registry_address: coin_registry.address.to_string(),
+                    timelock_delay: 86_400,
+                    canceller: None,
                 },
                 &[],
                 "Palomadex Factory",
@@ -197,6 +199,7 @@ where
                 &self.address,
                 &QueryMsg::Pair {
                     asset_infos: asset_infos.to_vec(),
+                    pair_type: Some(PairType::Xyk {}),
                 },
             )
             .unwrap();
@@ -218,6 +221,7 @@ where
         let default_params = StablePoolParams {
             amp: 100,
             owner: Some(palomadex.to_string()),
+            rate_providers: None,
         };
 
         self.app
@@ -244,6 +248,7 @@ where
                 &self.address,
                 &QueryMsg::Pair {
                     asset_infos: asset_infos.to_vec(),
+                    pair_type: Some(PairType::Stable {}),
                 },
             )
             .unwrap();
@@ -276,6 +281,7 @@ where
             self.address.clone(),
             &ExecuteMsg::Deregister {
                 asset_infos: asset_infos.to_vec(),
+                pair_type: None,
             },
             &[],
         )
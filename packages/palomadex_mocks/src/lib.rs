@@ -11,6 +11,7 @@ pub use {
     factory::{MockFactory, MockFactoryBuilder},
     pair::{MockXykPair, MockXykPairBuilder},
     pair_stable::{MockStablePair, MockStablePairBuilder},
+    rate_provider::{MockRateProvider, MockRateProviderBuilder},
     token::{MockToken, MockTokenBuilder},
 };
 
@@ -18,6 +19,7 @@ pub mod coin_registry;
 pub mod factory;
 pub mod pair;
 pub mod pair_stable;
+pub mod rate_provider;
 pub mod token;
 pub mod whitelist;
 
@@ -149,6 +149,8 @@ where
                     assets: assets.into(),
                     slippage_tolerance,
                     receiver: receiver.into(),
+                    min_lp_to_receive: None,
+                    deadline: None,
                 },
                 &coins,
             )
@@ -0,0 +1,180 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_json_binary, Addr, Api, Binary, CustomMsg, CustomQuery, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Response, StdResult, Storage,
+};
+use cw_multi_test::{
+    Bank, ContractWrapper, Distribution, Executor, Gov, Ibc, Module, Staking, Stargate,
+};
+use cw_storage_plus::Item;
+use palomadex::rate_provider::{ExchangeRateResponse, QueryMsg};
+use serde::de::DeserializeOwned;
+
+use crate::{palomadex_address, WKApp, PALOMADEX};
+
+/// The exchange rate reported by the mock rate provider
+const RATE: Item<ExchangeRateResponse> = Item::new("rate");
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub rate: Decimal,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Sets the reported rate, the rate is considered updated at the current block time
+    SetRate { rate: Decimal },
+    /// Sets the reported rate along with the time it was updated at
+    SetRateUpdatedAt { rate: Decimal, updated_at: u64 },
+}
+
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    RATE.save(
+        deps.storage,
+        &ExchangeRateResponse {
+            rate: msg.rate,
+            updated_at: env.block.time.seconds(),
+        },
+    )?;
+
+    Ok(Response::new())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response> {
+    let (rate, updated_at) = match msg {
+        ExecuteMsg::SetRate { rate } => (rate, env.block.time.seconds()),
+        ExecuteMsg::SetRateUpdatedAt { rate, updated_at } => (rate, updated_at),
+    };
+    RATE.save(deps.storage, &ExchangeRateResponse { rate, updated_at })?;
+
+    Ok(Response::new())
+}
+
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::ExchangeRate {} => to_json_binary(&RATE.load(deps.storage)?),
+    }
+}
+
+pub fn store_code<B, A, S, C, X, D, I, G, T>(app: &WKApp<B, A, S, C, X, D, I, G, T>) -> u64
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    T: Stargate,
+    C::ExecT: CustomMsg + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    let contract = Box::new(ContractWrapper::new_with_empty(execute, instantiate, query));
+
+    app.borrow_mut().store_code(contract)
+}
+
+pub struct MockRateProviderBuilder<B, A, S, C: Module, X, D, I, G, T> {
+    pub app: WKApp<B, A, S, C, X, D, I, G, T>,
+    pub rate: Decimal,
+}
+
+impl<B, A, S, C, X, D, I, G, T> MockRateProviderBuilder<B, A, S, C, X, D, I, G, T>
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    T: Stargate,
+    C::ExecT: CustomMsg + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    pub fn new(app: &WKApp<B, A, S, C, X, D, I, G, T>) -> Self {
+        Self {
+            app: app.clone(),
+            rate: Decimal::one(),
+        }
+    }
+
+    pub fn with_rate(mut self, rate: Decimal) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn instantiate(self) -> MockRateProvider<B, A, S, C, X, D, I, G, T> {
+        let code_id = store_code(&self.app);
+
+        let address = self
+            .app
+            .borrow_mut()
+            .instantiate_contract(
+                code_id,
+                palomadex_address(),
+                &InstantiateMsg { rate: self.rate },
+                &[],
+                "Palomadex Rate Provider",
+                Some(PALOMADEX.to_owned()),
+            )
+            .unwrap();
+
+        MockRateProvider {
+            app: self.app,
+            address,
+        }
+    }
+}
+
+pub struct MockRateProvider<B, A, S, C: Module, X, D, I, G, T> {
+    pub app: WKApp<B, A, S, C, X, D, I, G, T>,
+    pub address: Addr,
+}
+
+impl<B, A, S, C, X, D, I, G, T> MockRateProvider<B, A, S, C, X, D, I, G, T>
+where
+    B: Bank,
+    A: Api,
+    S: Storage,
+    C: Module,
+    X: Staking,
+    D: Distribution,
+    I: Ibc,
+    G: Gov,
+    T: Stargate,
+    C::ExecT: CustomMsg + DeserializeOwned + 'static,
+    C::QueryT: CustomQuery + DeserializeOwned + 'static,
+{
+    pub fn set_rate(&self, rate: Decimal) {
+        self.app
+            .borrow_mut()
+            .execute_contract(
+                palomadex_address(),
+                self.address.clone(),
+                &ExecuteMsg::SetRate { rate },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn exchange_rate(&self) -> ExchangeRateResponse {
+        self.app
+            .borrow()
+            .wrap()
+            .query_wasm_smart(self.address.clone(), &QueryMsg::ExchangeRate {})
+            .unwrap()
+    }
+}